@@ -0,0 +1,135 @@
+use crate::hash::bao::{self, SliceInput, SliceVerification};
+use crate::refusal::{RefusalCode, RefusalEnvelope};
+use serde_json::{Value, json};
+use std::path::Path;
+
+const BLAKE3_PREFIX: &str = "blake3:";
+
+/// Verify `[offset, offset + length)` of `path` against a BLAKE3 `bytes_hash`
+/// and its Bao outboard. Prints one JSON line and returns the exit code:
+/// 0 verified, 1 mismatch, 2 refusal.
+pub fn handle_command(
+    path: &Path,
+    outboard: &Path,
+    bytes_hash: &str,
+    offset: u64,
+    length: Option<u64>,
+) -> u8 {
+    match verify(path, outboard, bytes_hash, offset, length) {
+        Ok(report) => {
+            let verified = report
+                .get("verified")
+                .and_then(Value::as_bool)
+                .unwrap_or(false);
+            println!("{report}");
+            if verified { 0 } else { 1 }
+        }
+        Err(refusal) => {
+            println!("{}", refusal.render());
+            2
+        }
+    }
+}
+
+fn verify(
+    path: &Path,
+    outboard: &Path,
+    bytes_hash: &str,
+    offset: u64,
+    length: Option<u64>,
+) -> Result<Value, Box<RefusalEnvelope>> {
+    let root = parse_root(bytes_hash).ok_or_else(|| {
+        Box::new(RefusalEnvelope::from_code(
+            RefusalCode::BadInput,
+            json!({
+                "flag": "--hash",
+                "error": "expected blake3:<64 hex chars>"
+            }),
+        ))
+    })?;
+
+    let file_len = std::fs::metadata(path)
+        .map_err(|err| io_refusal(path, &err))?
+        .len();
+    let length = length.unwrap_or(file_len.saturating_sub(offset));
+    let in_bounds = offset
+        .checked_add(length)
+        .is_some_and(|end| end <= file_len && (offset < file_len || file_len == 0));
+    if !in_bounds {
+        return Err(Box::new(RefusalEnvelope::from_code(
+            RefusalCode::BadInput,
+            json!({
+                "offset": offset,
+                "length": length,
+                "file_len": file_len,
+                "error": "range is outside the file"
+            }),
+        )));
+    }
+
+    let outcome = bao::verify_file_slice(path, outboard, &root, offset, length).map_err(|err| {
+        let failed = match err.input {
+            SliceInput::Content => path,
+            SliceInput::Outboard => outboard,
+        };
+        io_refusal(failed, &err.error)
+    })?;
+
+    let mut report = json!({
+        "path": path.to_string_lossy(),
+        "outboard": outboard.to_string_lossy(),
+        "bytes_hash": bytes_hash,
+        "offset": offset,
+        "length": length,
+        "verified": outcome == SliceVerification::Verified,
+    });
+    match outcome {
+        SliceVerification::Verified => {}
+        SliceVerification::LengthMismatch {
+            outboard_len,
+            file_len,
+        } => {
+            report["mismatch"] = json!({
+                "reason": "length",
+                "outboard_len": outboard_len,
+                "file_len": file_len
+            });
+        }
+        SliceVerification::Mismatch { offset, length } => {
+            report["mismatch"] = json!({
+                "reason": "content",
+                "offset": offset,
+                "length": length
+            });
+        }
+        SliceVerification::OutboardSize {
+            outboard_bytes,
+            expected_bytes,
+        } => {
+            report["mismatch"] = json!({
+                "reason": "outboard",
+                "outboard_bytes": outboard_bytes,
+                "expected_bytes": expected_bytes
+            });
+        }
+    }
+    Ok(report)
+}
+
+fn parse_root(bytes_hash: &str) -> Option<blake3::Hash> {
+    let hex = bytes_hash.strip_prefix(BLAKE3_PREFIX)?;
+    if hex.len() != 64 || !hex.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')) {
+        return None;
+    }
+    blake3::Hash::from_hex(hex).ok()
+}
+
+fn io_refusal(path: &Path, err: &std::io::Error) -> Box<RefusalEnvelope> {
+    Box::new(RefusalEnvelope::from_code(
+        RefusalCode::Io,
+        json!({
+            "path": path.to_string_lossy(),
+            "error": err.to_string()
+        }),
+    ))
+}
@@ -1,20 +1,74 @@
+use crate::hash::registry::{self, AlgorithmEntry};
 use std::fmt;
 use std::str::FromStr;
 
+/// An algorithm from the registry. Built-ins have their own variants so
+/// callers can name them directly; downstream registrations are `Registered`.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Algorithm {
     Sha256,
     Blake3,
+    Sha384,
+    Sha512,
+    Sha3_256,
+    Xxh3_128,
+    GitSha1,
+    GitSha256,
+    Registered(&'static AlgorithmEntry),
 }
 
 impl Algorithm {
-    pub fn prefix(&self) -> &'static str {
+    pub const BUILTIN: [Self; 8] = [
+        Self::Sha256,
+        Self::Blake3,
+        Self::Sha384,
+        Self::Sha512,
+        Self::Sha3_256,
+        Self::Xxh3_128,
+        Self::GitSha1,
+        Self::GitSha256,
+    ];
+
+    /// Every registered algorithm, built-ins first.
+    pub fn all() -> Vec<Self> {
+        registry::algorithms()
+    }
+
+    pub fn entry(&self) -> &'static AlgorithmEntry {
         match self {
-            Self::Sha256 => "sha256",
-            Self::Blake3 => "blake3",
+            Self::Sha256 => &registry::SHA256,
+            Self::Blake3 => &registry::BLAKE3,
+            Self::Sha384 => &registry::SHA384,
+            Self::Sha512 => &registry::SHA512,
+            Self::Sha3_256 => &registry::SHA3_256,
+            Self::Xxh3_128 => &registry::XXH3_128,
+            Self::GitSha1 => &registry::GIT_SHA1,
+            Self::GitSha256 => &registry::GIT_SHA256,
+            Self::Registered(entry) => entry,
         }
     }
 
+    /// Name accepted by `--algorithm` and recorded as `hash_algorithm`.
+    pub fn name(&self) -> &'static str {
+        self.entry().name
+    }
+
+    /// Prefix written in front of the hex digest in `bytes_hash`.
+    pub fn prefix(&self) -> &'static str {
+        self.entry().prefix
+    }
+
+    /// Non-cryptographic digests are fine for scratch and caching work but
+    /// must never be treated as identity by downstream tools.
+    pub fn is_cryptographic(&self) -> bool {
+        self.entry().cryptographic
+    }
+
+    /// Number of lowercase hex characters in a digest after the prefix.
+    pub fn digest_hex_len(&self) -> usize {
+        self.entry().digest_hex_len
+    }
+
     pub fn format_bytes_hash(&self, digest_hex: &str) -> String {
         format!("{}:{}", self.prefix(), digest_hex.to_ascii_lowercase())
     }
@@ -24,27 +78,42 @@ impl FromStr for Algorithm {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.eq_ignore_ascii_case("sha256") {
-            Ok(Self::Sha256)
-        } else if s.eq_ignore_ascii_case("blake3") {
-            Ok(Self::Blake3)
-        } else {
-            Err(format!(
-                "Invalid algorithm '{s}'. Expected one of: sha256, blake3"
-            ))
+        registry::lookup(s).ok_or_else(|| {
+            let expected = Self::all()
+                .iter()
+                .map(Algorithm::name)
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("Invalid algorithm '{s}'. Expected one of: {expected}")
+        })
+    }
+}
+
+/// Parse a comma-separated `--algorithm` value such as `sha256,blake3`.
+/// The first entry is the primary algorithm that populates `bytes_hash` and
+/// `hash_algorithm`; every entry is computed in the same read pass.
+pub fn parse_algorithm_list(s: &str) -> Result<Vec<Algorithm>, String> {
+    let mut algorithms = Vec::new();
+    for entry in s.split(',') {
+        let algorithm = Algorithm::from_str(entry.trim())?;
+        if algorithms.contains(&algorithm) {
+            return Err(format!("Duplicate algorithm '{algorithm}' in '{s}'"));
         }
+        algorithms.push(algorithm);
     }
+
+    Ok(algorithms)
 }
 
 impl fmt::Display for Algorithm {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.prefix())
+        write!(f, "{}", self.name())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Algorithm;
+    use super::{Algorithm, parse_algorithm_list};
     use std::str::FromStr;
 
     #[test]
@@ -63,6 +132,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parses_sha2_and_sha3_variants() {
+        assert_eq!(
+            Algorithm::from_str("SHA512").expect("parse SHA512"),
+            Algorithm::Sha512
+        );
+        assert_eq!(
+            Algorithm::from_str("sha384").expect("parse sha384"),
+            Algorithm::Sha384
+        );
+        assert_eq!(
+            Algorithm::from_str("SHA3-256").expect("parse SHA3-256"),
+            Algorithm::Sha3_256
+        );
+        assert_eq!(Algorithm::Sha384.digest_hex_len(), 96);
+        assert_eq!(Algorithm::Sha512.digest_hex_len(), 128);
+    }
+
+    #[test]
+    fn xxh3_uses_short_prefix_and_is_not_cryptographic() {
+        let algorithm = Algorithm::from_str("XXH3-128").expect("parse XXH3-128");
+        assert_eq!(algorithm, Algorithm::Xxh3_128);
+        assert_eq!(algorithm.name(), "xxh3-128");
+        assert_eq!(algorithm.format_bytes_hash("ABCD"), "xxh3:abcd");
+        assert!(!algorithm.is_cryptographic());
+        assert!(Algorithm::Sha256.is_cryptographic());
+        assert!(Algorithm::from_str("xxh3").is_err());
+    }
+
     #[test]
     fn rejects_invalid_algorithm_names() {
         let error = Algorithm::from_str("md5").expect_err("md5 must be rejected");
@@ -75,4 +173,23 @@ mod tests {
         let bytes_hash = Algorithm::Blake3.format_bytes_hash("ABCDEF1234");
         assert_eq!(bytes_hash, "blake3:abcdef1234");
     }
+
+    #[test]
+    fn parses_comma_separated_algorithm_lists_in_order() {
+        assert_eq!(
+            parse_algorithm_list("sha256").expect("single algorithm"),
+            vec![Algorithm::Sha256]
+        );
+        assert_eq!(
+            parse_algorithm_list("BLAKE3, sha256").expect("two algorithms"),
+            vec![Algorithm::Blake3, Algorithm::Sha256]
+        );
+    }
+
+    #[test]
+    fn rejects_duplicate_and_empty_list_entries() {
+        let duplicate = parse_algorithm_list("sha256,SHA256").expect_err("duplicate rejected");
+        assert!(duplicate.contains("Duplicate"));
+        assert!(parse_algorithm_list("sha256,").is_err());
+    }
 }
@@ -12,20 +12,116 @@ pub struct Cli {
     /// JSONL manifest file (default: stdin)
     pub input: Option<PathBuf>,
 
-    /// Hash algorithm: sha256 or blake3
+    /// Hash algorithm: sha256, blake3, sha384, sha512, sha3-256, xxh3-128 (non-cryptographic), git-sha1 or git-sha256 (git blob ids); comma-separate to compute several in one pass
     #[arg(long, default_value = "sha256")]
     pub algorithm: String,
 
-    /// Number of parallel workers (default: CPU count)
+    /// BLAKE3 keyed mode: file holding a 32-byte key (raw or 64 hex chars)
+    #[arg(long, value_name = "PATH", conflicts_with = "blake3_context")]
+    pub blake3_key_file: Option<PathBuf>,
+
+    /// BLAKE3 derive-key mode using this context string
+    #[arg(long, value_name = "CONTEXT")]
+    pub blake3_context: Option<String>,
+
+    /// BLAKE3 output length in bytes (16-64); other than 32 uses extended output
+    #[arg(long, value_name = "N")]
+    pub digest_length: Option<usize>,
+
+    /// Write a Bao outboard per file into this directory (requires blake3)
+    #[arg(long, value_name = "DIR")]
+    pub bao_outboard: Option<PathBuf>,
+
+    /// Also record per-chunk hashes and a chunk-tree root (e.g. 64MiB)
+    #[arg(long, value_name = "SIZE")]
+    pub chunk_size: Option<String>,
+
+    /// Also record FastCDC content-defined chunks and a manifest dedup summary
+    #[arg(long)]
+    pub cdc: bool,
+
+    /// Also write the primary digest as bytes_hash_encoded: hex, sri, multibase-base32 or multihash
+    #[arg(long, value_name = "ENC")]
+    pub encoding: Option<String>,
+
+    /// Also hash the decoded payload of compressed files: comma-separated gzip, zstd, xz, bz2
+    #[arg(long, value_name = "FORMATS")]
+    pub inner_hash: Option<String>,
+
+    /// Also record object_store_checksums: S3 ETag, base64 CRC32C and CRC64NVME
+    #[arg(long)]
+    pub object_store_checksums: bool,
+
+    /// Part size for the S3 multipart ETag (default 8MiB; implies --object-store-checksums)
+    #[arg(long, value_name = "SIZE")]
+    pub s3_part_size: Option<String>,
+
+    /// Also record the IPFS UnixFS CIDv1 (256KiB chunks, raw leaves, balanced DAG) as ipfs_cid
+    #[arg(long)]
+    pub ipfs_cid: bool,
+
+    /// Also record the fs-verity file digest (SHA-256, 4KiB blocks) as fsverity_digest
+    #[arg(long)]
+    pub fsverity: bool,
+
+    /// Triage mode: hash only size, both ends and evenly spaced windows into sample_hash; bytes_hash stays null and records are marked non_identity
+    #[arg(long)]
+    pub sample_hash: bool,
+
+    /// Bytes sampled at each end of a file with --sample-hash (default 1MiB)
+    #[arg(long, value_name = "SIZE")]
+    pub sample_edge: Option<String>,
+
+    /// Number of evenly spaced windows sampled with --sample-hash (default 16)
+    #[arg(long, value_name = "K")]
+    pub sample_windows: Option<u64>,
+
+    /// Length of each --sample-hash window (default 64KiB)
+    #[arg(long, value_name = "SIZE")]
+    pub sample_window_size: Option<String>,
+
+    /// Hash only possible duplicates: unique sizes, then unique first-block XXH3 prefixes, skip the full hash; records carry dedup_tier
+    #[arg(long)]
+    pub dedup_only: bool,
+
+    /// Mark records whose bytes_hash repeats with duplicate_group, duplicate_count and first_path (output is held until the manifest ends)
+    #[arg(long)]
+    pub annotate_duplicates: bool,
+
+    /// Mark each record change: unchanged, modified or new against a previous hash.v0 manifest (by relative_path or path); its unmatched records follow as change: removed
+    #[arg(long, value_name = "PATH")]
+    pub baseline: Option<PathBuf>,
+
+    /// With --baseline, leave unchanged records out of the output
+    #[arg(long)]
+    pub changed_only: bool,
+
+    /// Write checksum-file lines instead of hash.v0 JSONL: gnu (`<hex>  <name>`, as sha256sum/b3sum print) or bsd (`SHA256 (<name>) = <hex>`)
+    #[arg(long, value_name = "FORMAT")]
+    pub emit_sums: Option<String>,
+
+    /// Hash directory records as git tree ids built from the manifest's file records (requires git-sha1 or git-sha256 as primary)
     #[arg(long)]
+    pub git_tree: bool,
+
+    /// Also emit a record per member of zip and tar (optionally gzip/zstd/xz/bz2) archives, hashed without extraction
+    #[arg(long)]
+    pub expand_archives: bool,
+
+    /// Re-read a file up to N more times when its size, mtime, ctime or inode changes during hashing; then skip it with E_CHANGED_DURING_HASH [default: 2]
+    #[arg(long, value_name = "N")]
+    pub change_retries: Option<u32>,
+
+    /// Number of parallel workers (default: CPU count)
+    #[arg(long, global = true)]
     pub jobs: Option<usize>,
 
     /// Suppress witness ledger recording
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub no_witness: bool,
 
     /// Emit progress to stderr
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub progress: bool,
 
     /// Print operator.json and exit
@@ -44,6 +140,51 @@ pub enum Command {
         #[command(subcommand)]
         action: WitnessAction,
     },
+    /// Re-hash every record of a hash.v0 manifest with its hash_algorithm and add verification: match, mismatch, missing, unreadable or unverifiable (keyed and derive-key BLAKE3 records need --blake3-key-file or --blake3-context)
+    Verify {
+        /// hash.v0 JSONL manifest to check; with --lockfile, a listing of what is on disk now (vacuum or hash.v0) whose unpinned paths are reported as added
+        #[arg(required_unless_present = "lockfile")]
+        manifest: Option<PathBuf>,
+
+        /// Check the artifacts pinned in a `lock` lockfile instead, adding drift: changed or missing
+        #[arg(long)]
+        lockfile: Option<PathBuf>,
+    },
+    /// Verify the files listed in a GNU or BSD-tag checksum file (sha256sum, b3sum, ...) and emit hash.v0 records with verification
+    Check {
+        /// Checksum file to read, e.g. SHA256SUMS
+        sums: PathBuf,
+
+        /// Algorithm of untagged lines (default: from the file name, else the digest length)
+        #[arg(long)]
+        algorithm: Option<String>,
+    },
+    /// Report groups of records sharing a bytes_hash in a hash.v0 manifest, with reclaimable bytes
+    Duplicates {
+        /// hash.v0 JSONL manifest to read
+        manifest: PathBuf,
+    },
+    /// Verify a byte range of a file against its BLAKE3 bytes_hash and Bao outboard
+    BaoVerify {
+        /// File whose bytes are checked
+        path: PathBuf,
+
+        /// Outboard written by --bao-outboard
+        #[arg(long)]
+        outboard: PathBuf,
+
+        /// Expected bytes_hash (blake3:<hex>)
+        #[arg(long)]
+        hash: String,
+
+        /// First byte of the range
+        #[arg(long, default_value_t = 0)]
+        offset: u64,
+
+        /// Range length in bytes (default: to end of file)
+        #[arg(long)]
+        length: Option<u64>,
+    },
     /// Inspect hashbytes health and agent-facing capabilities
     Doctor {
         /// Emit one machine-readable triage report
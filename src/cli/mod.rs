@@ -1,7 +1,9 @@
 pub mod algorithm;
 pub mod args;
 pub mod exit;
+pub mod size;
 
-pub use algorithm::Algorithm;
+pub use algorithm::{Algorithm, parse_algorithm_list};
 pub use args::{Cli, Command, DoctorAction, WitnessAction};
 pub use exit::{Outcome, exit_code};
+pub use size::parse_byte_size;
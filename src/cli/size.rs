@@ -0,0 +1,55 @@
+/// Parse a byte size such as `65536`, `64KiB`, `64MiB` or `1GiB`. Suffixes are
+/// binary (`K`/`KiB` = 1024) and case-insensitive; zero is rejected.
+pub fn parse_byte_size(value: &str) -> Result<u64, String> {
+    let trimmed = value.trim();
+    let digits_end = trimmed
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(trimmed.len());
+    let (digits, suffix) = trimmed.split_at(digits_end);
+
+    let multiplier: u64 = match suffix.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kib" => 1 << 10,
+        "m" | "mib" => 1 << 20,
+        "g" | "gib" => 1 << 30,
+        other => {
+            return Err(format!(
+                "unknown size suffix '{other}' (expected KiB, MiB or GiB)"
+            ));
+        }
+    };
+
+    let count: u64 = digits
+        .parse()
+        .map_err(|_| format!("invalid size '{value}'"))?;
+    let size = count
+        .checked_mul(multiplier)
+        .ok_or_else(|| format!("size '{value}' is too large"))?;
+    if size == 0 {
+        return Err("size must be greater than zero".to_owned());
+    }
+    Ok(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_byte_size;
+
+    #[test]
+    fn parses_plain_and_binary_suffixed_sizes() {
+        assert_eq!(parse_byte_size("4096"), Ok(4096));
+        assert_eq!(parse_byte_size("64KiB"), Ok(64 * 1024));
+        assert_eq!(parse_byte_size("64MiB"), Ok(64 * 1024 * 1024));
+        assert_eq!(parse_byte_size("1gib"), Ok(1024 * 1024 * 1024));
+        assert_eq!(parse_byte_size("8 M"), Ok(8 * 1024 * 1024));
+    }
+
+    #[test]
+    fn rejects_zero_unknown_suffix_and_garbage() {
+        assert!(parse_byte_size("0MiB").is_err());
+        assert!(parse_byte_size("64MB").is_err());
+        assert!(parse_byte_size("MiB").is_err());
+        assert!(parse_byte_size("").is_err());
+        assert!(parse_byte_size("99999999999999GiB").is_err());
+    }
+}
@@ -6,6 +6,7 @@ const HEALTH_SCHEMA: &str = "hashbytes.doctor.health.v1";
 const CAPABILITIES_SCHEMA: &str = "hashbytes.doctor.capabilities.v1";
 const TRIAGE_SCHEMA: &str = "hashbytes.doctor.triage.v1";
 const READ_ONLY_CONTRACT: &str = "cmdrvl.read_only_doctor.v1";
+const BYTES_HASH_PATTERN: &str = "^((sha256|blake3|sha3-256|gitsha256):[a-f0-9]{64}|sha384:[a-f0-9]{96}|sha512:[a-f0-9]{128}|xxh3:[a-f0-9]{32}|gitsha1:[a-f0-9]{40}|blake3-(keyed|derive|xof):([a-f0-9]{2}){16,64})$";
 
 pub fn handle_command(
     action: Option<&cli::DoctorAction>,
@@ -164,7 +165,11 @@ fn capabilities_report() -> Value {
         },
         "hashbytes_capabilities": {
             "streaming_jsonl": true,
-            "algorithms": ["sha256", "blake3"],
+            "algorithms": cli::Algorithm::all()
+                .iter()
+                .map(cli::Algorithm::name)
+                .collect::<Vec<_>>(),
+            "non_cryptographic_algorithms": non_cryptographic_algorithms(),
             "operator_describe": true,
             "schema_describe": true,
             "witness_query": true,
@@ -234,8 +239,9 @@ fn operator_manifest_check() -> Value {
     }
 }
 
+const SCHEMA: &str = include_str!("../schema/hash.v0.schema.json");
+
 fn schema_contract_check() -> Value {
-    const SCHEMA: &str = include_str!("../schema/hash.v0.schema.json");
     let parsed = serde_json::from_str::<Value>(SCHEMA);
 
     match parsed {
@@ -255,8 +261,24 @@ fn schema_contract_check() -> Value {
                 .and_then(|properties| properties.get("bytes_hash"))
                 .and_then(|bytes_hash| bytes_hash.get("pattern"))
                 .and_then(Value::as_str)
-                == Some("^(sha256|blake3):[a-f0-9]{64}$");
-            let ok = title_ok && required_ok && hash_pattern_ok;
+                == Some(BYTES_HASH_PATTERN);
+            let non_cryptographic_marker_ok = value
+                .get("properties")
+                .and_then(|properties| properties.get("non_cryptographic"))
+                .and_then(|marker| marker.get("const"))
+                .and_then(Value::as_bool)
+                == Some(true);
+            let non_identity_marker_ok = value
+                .get("properties")
+                .and_then(|properties| properties.get("non_identity"))
+                .and_then(|marker| marker.get("const"))
+                .and_then(Value::as_bool)
+                == Some(true);
+            let ok = title_ok
+                && required_ok
+                && hash_pattern_ok
+                && non_cryptographic_marker_ok
+                && non_identity_marker_ok;
 
             json!({
                 "name": "schema_contract",
@@ -264,7 +286,9 @@ fn schema_contract_check() -> Value {
                 "details": {
                     "title": title_ok,
                     "required_fields": required_ok,
-                    "bytes_hash_pattern": hash_pattern_ok
+                    "bytes_hash_pattern": hash_pattern_ok,
+                    "non_cryptographic_marker": non_cryptographic_marker_ok,
+                    "non_identity_marker": non_identity_marker_ok
                 }
             })
         }
@@ -282,7 +306,28 @@ fn algorithm_contract_check() -> Value {
     let sha256_ok = cli::Algorithm::from_str("sha256").is_ok();
     let blake3_ok = cli::Algorithm::from_str("BLAKE3").is_ok();
     let rejects_unknown = cli::Algorithm::from_str("md5").is_err();
-    let ok = sha256_ok && blake3_ok && rejects_unknown;
+    let all_parse = cli::Algorithm::all()
+        .iter()
+        .all(|algorithm| cli::Algorithm::from_str(algorithm.name()).ok() == Some(*algorithm));
+    // A full-width digest of every built-in algorithm must match the schema's
+    // bytes_hash pattern; downstream registrations are outside hash.v0 by
+    // definition.
+    let digest_lengths_ok = schema_bytes_hash_regex().is_some_and(|pattern| {
+        cli::Algorithm::BUILTIN.iter().all(|algorithm| {
+            pattern.is_match(&algorithm.format_bytes_hash(&"0".repeat(algorithm.digest_hex_len())))
+        })
+    });
+    let builtin_names: Vec<String> = cli::Algorithm::BUILTIN
+        .iter()
+        .map(|algorithm| algorithm.name().to_owned())
+        .collect();
+    let operator_choices_ok = crate::operator_algorithm_choices() == Some(builtin_names);
+    let ok = sha256_ok
+        && blake3_ok
+        && rejects_unknown
+        && all_parse
+        && digest_lengths_ok
+        && operator_choices_ok;
 
     json!({
         "name": "algorithm_contract",
@@ -290,11 +335,34 @@ fn algorithm_contract_check() -> Value {
         "details": {
             "sha256": sha256_ok,
             "blake3_case_insensitive": blake3_ok,
-            "rejects_unknown": rejects_unknown
+            "rejects_unknown": rejects_unknown,
+            "all_algorithms_parse": all_parse,
+            "digest_lengths_in_schema": digest_lengths_ok,
+            "operator_choices_match_registry": operator_choices_ok,
+            "non_identity_grade": non_cryptographic_algorithms()
         }
     })
 }
 
+/// The compiled `bytes_hash` pattern of the bundled hash.v0 schema.
+fn schema_bytes_hash_regex() -> Option<regex::Regex> {
+    let schema: Value = serde_json::from_str(SCHEMA).ok()?;
+    let pattern = schema
+        .get("properties")?
+        .get("bytes_hash")?
+        .get("pattern")?
+        .as_str()?;
+    regex::Regex::new(pattern).ok()
+}
+
+fn non_cryptographic_algorithms() -> Vec<&'static str> {
+    cli::Algorithm::all()
+        .iter()
+        .filter(|algorithm| !algorithm.is_cryptographic())
+        .map(cli::Algorithm::name)
+        .collect()
+}
+
 fn stream_contract_check() -> Value {
     json!({
         "name": "stream_contract",
@@ -399,3 +467,18 @@ fn print_json(value: &Value) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schema_pattern_checks_digest_width_per_prefix() {
+        let pattern = schema_bytes_hash_regex().expect("schema bytes_hash pattern");
+        assert!(pattern.is_match(&format!("sha256:{}", "0".repeat(64))));
+        assert!(!pattern.is_match(&format!("sha256:{}", "0".repeat(63))));
+        assert!(!pattern.is_match(&format!("sha256:{}", "0".repeat(128))));
+        assert!(pattern.is_match(&format!("gitsha1:{}", "0".repeat(40))));
+        assert!(!pattern.is_match(&format!("gitsha1:{}", "0".repeat(64))));
+    }
+}
@@ -0,0 +1,74 @@
+use crate::pipeline::duplicates::DuplicateIndex;
+use crate::pipeline::reader::{parse_json_line, read_line};
+use crate::refusal::{RefusalCode, RefusalEnvelope};
+use serde_json::{Value, json};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+/// Group the records of a hash.v0 manifest by `bytes_hash`. Prints one JSON
+/// line with every duplicate group and the bytes reclaimable by keeping one
+/// copy of each, and returns the exit code: 0 reported, 2 refusal.
+pub fn handle_command(manifest: &Path) -> u8 {
+    match report(manifest) {
+        Ok(report) => {
+            println!("{report}");
+            0
+        }
+        Err(refusal) => {
+            println!("{}", refusal.render());
+            2
+        }
+    }
+}
+
+fn report(manifest: &Path) -> Result<Value, Box<RefusalEnvelope>> {
+    let file = File::open(manifest).map_err(|err| io_refusal(manifest, &err))?;
+    let mut reader = BufReader::new(file);
+    let mut buffer = String::new();
+    let mut line_number = 0;
+    let mut records = 0usize;
+    let mut index = DuplicateIndex::default();
+
+    while read_line(&mut reader, &mut buffer).map_err(|err| io_refusal(manifest, &err))? > 0 {
+        line_number += 1;
+        if buffer.trim().is_empty() {
+            continue;
+        }
+        let parsed_line = parse_json_line(&buffer, line_number)?;
+        index.add_record(&parsed_line.record);
+        records += 1;
+    }
+
+    let groups: Vec<Value> = index
+        .duplicate_groups()
+        .map(|group| group.to_json())
+        .collect();
+    let duplicate_records: usize = index
+        .duplicate_groups()
+        .map(|group| group.paths.len())
+        .sum();
+    let reclaimable_bytes: u64 = index
+        .duplicate_groups()
+        .map(|group| group.reclaimable_bytes())
+        .sum();
+
+    Ok(json!({
+        "manifest": manifest.to_string_lossy(),
+        "records": records,
+        "duplicate_groups": groups.len(),
+        "duplicate_records": duplicate_records,
+        "reclaimable_bytes": reclaimable_bytes,
+        "groups": groups,
+    }))
+}
+
+fn io_refusal(path: &Path, err: &std::io::Error) -> Box<RefusalEnvelope> {
+    Box::new(RefusalEnvelope::from_code(
+        RefusalCode::Io,
+        json!({
+            "path": path.to_string_lossy(),
+            "error": err.to_string()
+        }),
+    ))
+}
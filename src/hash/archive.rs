@@ -0,0 +1,227 @@
+use super::compute::{FileDigests, HashOptions, hash_reader_with_options};
+use super::inner::Compression;
+use std::fs::File;
+use std::io::{self, BufReader, Cursor, Read, Seek, SeekFrom};
+use std::path::Path;
+
+const TAR_BLOCK: usize = 512;
+const ZIP_MAGIC: [&[u8]; 2] = [b"PK\x03\x04", b"PK\x05\x06"];
+
+/// Archive container recognized by `--expand-archives`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Zip,
+    /// A tar stream, optionally inside one of the `--inner-hash` compressions.
+    Tar(Option<Compression>),
+}
+
+impl ArchiveFormat {
+    pub fn label(&self) -> String {
+        match self {
+            Self::Zip => "zip".to_owned(),
+            Self::Tar(None) => "tar".to_owned(),
+            Self::Tar(Some(compression)) => format!("tar+{compression}"),
+        }
+    }
+}
+
+/// One regular-file member and the digests of its bytes.
+#[derive(Debug)]
+pub struct MemberDigests {
+    /// Member name as stored in the archive, without a leading `./` or `/`.
+    pub name: String,
+    pub size: u64,
+    pub digests: io::Result<FileDigests>,
+}
+
+/// Every member hashed before the archive ended or became unreadable.
+#[derive(Debug)]
+pub struct ArchiveListing {
+    pub format: ArchiveFormat,
+    pub members: Vec<MemberDigests>,
+    /// Why listing stopped early, if it did; `members` is complete otherwise.
+    pub error: Option<String>,
+}
+
+/// Hash each regular-file member of the archive at `path` in archive order,
+/// streaming member bytes straight into the hashers without extracting them.
+/// Returns `None` when the file is not a zip or (compressed) tar archive.
+pub fn hash_archive_members(
+    path: &Path,
+    options: &HashOptions,
+) -> io::Result<Option<ArchiveListing>> {
+    let mut file = File::open(path)?;
+    let mut head = Vec::with_capacity(TAR_BLOCK);
+    (&mut file).take(TAR_BLOCK as u64).read_to_end(&mut head)?;
+
+    if ZIP_MAGIC.iter().any(|magic| head.starts_with(magic)) {
+        file.seek(SeekFrom::Start(0))?;
+        return Ok(Some(hash_zip_members(file, options)));
+    }
+
+    file.seek(SeekFrom::Start(0))?;
+    let reader = BufReader::with_capacity(super::stream::BUFFER_SIZE, file);
+    match Compression::detect(&head, &Compression::ALL) {
+        Some(compression) => {
+            let Ok(decoder) = compression.decoder(reader) else {
+                return Ok(None);
+            };
+            hash_tar_members(decoder, Some(compression), options)
+        }
+        None => hash_tar_members(reader, None, options),
+    }
+}
+
+fn hash_zip_members(file: File, options: &HashOptions) -> ArchiveListing {
+    let mut listing = ArchiveListing {
+        format: ArchiveFormat::Zip,
+        members: Vec::new(),
+        error: None,
+    };
+    let mut archive = match zip::ZipArchive::new(BufReader::new(file)) {
+        Ok(archive) => archive,
+        Err(err) => {
+            listing.error = Some(err.to_string());
+            return listing;
+        }
+    };
+
+    for index in 0..archive.len() {
+        let name = archive
+            .name_for_index(index)
+            .map(member_name)
+            .unwrap_or_default();
+        let mut member = match archive.by_index(index) {
+            Ok(member) => member,
+            // Encrypted or unsupported members fail alone; the rest still hash.
+            Err(zip::result::ZipError::UnsupportedArchive(reason)) => {
+                listing.members.push(MemberDigests {
+                    name,
+                    size: 0,
+                    digests: Err(io::Error::new(io::ErrorKind::Unsupported, reason)),
+                });
+                continue;
+            }
+            Err(err) => {
+                listing.error = Some(err.to_string());
+                return listing;
+            }
+        };
+        if !member.is_file() {
+            continue;
+        }
+        let size = member.size();
+        let digests = hash_member(&mut member, size, options);
+        listing.members.push(MemberDigests {
+            name,
+            size,
+            digests,
+        });
+    }
+    listing
+}
+
+fn hash_tar_members<R: Read>(
+    mut reader: R,
+    compression: Option<Compression>,
+    options: &HashOptions,
+) -> io::Result<Option<ArchiveListing>> {
+    // A tar stream is recognized by the ustar magic in its first header.
+    let mut head = Vec::with_capacity(TAR_BLOCK);
+    if (&mut reader)
+        .take(TAR_BLOCK as u64)
+        .read_to_end(&mut head)
+        .is_err()
+        || head.len() < TAR_BLOCK
+        || &head[257..262] != b"ustar"
+    {
+        return Ok(None);
+    }
+
+    let mut listing = ArchiveListing {
+        format: ArchiveFormat::Tar(compression),
+        members: Vec::new(),
+        error: None,
+    };
+    let mut archive = tar::Archive::new(Cursor::new(head).chain(reader));
+    let entries = match archive.entries() {
+        Ok(entries) => entries,
+        Err(err) => {
+            listing.error = Some(err.to_string());
+            return Ok(Some(listing));
+        }
+    };
+
+    for entry in entries {
+        let mut entry = match entry {
+            Ok(entry) => entry,
+            Err(err) => {
+                listing.error = Some(err.to_string());
+                break;
+            }
+        };
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let name = member_name(&String::from_utf8_lossy(&entry.path_bytes()));
+        let size = entry.size();
+        let digests = hash_member(&mut entry, size, options);
+        // A short member means the archive itself is truncated.
+        if let Err(err) = &digests
+            && err.kind() == io::ErrorKind::UnexpectedEof
+        {
+            listing.error = Some(err.to_string());
+            break;
+        }
+        listing.members.push(MemberDigests {
+            name,
+            size,
+            digests,
+        });
+    }
+    Ok(Some(listing))
+}
+
+/// Hash exactly `size` bytes of a member. Archive readers simply stop when
+/// the container ends early, so a short member is turned into an error
+/// rather than a digest of the wrong bytes.
+fn hash_member<R: Read>(member: R, size: u64, options: &HashOptions) -> io::Result<FileDigests> {
+    let mut exact = ExactReader {
+        inner: member,
+        remaining: size,
+    };
+    let digests = hash_reader_with_options(&mut exact, size, options)?;
+    if exact.remaining > 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!("archive member ended {} bytes early", exact.remaining),
+        ));
+    }
+    Ok(digests)
+}
+
+struct ExactReader<R> {
+    inner: R,
+    remaining: u64,
+}
+
+impl<R: Read> Read for ExactReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.remaining = self.remaining.saturating_sub(read as u64);
+        Ok(read)
+    }
+}
+
+fn member_name(name: &str) -> String {
+    let mut name = name;
+    loop {
+        if let Some(rest) = name.strip_prefix("./") {
+            name = rest;
+        } else if let Some(rest) = name.strip_prefix('/') {
+            name = rest;
+        } else {
+            return name.to_owned();
+        }
+    }
+}
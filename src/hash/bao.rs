@@ -0,0 +1,407 @@
+use blake3::hazmat::{
+    ChainingValue, HasherExt, Mode, left_subtree_len, merge_subtrees_non_root, merge_subtrees_root,
+};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+pub const CHUNK_LEN: u64 = blake3::CHUNK_LEN as u64;
+pub const HEADER_LEN: u64 = 8;
+pub const PARENT_LEN: u64 = 64;
+pub const OUTBOARD_EXTENSION: &str = "obao";
+
+/// Subtrees up to this size are read and encoded in memory, so the writer only
+/// seeks back for the few parent nodes above them.
+const IN_MEMORY_SUBTREE_LEN: u64 = 1024 * 1024;
+
+static TEMP_OUTBOARD_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Size in bytes of the Bao outboard for content of `content_len` bytes: the
+/// length header plus one 64-byte parent node per chunk after the first.
+pub fn outboard_size(content_len: u64) -> u64 {
+    HEADER_LEN + PARENT_LEN * (chunk_count(content_len) - 1)
+}
+
+fn chunk_count(content_len: u64) -> u64 {
+    content_len.div_ceil(CHUNK_LEN).max(1)
+}
+
+/// Write a standard Bao outboard (length header, then parent nodes in
+/// pre-order) for exactly `content_len` bytes read from `input`, returning
+/// the BLAKE3 root hash. Input is consumed strictly front to back.
+pub fn encode_outboard<R, W>(
+    input: &mut R,
+    content_len: u64,
+    output: &mut W,
+) -> io::Result<blake3::Hash>
+where
+    R: Read,
+    W: Write + Seek,
+{
+    output.write_all(&content_len.to_le_bytes())?;
+
+    if content_len <= CHUNK_LEN {
+        let data = read_exact_len(input, content_len)?;
+        return Ok(blake3::hash(&data));
+    }
+
+    let (left_cv, right_cv) = encode_children(input, 0, content_len, output)?;
+    Ok(merge_subtrees_root(&left_cv, &right_cv, Mode::Hash))
+}
+
+/// Encode the two children of a parent node covering `len` bytes at `offset`,
+/// filling the parent slot reserved at the current output position.
+fn encode_children<R, W>(
+    input: &mut R,
+    offset: u64,
+    len: u64,
+    output: &mut W,
+) -> io::Result<(ChainingValue, ChainingValue)>
+where
+    R: Read,
+    W: Write + Seek,
+{
+    let parent_position = output.stream_position()?;
+    output.write_all(&[0_u8; PARENT_LEN as usize])?;
+
+    let left_len = left_subtree_len(len);
+    let left_cv = encode_subtree(input, offset, left_len, output)?;
+    let right_cv = encode_subtree(input, offset + left_len, len - left_len, output)?;
+
+    let end_position = output.stream_position()?;
+    output.seek(SeekFrom::Start(parent_position))?;
+    output.write_all(&left_cv)?;
+    output.write_all(&right_cv)?;
+    output.seek(SeekFrom::Start(end_position))?;
+
+    Ok((left_cv, right_cv))
+}
+
+fn encode_subtree<R, W>(
+    input: &mut R,
+    offset: u64,
+    len: u64,
+    output: &mut W,
+) -> io::Result<ChainingValue>
+where
+    R: Read,
+    W: Write + Seek,
+{
+    if len <= IN_MEMORY_SUBTREE_LEN {
+        let data = read_exact_len(input, len)?;
+        let mut nodes = Vec::new();
+        let cv = encode_subtree_in_memory(&data, offset, &mut nodes);
+        output.write_all(&nodes)?;
+        return Ok(cv);
+    }
+
+    let (left_cv, right_cv) = encode_children(input, offset, len, output)?;
+    Ok(merge_subtrees_non_root(&left_cv, &right_cv, Mode::Hash))
+}
+
+fn encode_subtree_in_memory(data: &[u8], offset: u64, nodes: &mut Vec<u8>) -> ChainingValue {
+    if data.len() as u64 <= CHUNK_LEN {
+        return chunk_cv(data, offset);
+    }
+
+    let parent_index = nodes.len();
+    nodes.extend_from_slice(&[0_u8; PARENT_LEN as usize]);
+
+    let left_len = left_subtree_len(data.len() as u64);
+    let (left, right) = data.split_at(left_len as usize);
+    let left_cv = encode_subtree_in_memory(left, offset, nodes);
+    let right_cv = encode_subtree_in_memory(right, offset + left_len, nodes);
+
+    nodes[parent_index..parent_index + 32].copy_from_slice(&left_cv);
+    nodes[parent_index + 32..parent_index + 64].copy_from_slice(&right_cv);
+    merge_subtrees_non_root(&left_cv, &right_cv, Mode::Hash)
+}
+
+fn chunk_cv(data: &[u8], offset: u64) -> ChainingValue {
+    blake3::Hasher::new()
+        .set_input_offset(offset)
+        .update(data)
+        .finalize_non_root()
+}
+
+fn read_exact_len<R: Read>(input: &mut R, len: u64) -> io::Result<Vec<u8>> {
+    let len = usize::try_from(len).map_err(io::Error::other)?;
+    let mut data = vec![0_u8; len];
+    input.read_exact(&mut data)?;
+    Ok(data)
+}
+
+/// Encode the outboard into `dir`, named after the BLAKE3 root hash. The tree
+/// is written to a temporary file first so a failed run never leaves a
+/// truncated outboard under a valid-looking name.
+pub fn write_outboard_file<R: Read>(
+    input: &mut R,
+    content_len: u64,
+    dir: &Path,
+) -> io::Result<(blake3::Hash, PathBuf)> {
+    let temp_path = dir.join(format!(
+        ".{}-{}.{OUTBOARD_EXTENSION}.tmp",
+        std::process::id(),
+        TEMP_OUTBOARD_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let temp_file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&temp_path)?;
+
+    let mut writer = BufWriter::new(temp_file);
+    let encoded = encode_outboard(input, content_len, &mut writer).and_then(|hash| {
+        writer.flush()?;
+        Ok(hash)
+    });
+    drop(writer);
+
+    match encoded {
+        Ok(hash) => {
+            let final_path = dir.join(format!("{}.{OUTBOARD_EXTENSION}", hash.to_hex()));
+            fs::rename(&temp_path, &final_path)?;
+            Ok((hash, final_path))
+        }
+        Err(err) => {
+            let _ = fs::remove_file(&temp_path);
+            Err(err)
+        }
+    }
+}
+
+/// Outcome of checking a byte range against an outboard and root hash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SliceVerification {
+    Verified,
+    /// The outboard header disagrees with the file length on disk.
+    LengthMismatch {
+        outboard_len: u64,
+        file_len: u64,
+    },
+    /// The subtree or chunk starting at `offset` does not hash to its parent.
+    Mismatch {
+        offset: u64,
+        length: u64,
+    },
+    /// The outboard is not the size the tree for the file needs: truncated,
+    /// padded, or too short to hold its length header.
+    OutboardSize {
+        outboard_bytes: u64,
+        expected_bytes: u64,
+    },
+}
+
+/// Which of the two files [`verify_slice`] reads failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SliceInput {
+    Content,
+    Outboard,
+}
+
+/// An I/O error from [`verify_slice`], tagged with the input it came from.
+#[derive(Debug)]
+pub struct SliceReadError {
+    pub input: SliceInput,
+    pub error: io::Error,
+}
+
+impl SliceReadError {
+    fn content(error: io::Error) -> Self {
+        Self {
+            input: SliceInput::Content,
+            error,
+        }
+    }
+
+    fn outboard(error: io::Error) -> Self {
+        Self {
+            input: SliceInput::Outboard,
+            error,
+        }
+    }
+}
+
+/// Verify bytes `[offset, offset + length)` of `content` against the BLAKE3
+/// `root` using a pre-order Bao outboard. Only the chunks overlapping the
+/// range and the parent nodes on their paths are read. An outboard of the
+/// wrong size is an [`SliceVerification::OutboardSize`] mismatch, so every
+/// error is a real read failure.
+pub fn verify_slice<C, O>(
+    content: &mut C,
+    outboard: &mut O,
+    root: &blake3::Hash,
+    offset: u64,
+    length: u64,
+) -> Result<SliceVerification, SliceReadError>
+where
+    C: Read + Seek,
+    O: Read + Seek,
+{
+    let file_len = content
+        .seek(SeekFrom::End(0))
+        .map_err(SliceReadError::content)?;
+    let outboard_bytes = outboard
+        .seek(SeekFrom::End(0))
+        .map_err(SliceReadError::outboard)?;
+    let size_mismatch = SliceVerification::OutboardSize {
+        outboard_bytes,
+        expected_bytes: outboard_size(file_len),
+    };
+    if outboard_bytes < HEADER_LEN {
+        return Ok(size_mismatch);
+    }
+
+    let mut header = [0_u8; HEADER_LEN as usize];
+    outboard
+        .seek(SeekFrom::Start(0))
+        .and_then(|_| outboard.read_exact(&mut header))
+        .map_err(SliceReadError::outboard)?;
+    let outboard_len = u64::from_le_bytes(header);
+    if outboard_len != file_len {
+        return Ok(SliceVerification::LengthMismatch {
+            outboard_len,
+            file_len,
+        });
+    }
+    if outboard_bytes != outboard_size(file_len) {
+        return Ok(size_mismatch);
+    }
+
+    let range = offset..offset.saturating_add(length).max(offset + 1);
+    let mut verifier = SliceVerifier {
+        content,
+        outboard,
+        range,
+    };
+
+    if file_len <= CHUNK_LEN {
+        let data = verifier.read_content(0, file_len)?;
+        return Ok(if blake3::hash(&data) == *root {
+            SliceVerification::Verified
+        } else {
+            SliceVerification::Mismatch {
+                offset: 0,
+                length: file_len,
+            }
+        });
+    }
+
+    let (left_cv, right_cv) = verifier.read_parent(HEADER_LEN)?;
+    if merge_subtrees_root(&left_cv, &right_cv, Mode::Hash) != *root {
+        return Ok(SliceVerification::Mismatch {
+            offset: 0,
+            length: file_len,
+        });
+    }
+    verifier.verify_children(HEADER_LEN, 0, file_len, left_cv, right_cv)
+}
+
+struct SliceVerifier<'a, C, O> {
+    content: &'a mut C,
+    outboard: &'a mut O,
+    range: std::ops::Range<u64>,
+}
+
+impl<C, O> SliceVerifier<'_, C, O>
+where
+    C: Read + Seek,
+    O: Read + Seek,
+{
+    fn verify_children(
+        &mut self,
+        parent_position: u64,
+        offset: u64,
+        len: u64,
+        left_cv: ChainingValue,
+        right_cv: ChainingValue,
+    ) -> Result<SliceVerification, SliceReadError> {
+        let left_len = left_subtree_len(len);
+        let left_position = parent_position + PARENT_LEN;
+        let right_position = left_position + PARENT_LEN * (chunk_count(left_len) - 1);
+
+        for (position, child_offset, child_len, expected) in [
+            (left_position, offset, left_len, left_cv),
+            (right_position, offset + left_len, len - left_len, right_cv),
+        ] {
+            if !self.overlaps(child_offset, child_len) {
+                continue;
+            }
+            let outcome = self.verify_subtree(position, child_offset, child_len, expected)?;
+            if outcome != SliceVerification::Verified {
+                return Ok(outcome);
+            }
+        }
+
+        Ok(SliceVerification::Verified)
+    }
+
+    fn verify_subtree(
+        &mut self,
+        position: u64,
+        offset: u64,
+        len: u64,
+        expected: ChainingValue,
+    ) -> Result<SliceVerification, SliceReadError> {
+        let mismatch = SliceVerification::Mismatch {
+            offset,
+            length: len,
+        };
+
+        if len <= CHUNK_LEN {
+            let data = self.read_content(offset, len)?;
+            return Ok(if chunk_cv(&data, offset) == expected {
+                SliceVerification::Verified
+            } else {
+                mismatch
+            });
+        }
+
+        let (left_cv, right_cv) = self.read_parent(position)?;
+        if merge_subtrees_non_root(&left_cv, &right_cv, Mode::Hash) != expected {
+            return Ok(mismatch);
+        }
+        self.verify_children(position, offset, len, left_cv, right_cv)
+    }
+
+    fn overlaps(&self, offset: u64, len: u64) -> bool {
+        offset < self.range.end && self.range.start < offset + len
+    }
+
+    fn read_parent(
+        &mut self,
+        position: u64,
+    ) -> Result<(ChainingValue, ChainingValue), SliceReadError> {
+        let mut node = [0_u8; PARENT_LEN as usize];
+        self.outboard
+            .seek(SeekFrom::Start(position))
+            .and_then(|_| self.outboard.read_exact(&mut node))
+            .map_err(SliceReadError::outboard)?;
+
+        let mut left_cv = [0_u8; 32];
+        let mut right_cv = [0_u8; 32];
+        left_cv.copy_from_slice(&node[..32]);
+        right_cv.copy_from_slice(&node[32..]);
+        Ok((left_cv, right_cv))
+    }
+
+    fn read_content(&mut self, offset: u64, len: u64) -> Result<Vec<u8>, SliceReadError> {
+        self.content
+            .seek(SeekFrom::Start(offset))
+            .and_then(|_| read_exact_len(self.content, len))
+            .map_err(SliceReadError::content)
+    }
+}
+
+/// Open `content_path` and `outboard_path` and verify a byte range.
+pub fn verify_file_slice(
+    content_path: &Path,
+    outboard_path: &Path,
+    root: &blake3::Hash,
+    offset: u64,
+    length: u64,
+) -> Result<SliceVerification, SliceReadError> {
+    let mut content = File::open(content_path).map_err(SliceReadError::content)?;
+    let mut outboard = File::open(outboard_path).map_err(SliceReadError::outboard)?;
+    verify_slice(&mut content, &mut outboard, root, offset, length)
+}
@@ -1,21 +1,136 @@
-use std::fs::File;
-use std::io::{self, BufRead, BufReader};
+use super::compute::HashOptions;
+use crate::cli::Algorithm;
+use std::fmt;
+use std::io;
 use std::path::Path;
 
-pub fn hash_file(path: &Path) -> Result<String, io::Error> {
-    let file = File::open(path)?;
-    let mut reader = BufReader::with_capacity(64 * 1024, file); // 64 KB buffer
-
-    let mut hasher = blake3::Hasher::new();
-    loop {
-        let buf = reader.fill_buf()?;
-        if buf.is_empty() {
-            break;
+pub const DEFAULT_DIGEST_LENGTH: usize = blake3::OUT_LEN;
+pub const MIN_DIGEST_LENGTH: usize = 16;
+pub const MAX_DIGEST_LENGTH: usize = 64;
+
+/// Files at least this large are worth splitting across worker threads.
+pub const PARALLEL_MIN_LEN: u64 = 128 * 1024 * 1024;
+
+/// BLAKE3 hashing mode. Keyed and derive-key digests carry their own prefixes
+/// so downstream tools can never confuse them with plain BLAKE3.
+#[derive(Clone, PartialEq, Eq, Default)]
+pub enum Blake3Mode {
+    #[default]
+    Hash,
+    Keyed([u8; blake3::KEY_LEN]),
+    DeriveKey(String),
+}
+
+impl fmt::Debug for Blake3Mode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Hash => write!(f, "Hash"),
+            Self::Keyed(_) => write!(f, "Keyed(<redacted>)"),
+            Self::DeriveKey(context) => f.debug_tuple("DeriveKey").field(context).finish(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Blake3Options {
+    pub mode: Blake3Mode,
+    /// Output length in bytes; anything but 32 reads the extended output.
+    pub digest_length: usize,
+}
+
+impl Default for Blake3Options {
+    fn default() -> Self {
+        Self {
+            mode: Blake3Mode::Hash,
+            digest_length: DEFAULT_DIGEST_LENGTH,
+        }
+    }
+}
+
+impl Blake3Options {
+    /// Algorithm label used for both `hash_algorithm` and the digest prefix.
+    pub fn label(&self) -> &'static str {
+        match &self.mode {
+            Blake3Mode::Hash if self.digest_length == DEFAULT_DIGEST_LENGTH => "blake3",
+            Blake3Mode::Hash => "blake3-xof",
+            Blake3Mode::Keyed(_) => "blake3-keyed",
+            Blake3Mode::DeriveKey(_) => "blake3-derive",
+        }
+    }
+
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn hasher(&self) -> blake3::Hasher {
+        match &self.mode {
+            Blake3Mode::Hash => blake3::Hasher::new(),
+            Blake3Mode::Keyed(key) => blake3::Hasher::new_keyed(key),
+            Blake3Mode::DeriveKey(context) => blake3::Hasher::new_derive_key(context),
+        }
+    }
+
+    pub fn finalize(&self, hasher: &blake3::Hasher) -> String {
+        format!("{}:{}", self.label(), self.finalize_hex(hasher))
+    }
+
+    /// Lowercase hex digest of `digest_length` bytes, without a prefix.
+    pub fn finalize_hex(&self, hasher: &blake3::Hasher) -> String {
+        if self.digest_length == DEFAULT_DIGEST_LENGTH {
+            hasher.finalize().to_hex().to_string()
+        } else {
+            let mut output = vec![0_u8; self.digest_length];
+            hasher.finalize_xof().fill(&mut output);
+            output.iter().map(|byte| format!("{byte:02x}")).collect()
         }
-        hasher.update(buf);
-        let len = buf.len();
-        reader.consume(len);
     }
+}
+
+/// Parse a `--blake3-key-file` payload: either exactly 32 raw bytes or 64 hex
+/// characters with optional surrounding whitespace.
+pub fn parse_key(contents: &[u8]) -> Result<[u8; blake3::KEY_LEN], String> {
+    if let Ok(raw) = <[u8; blake3::KEY_LEN]>::try_from(contents) {
+        return Ok(raw);
+    }
+
+    let text = std::str::from_utf8(contents)
+        .map(str::trim)
+        .map_err(|_| "key must be 32 raw bytes or 64 hex characters".to_owned())?;
+    if text.len() != blake3::KEY_LEN * 2 || !text.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return Err(format!(
+            "key must be 32 raw bytes or 64 hex characters, got {} bytes",
+            contents.len()
+        ));
+    }
+
+    let mut key = [0_u8; blake3::KEY_LEN];
+    for (index, byte) in key.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&text[index * 2..index * 2 + 2], 16)
+            .map_err(|err| err.to_string())?;
+    }
+    Ok(key)
+}
+
+pub fn hash_file(path: &Path) -> Result<String, io::Error> {
+    hash_file_with(path, &Blake3Options::default())
+}
+
+/// BLAKE3 digest of the file in the mode `options` selects, labelled as the
+/// registry entry labels it.
+pub fn hash_file_with(path: &Path, options: &Blake3Options) -> Result<String, io::Error> {
+    let hash_options = HashOptions {
+        blake3: options.clone(),
+        ..HashOptions::new(vec![Algorithm::Blake3])
+    };
+    let mut digests = super::compute::hash_file_with_options(path, &hash_options)?.digests;
+    Ok(digests.remove(0))
+}
 
-    Ok(format!("blake3:{}", hasher.finalize().to_hex()))
+/// Hash a file with BLAKE3's memory-mapped, multi-threaded update. Work runs
+/// on the current Rayon pool, so calling this from inside the `--jobs` pool
+/// shares that worker budget. The digest is identical to [`hash_file_with`].
+pub fn hash_file_parallel(path: &Path, options: &Blake3Options) -> Result<String, io::Error> {
+    let mut hasher = options.hasher();
+    hasher.update_mmap_rayon(path)?;
+    Ok(options.finalize(&hasher))
 }
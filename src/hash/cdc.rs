@@ -0,0 +1,113 @@
+use fastcdc::v2020::{MASKS, cut, logarithm2};
+use std::collections::BTreeSet;
+
+/// FastCDC (2020) parameters: 16 KiB minimum, 64 KiB average and 256 KiB
+/// maximum chunk length, normalization level 1 — the crate's defaults.
+pub const MIN_CHUNK_LEN: usize = 16 * 1024;
+pub const AVG_CHUNK_LEN: usize = 64 * 1024;
+pub const MAX_CHUNK_LEN: usize = 256 * 1024;
+const NORMALIZATION_BITS: u32 = 1;
+
+/// One content-defined chunk of a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CdcChunk {
+    pub offset: u64,
+    pub length: u64,
+    /// Plain BLAKE3 of the chunk bytes, `blake3:`-prefixed.
+    pub blake3: String,
+}
+
+/// Push-style FastCDC chunker. A cut point never lies more than
+/// `MAX_CHUNK_LEN` past the previous one, so at most that much input is held
+/// back while waiting for the next boundary; results match chunking the
+/// whole file in memory.
+pub(super) struct CdcChunker {
+    pending: Vec<u8>,
+    offset: u64,
+    chunks: Vec<CdcChunk>,
+    mask_s: u64,
+    mask_l: u64,
+}
+
+impl CdcChunker {
+    pub(super) fn new() -> Self {
+        let bits = logarithm2(AVG_CHUNK_LEN as u32);
+        Self {
+            pending: Vec::with_capacity(2 * MAX_CHUNK_LEN),
+            offset: 0,
+            chunks: Vec::new(),
+            mask_s: MASKS[(bits + NORMALIZATION_BITS) as usize],
+            mask_l: MASKS[(bits - NORMALIZATION_BITS) as usize],
+        }
+    }
+
+    pub(super) fn update(&mut self, buf: &[u8]) {
+        self.pending.extend_from_slice(buf);
+        if self.pending.len() >= MAX_CHUNK_LEN {
+            self.cut_pending(MAX_CHUNK_LEN);
+        }
+    }
+
+    pub(super) fn finish(mut self) -> Vec<CdcChunk> {
+        self.cut_pending(1);
+        self.chunks
+    }
+
+    /// Emit chunks while at least `keep_below` bytes are pending.
+    fn cut_pending(&mut self, keep_below: usize) {
+        let mut start = 0;
+        while self.pending.len() - start >= keep_below {
+            let remaining = &self.pending[start..];
+            let (_, len) = cut(
+                remaining,
+                MIN_CHUNK_LEN,
+                AVG_CHUNK_LEN,
+                MAX_CHUNK_LEN,
+                self.mask_s,
+                self.mask_l,
+                self.mask_s << 1,
+                self.mask_l << 1,
+            );
+            let bytes = &remaining[..len];
+            self.chunks.push(CdcChunk {
+                offset: self.offset,
+                length: len as u64,
+                blake3: format!("blake3:{}", blake3::hash(bytes).to_hex()),
+            });
+            self.offset += len as u64;
+            start += len;
+        }
+        self.pending.drain(..start);
+    }
+}
+
+/// Running dedup totals across every chunk list in a manifest. A chunk is
+/// unique the first time its BLAKE3 digest is seen; every later occurrence
+/// counts as shared.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CdcSummary {
+    pub files: u64,
+    pub chunks: u64,
+    pub total_bytes: u64,
+    pub unique_chunks: u64,
+    pub unique_bytes: u64,
+    seen: BTreeSet<String>,
+}
+
+impl CdcSummary {
+    pub fn add(&mut self, chunks: &[CdcChunk]) {
+        self.files += 1;
+        for chunk in chunks {
+            self.chunks += 1;
+            self.total_bytes += chunk.length;
+            if self.seen.insert(chunk.blake3.clone()) {
+                self.unique_chunks += 1;
+                self.unique_bytes += chunk.length;
+            }
+        }
+    }
+
+    pub fn shared_bytes(&self) -> u64 {
+        self.total_bytes - self.unique_bytes
+    }
+}
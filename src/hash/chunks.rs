@@ -0,0 +1,102 @@
+use super::compute::HashOptions;
+use super::hasher::{ContentHasher, finalize_prefixed};
+
+/// Digests of consecutive fixed-size chunks of one file, plus the root of a
+/// binary hash tree built over them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkDigests {
+    pub chunk_size: u64,
+    /// One prefixed digest per chunk, in file order. An empty file has a
+    /// single chunk: the digest of zero bytes.
+    pub hashes: Vec<String>,
+    pub root: String,
+}
+
+/// Splits a byte stream at `chunk_size` boundaries and hashes each chunk
+/// with the primary algorithm, alongside the whole-file hashers.
+pub(super) struct ChunkHasher {
+    chunk_size: u64,
+    filled: u64,
+    /// Bytes not yet fed, so each new chunk hasher knows its exact length.
+    remaining: u64,
+    current: Box<dyn ContentHasher>,
+    hashes: Vec<String>,
+}
+
+impl ChunkHasher {
+    pub(super) fn new(chunk_size: u64, content_len: u64, options: &HashOptions) -> Self {
+        Self {
+            chunk_size,
+            filled: 0,
+            remaining: content_len,
+            current: options.new_hasher(options.primary(), content_len.min(chunk_size)),
+            hashes: Vec::new(),
+        }
+    }
+
+    pub(super) fn update(&mut self, mut buf: &[u8], options: &HashOptions) {
+        while !buf.is_empty() {
+            let room = self.chunk_size - self.filled;
+            let take = buf.len().min(usize::try_from(room).unwrap_or(usize::MAX));
+            self.current.update(&buf[..take]);
+            self.filled += take as u64;
+            self.remaining = self.remaining.saturating_sub(take as u64);
+            buf = &buf[take..];
+
+            if self.filled == self.chunk_size {
+                let next_len = self.remaining.min(self.chunk_size);
+                let next = options.new_hasher(options.primary(), next_len);
+                let full = std::mem::replace(&mut self.current, next);
+                self.hashes.push(finalize_prefixed(
+                    options.digest_prefix(options.primary()),
+                    full,
+                ));
+                self.filled = 0;
+            }
+        }
+    }
+
+    pub(super) fn finish(mut self, options: &HashOptions) -> ChunkDigests {
+        if self.filled > 0 || self.hashes.is_empty() {
+            let prefix = options.digest_prefix(options.primary());
+            self.hashes.push(finalize_prefixed(prefix, self.current));
+        }
+        let root = chunk_root(&self.hashes, options);
+        ChunkDigests {
+            chunk_size: self.chunk_size,
+            hashes: self.hashes,
+            root,
+        }
+    }
+}
+
+/// Root of the chunk tree: each parent hashes the raw digest bytes of its two
+/// children, and an unpaired node is carried up a level unchanged. A file
+/// that fits in one chunk therefore has `root == bytes_hash`.
+pub fn chunk_root(hashes: &[String], options: &HashOptions) -> String {
+    let mut level: Vec<String> = hashes.to_vec();
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => {
+                    let (left, right) = (digest_bytes(left), digest_bytes(right));
+                    let parent_len = (left.len() + right.len()) as u64;
+                    let mut parent = options.new_hasher(options.primary(), parent_len);
+                    parent.update(&left);
+                    parent.update(&right);
+                    finalize_prefixed(options.digest_prefix(options.primary()), parent)
+                }
+                _ => pair[0].clone(),
+            })
+            .collect();
+    }
+    level.into_iter().next().unwrap_or_default()
+}
+
+pub(crate) fn digest_bytes(digest: &str) -> Vec<u8> {
+    let hex = digest.rsplit_once(':').map_or(digest, |(_, hex)| hex);
+    (0..hex.len() / 2)
+        .filter_map(|i| u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).ok())
+        .collect()
+}
@@ -1,10 +1,358 @@
+use super::blake3::Blake3Options;
+use super::cdc::{CdcChunk, CdcChunker};
+use super::chunks::{ChunkDigests, ChunkHasher};
+use super::encoding::DigestEncoding;
+use super::fsverity::FsverityHasher;
+use super::hasher::{ContentHasher, finalize_prefixed};
+use super::inner::{Compression, InnerDigest};
+use super::ipfs::IpfsCidBuilder;
+use super::object_store::{ObjectStoreChecksums, ObjectStoreHasher, ObjectStoreOptions};
+use super::range::{ByteRange, open_range};
+use super::sample::SampleOptions;
 use crate::cli::Algorithm;
-use std::io;
-use std::path::Path;
+use crate::pipeline::sums::SumsFormat;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+/// Everything that determines the digests computed for one file. The first
+/// algorithm is primary: it populates `bytes_hash` and `hash_algorithm`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HashOptions {
+    pub algorithms: Vec<Algorithm>,
+    pub blake3: Blake3Options,
+    /// Directory receiving a Bao outboard per file, built from the BLAKE3 pass.
+    pub bao_outboard_dir: Option<PathBuf>,
+    /// Also hash fixed-size chunks of this many bytes with the primary algorithm.
+    pub chunk_size: Option<u64>,
+    /// Also split the file into FastCDC content-defined chunks.
+    pub cdc: bool,
+    /// Also write the primary digest in this spelling as `bytes_hash_encoded`.
+    pub encoding: Option<DigestEncoding>,
+    /// Also hash the decoded payload of files compressed in these formats.
+    pub inner_hash: Vec<Compression>,
+    /// Hash directory records as git trees of the manifest's file records.
+    pub git_tree: bool,
+    /// Also hash each member of zip and (compressed) tar archives.
+    pub expand_archives: bool,
+    /// Also compute the S3 ETag, CRC32C and CRC64NVME in the same pass.
+    pub object_store: Option<ObjectStoreOptions>,
+    /// Also compute the IPFS UnixFS CIDv1 `ipfs add --cid-version=1` assigns.
+    pub ipfs_cid: bool,
+    /// Also compute the fs-verity file digest (SHA-256, 4 KiB blocks).
+    pub fsverity: bool,
+    /// Sample files instead of reading them whole (`--sample-hash`). Such
+    /// digests are never identity and never written to `bytes_hash`.
+    pub sample: Option<SampleOptions>,
+    /// Hash only records that may have a duplicate (`--dedup-only`): unique
+    /// sizes and unique first blocks are settled without the full hash.
+    pub dedup_only: bool,
+    /// Hold output until the manifest ends and mark records whose
+    /// `bytes_hash` repeats (`--annotate-duplicates`).
+    pub annotate_duplicates: bool,
+    /// Re-hash hash.v0 records with their recorded algorithm and compare
+    /// instead of enriching them (`hashbytes verify`).
+    pub verify: bool,
+    /// Also mark verified records with `drift` (`verify --lockfile`).
+    pub lock_drift: bool,
+    /// Write checksum-file lines instead of JSONL (`--emit-sums`).
+    pub emit_sums: Option<SumsFormat>,
+    /// Previous hash.v0 manifest to mark each record's `change` against
+    /// (`--baseline`).
+    pub baseline: Option<PathBuf>,
+    /// Drop `unchanged` records from the output (`--changed-only`).
+    pub changed_only: bool,
+    /// Extra read passes for a file whose size, mtime, ctime or inode
+    /// changed during a pass, before its record is skipped.
+    pub change_retries: u32,
+    /// Whole files at least this large are hashed with multi-threaded BLAKE3
+    /// when BLAKE3 is the only output. `None` keeps every file single-threaded.
+    pub parallel_blake3_min_len: Option<u64>,
+}
+
+/// Result of one read pass over a file.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FileDigests {
+    /// One prefixed digest per requested algorithm, in request order.
+    pub digests: Vec<String>,
+    pub bao_outboard: Option<PathBuf>,
+    pub chunks: Option<ChunkDigests>,
+    pub cdc_chunks: Option<Vec<CdcChunk>>,
+    /// The range that was hashed, with its length resolved, when only part of
+    /// the file was requested.
+    pub range: Option<ByteRange>,
+    /// Digest of the decoded payload when the file is compressed and
+    /// `--inner-hash` covers its format.
+    pub inner: Option<InnerDigest>,
+    pub object_store: Option<ObjectStoreChecksums>,
+    pub ipfs_cid: Option<String>,
+    pub fsverity_digest: Option<String>,
+}
+
+impl HashOptions {
+    pub fn new(algorithms: Vec<Algorithm>) -> Self {
+        Self {
+            algorithms,
+            blake3: Blake3Options::default(),
+            bao_outboard_dir: None,
+            chunk_size: None,
+            cdc: false,
+            encoding: None,
+            inner_hash: Vec::new(),
+            git_tree: false,
+            expand_archives: false,
+            object_store: None,
+            ipfs_cid: false,
+            fsverity: false,
+            sample: None,
+            dedup_only: false,
+            annotate_duplicates: false,
+            verify: false,
+            lock_drift: false,
+            emit_sums: None,
+            baseline: None,
+            changed_only: false,
+            change_retries: super::snapshot::DEFAULT_CHANGE_RETRIES,
+            parallel_blake3_min_len: None,
+        }
+    }
+
+    pub fn primary(&self) -> Algorithm {
+        self.algorithms
+            .first()
+            .copied()
+            .unwrap_or(Algorithm::Sha256)
+    }
+
+    /// Multi-threaded BLAKE3 produces nothing but the digest, so it applies
+    /// only when no other algorithm or side output needs the same bytes.
+    fn parallel_blake3_eligible(&self) -> bool {
+        self.algorithms == [Algorithm::Blake3]
+            && self.bao_outboard_dir.is_none()
+            && self.chunk_size.is_none()
+            && !self.cdc
+            && self.object_store.is_none()
+            && !self.ipfs_cid
+            && !self.fsverity
+    }
+
+    /// Fresh hasher for `algorithm` under these options, from the registry.
+    /// `content_len` is the exact number of bytes that will be fed; object
+    /// formats such as git blobs hash it ahead of the content.
+    pub fn new_hasher(&self, algorithm: Algorithm, content_len: u64) -> Box<dyn ContentHasher> {
+        (algorithm.entry().new_hasher)(self, content_len)
+    }
+
+    /// Label recorded as `hash_algorithm` (and `hashes` key) for `algorithm`
+    /// under these options: the entry's mode label, else its name.
+    pub fn algorithm_name(&self, algorithm: Algorithm) -> &'static str {
+        let entry = algorithm.entry();
+        entry.mode_label.map_or(entry.name, |label| label(self))
+    }
+
+    /// Prefix written in front of `algorithm`'s hex digest under these
+    /// options: the entry's mode label, else its prefix.
+    pub fn digest_prefix(&self, algorithm: Algorithm) -> &'static str {
+        let entry = algorithm.entry();
+        entry.mode_label.map_or(entry.prefix, |label| label(self))
+    }
+}
 
 pub fn hash_file(path: &Path, algorithm: Algorithm) -> Result<String, io::Error> {
-    match algorithm {
-        Algorithm::Sha256 => super::sha256::hash_file(path),
-        Algorithm::Blake3 => super::blake3::hash_file(path),
+    let mut digests = hash_file_multi(path, &[algorithm])?;
+    Ok(digests.remove(0))
+}
+
+/// Hash a file in a single read pass, feeding every buffer to each requested
+/// algorithm. Digests are returned in the order the algorithms were given.
+pub fn hash_file_multi(path: &Path, algorithms: &[Algorithm]) -> Result<Vec<String>, io::Error> {
+    hash_file_with_options(path, &HashOptions::new(algorithms.to_vec()))
+        .map(|file_digests| file_digests.digests)
+}
+
+pub fn hash_file_with_options(
+    path: &Path,
+    options: &HashOptions,
+) -> Result<FileDigests, io::Error> {
+    // Read to EOF rather than trusting the metadata size, which is zero or
+    // meaningless for FIFOs and pseudo-files; it only sizes the hashers.
+    let reader = File::open(path)?;
+    let content_len = reader.metadata()?.len();
+    let file_digests = if let Some(min_len) = options.parallel_blake3_min_len
+        && content_len >= min_len
+        && options.parallel_blake3_eligible()
+    {
+        drop(reader);
+        FileDigests {
+            digests: vec![super::blake3::hash_file_parallel(path, &options.blake3)?],
+            ..FileDigests::default()
+        }
+    } else {
+        hash_reader_with_options(reader, content_len, options)?
+    };
+    with_inner_digest(file_digests, path, None, options)
+}
+
+/// Hash only `range` of the file. Every output — digests, chunk lists and
+/// any Bao outboard — covers the range alone, with offsets relative to it.
+pub fn hash_file_range_with_options(
+    path: &Path,
+    range: ByteRange,
+    options: &HashOptions,
+) -> Result<FileDigests, io::Error> {
+    let (reader, content_len) = open_range(path, range)?;
+    let file_digests = FileDigests {
+        range: Some(ByteRange {
+            offset: range.offset,
+            length: Some(content_len),
+        }),
+        ..hash_reader_with_options(reader, content_len, options)?
+    };
+    with_inner_digest(file_digests, path, Some(range), options)
+}
+
+/// Decoding needs its own pass: the payload length is unknown until the
+/// stream ends, and a corrupt stream must not cost the raw digests.
+fn with_inner_digest(
+    file_digests: FileDigests,
+    path: &Path,
+    range: Option<ByteRange>,
+    options: &HashOptions,
+) -> Result<FileDigests, io::Error> {
+    if options.inner_hash.is_empty() {
+        return Ok(file_digests);
+    }
+    Ok(FileDigests {
+        inner: super::inner::hash_inner(path, range, options)?,
+        ..file_digests
+    })
+}
+
+/// Hash `content_len` bytes from any reader, such as an archive member, with
+/// every output the options request.
+pub fn hash_reader_with_options<R: Read>(
+    reader: R,
+    content_len: u64,
+    options: &HashOptions,
+) -> Result<FileDigests, io::Error> {
+    if let Some(dir) = &options.bao_outboard_dir {
+        return hash_reader_with_bao_outboard(reader, content_len, options, dir);
+    }
+
+    let mut hashers: Vec<Box<dyn ContentHasher>> = options
+        .algorithms
+        .iter()
+        .map(|algorithm| options.new_hasher(*algorithm, content_len))
+        .collect();
+    let mut side_outputs = SideOutputs::new(options, content_len);
+
+    super::stream::for_each_buffer_from(reader, |buf| {
+        for hasher in &mut hashers {
+            hasher.update(buf);
+        }
+        side_outputs.update(buf, options);
+    })?;
+
+    let digests = hashers
+        .into_iter()
+        .zip(&options.algorithms)
+        .map(|(hasher, algorithm)| finalize_prefixed(options.digest_prefix(*algorithm), hasher))
+        .collect();
+    Ok(side_outputs.finish(digests, options))
+}
+
+/// Drive the read pass from the Bao encoder, whose root hash is the BLAKE3
+/// digest; the remaining algorithms observe the same bytes through a tee.
+fn hash_reader_with_bao_outboard<R: Read>(
+    reader: R,
+    content_len: u64,
+    options: &HashOptions,
+    dir: &Path,
+) -> Result<FileDigests, io::Error> {
+    let mut hashers: Vec<Option<Box<dyn ContentHasher>>> = options
+        .algorithms
+        .iter()
+        .map(|algorithm| {
+            (*algorithm != Algorithm::Blake3).then(|| options.new_hasher(*algorithm, content_len))
+        })
+        .collect();
+    let mut side_outputs = SideOutputs::new(options, content_len);
+
+    let reader = BufReader::with_capacity(super::stream::BUFFER_SIZE, reader);
+    let mut tee = super::stream::TeeReader::new(reader, |buf: &[u8]| {
+        for hasher in hashers.iter_mut().flatten() {
+            hasher.update(buf);
+        }
+        side_outputs.update(buf, options);
+    });
+    let (root, outboard_path) = super::bao::write_outboard_file(&mut tee, content_len, dir)?;
+    drop(tee);
+
+    let digests = hashers
+        .into_iter()
+        .zip(&options.algorithms)
+        .map(|(hasher, algorithm)| match hasher {
+            Some(hasher) => finalize_prefixed(options.digest_prefix(*algorithm), hasher),
+            None => Algorithm::Blake3.format_bytes_hash(root.to_hex().as_str()),
+        })
+        .collect();
+    Ok(FileDigests {
+        bao_outboard: Some(outboard_path),
+        ..side_outputs.finish(digests, options)
+    })
+}
+
+/// Per-file outputs computed from the same buffers as the whole-file digests.
+struct SideOutputs {
+    chunks: Option<ChunkHasher>,
+    cdc: Option<CdcChunker>,
+    object_store: Option<ObjectStoreHasher>,
+    ipfs_cid: Option<IpfsCidBuilder>,
+    fsverity: Option<FsverityHasher>,
+}
+
+impl SideOutputs {
+    fn new(options: &HashOptions, content_len: u64) -> Self {
+        Self {
+            chunks: options
+                .chunk_size
+                .map(|chunk_size| ChunkHasher::new(chunk_size, content_len, options)),
+            cdc: options.cdc.then(CdcChunker::new),
+            object_store: options.object_store.map(ObjectStoreHasher::new),
+            ipfs_cid: options.ipfs_cid.then(IpfsCidBuilder::new),
+            fsverity: options.fsverity.then(FsverityHasher::new),
+        }
+    }
+
+    fn update(&mut self, buf: &[u8], options: &HashOptions) {
+        if let Some(chunks) = &mut self.chunks {
+            chunks.update(buf, options);
+        }
+        if let Some(cdc) = &mut self.cdc {
+            cdc.update(buf);
+        }
+        if let Some(object_store) = &mut self.object_store {
+            object_store.update(buf);
+        }
+        if let Some(ipfs_cid) = &mut self.ipfs_cid {
+            ipfs_cid.update(buf);
+        }
+        if let Some(fsverity) = &mut self.fsverity {
+            fsverity.update(buf);
+        }
+    }
+
+    fn finish(self, digests: Vec<String>, options: &HashOptions) -> FileDigests {
+        FileDigests {
+            digests,
+            bao_outboard: None,
+            chunks: self.chunks.map(|chunks| chunks.finish(options)),
+            cdc_chunks: self.cdc.map(CdcChunker::finish),
+            range: None,
+            inner: None,
+            object_store: self.object_store.map(ObjectStoreHasher::finish),
+            ipfs_cid: self.ipfs_cid.map(IpfsCidBuilder::finish),
+            fsverity_digest: self.fsverity.map(FsverityHasher::finish),
+        }
     }
 }
@@ -0,0 +1,157 @@
+use super::blake3::Blake3Mode;
+use super::chunks::digest_bytes;
+use super::compute::HashOptions;
+use crate::cli::Algorithm;
+use std::fmt;
+use std::str::FromStr;
+
+/// Alternative spelling of the primary digest, written to
+/// `bytes_hash_encoded` alongside the canonical `bytes_hash`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DigestEncoding {
+    /// `<prefix>:<lowercase-hex>`, the OCI content digest form (sha256 and
+    /// sha512 only).
+    Hex,
+    /// Subresource Integrity: `sha256-<base64>`.
+    Sri,
+    /// Multibase `b` (RFC 4648 base32, lowercase, unpadded) of the multihash.
+    MultibaseBase32,
+    /// Lowercase hex of the multihash bytes: varint code, varint length, digest.
+    Multihash,
+}
+
+impl DigestEncoding {
+    pub const ALL: [Self; 4] = [Self::Hex, Self::Sri, Self::MultibaseBase32, Self::Multihash];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Hex => "hex",
+            Self::Sri => "sri",
+            Self::MultibaseBase32 => "multibase-base32",
+            Self::Multihash => "multihash",
+        }
+    }
+
+    /// Check that the primary digest under `options` has this spelling, so an
+    /// unusable combination is refused before any file is read.
+    pub fn check(&self, options: &HashOptions) -> Result<(), String> {
+        let primary = options.primary();
+        match self {
+            Self::Hex => oci_name(primary).map(|_| ()).ok_or_else(|| {
+                format!(
+                    "hex is the OCI digest form and requires sha256 or sha512 as the primary algorithm, got {}",
+                    options.algorithm_name(primary)
+                )
+            }),
+            Self::Sri => sri_name(primary).map(|_| ()).ok_or_else(|| {
+                format!(
+                    "sri requires sha256, sha384 or sha512 as the primary algorithm, got {}",
+                    options.algorithm_name(primary)
+                )
+            }),
+            Self::MultibaseBase32 | Self::Multihash => {
+                multihash_code(primary, options).map(|_| ()).ok_or_else(|| {
+                    format!(
+                        "{} has no multihash code for {}",
+                        self.name(),
+                        options.algorithm_name(primary)
+                    )
+                })
+            }
+        }
+    }
+
+    /// Re-spell a prefixed primary digest such as `sha256:<hex>`.
+    pub fn encode(&self, bytes_hash: &str, options: &HashOptions) -> Option<String> {
+        let primary = options.primary();
+        let digest = digest_bytes(bytes_hash);
+        match self {
+            Self::Hex => oci_name(primary).map(|_| bytes_hash.to_owned()),
+            Self::Sri => Some(format!(
+                "{}-{}",
+                sri_name(primary)?,
+                data_encoding::BASE64.encode(&digest)
+            )),
+            Self::MultibaseBase32 => {
+                let multihash = multihash(multihash_code(primary, options)?, &digest);
+                Some(format!(
+                    "b{}",
+                    data_encoding::BASE32_NOPAD
+                        .encode(&multihash)
+                        .to_ascii_lowercase()
+                ))
+            }
+            Self::Multihash => {
+                let multihash = multihash(multihash_code(primary, options)?, &digest);
+                Some(data_encoding::HEXLOWER.encode(&multihash))
+            }
+        }
+    }
+}
+
+impl FromStr for DigestEncoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|encoding| s.eq_ignore_ascii_case(encoding.name()))
+            .ok_or_else(|| {
+                let expected = Self::ALL
+                    .iter()
+                    .map(DigestEncoding::name)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("Invalid encoding '{s}'. Expected one of: {expected}")
+            })
+    }
+}
+
+impl fmt::Display for DigestEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// OCI registers only these two digest algorithms; any other `<prefix>:<hex>`
+/// would look like an OCI digest without being one.
+fn oci_name(algorithm: Algorithm) -> Option<&'static str> {
+    match algorithm {
+        Algorithm::Sha256 | Algorithm::Sha512 => Some(algorithm.name()),
+        _ => None,
+    }
+}
+
+/// SRI defines only the SHA-2 family.
+fn sri_name(algorithm: Algorithm) -> Option<&'static str> {
+    match algorithm {
+        Algorithm::Sha256 | Algorithm::Sha384 | Algorithm::Sha512 => Some(algorithm.name()),
+        _ => None,
+    }
+}
+
+/// The multicodec code for BLAKE3 covers plain hashing only; keyed and
+/// derive-key digests are a different function and have none.
+fn multihash_code(algorithm: Algorithm, options: &HashOptions) -> Option<u64> {
+    if algorithm == Algorithm::Blake3 && options.blake3.mode != Blake3Mode::Hash {
+        return None;
+    }
+    algorithm.entry().multihash_code
+}
+
+fn multihash(code: u64, digest: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(digest.len() + 4);
+    push_varint(&mut bytes, code);
+    push_varint(&mut bytes, digest.len() as u64);
+    bytes.extend_from_slice(digest);
+    bytes
+}
+
+/// Unsigned LEB128, as used by multiformats.
+pub(crate) fn push_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
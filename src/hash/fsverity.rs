@@ -0,0 +1,112 @@
+use sha2::{Digest, Sha256};
+
+/// Merkle tree block size; also the data block size. 4 KiB is the size
+/// `fsverity enable` and the kernel use by default.
+pub const BLOCK_LEN: usize = 4096;
+const LOG_BLOCK_LEN: u8 = 12;
+const DESCRIPTOR_VERSION: u8 = 1;
+const HASH_ALG_SHA256: u8 = 1;
+const DESCRIPTOR_LEN: usize = 256;
+const DIGEST_LEN: usize = 32;
+
+/// Streaming fs-verity file digest (SHA-256, 4 KiB blocks, no salt), as
+/// `fsverity digest` prints it and `FS_IOC_MEASURE_VERITY` reports it once
+/// verity is enabled. Only one partial block per tree level is held.
+pub(super) struct FsverityHasher {
+    block: Sha256,
+    block_len: usize,
+    data_len: u64,
+    /// Hashes waiting to fill a block, per tree level; level 0 holds the
+    /// hashes of data blocks.
+    levels: Vec<Vec<u8>>,
+}
+
+impl FsverityHasher {
+    pub(super) fn new() -> Self {
+        Self {
+            block: Sha256::new(),
+            block_len: 0,
+            data_len: 0,
+            levels: vec![Vec::new()],
+        }
+    }
+
+    pub(super) fn update(&mut self, buf: &[u8]) {
+        self.data_len += buf.len() as u64;
+        let mut rest = buf;
+        while !rest.is_empty() {
+            let (head, tail) = rest.split_at((BLOCK_LEN - self.block_len).min(rest.len()));
+            self.block.update(head);
+            self.block_len += head.len();
+            rest = tail;
+            if self.block_len == BLOCK_LEN {
+                let block = std::mem::replace(&mut self.block, Sha256::new());
+                self.block_len = 0;
+                self.push(0, &block.finalize());
+            }
+        }
+    }
+
+    /// `sha256:<hex>` of the fs-verity descriptor.
+    pub(super) fn finish(mut self) -> String {
+        let root = self.root_hash();
+
+        let mut descriptor = [0u8; DESCRIPTOR_LEN];
+        descriptor[0] = DESCRIPTOR_VERSION;
+        descriptor[1] = HASH_ALG_SHA256;
+        descriptor[2] = LOG_BLOCK_LEN;
+        // salt_size (byte 3) and sig_size (bytes 4..8) stay zero.
+        descriptor[8..16].copy_from_slice(&self.data_len.to_le_bytes());
+        descriptor[16..16 + DIGEST_LEN].copy_from_slice(&root);
+        format!("sha256:{:x}", Sha256::digest(descriptor))
+    }
+
+    /// Zeros for an empty file, the hash of the only data block for a file
+    /// of one block, and otherwise the hash of the single top tree block.
+    fn root_hash(&mut self) -> [u8; DIGEST_LEN] {
+        if self.data_len == 0 {
+            return [0; DIGEST_LEN];
+        }
+        if self.block_len > 0 {
+            let padding = [0u8; BLOCK_LEN];
+            let block = std::mem::replace(&mut self.block, Sha256::new());
+            let hash = block
+                .chain_update(&padding[..BLOCK_LEN - self.block_len])
+                .finalize();
+            self.block_len = 0;
+            self.push(0, &hash);
+        }
+
+        let mut level = 0;
+        loop {
+            let is_top = self.levels[level + 1..].iter().all(Vec::is_empty);
+            if is_top && self.levels[level].len() == DIGEST_LEN {
+                let mut root = [0; DIGEST_LEN];
+                root.copy_from_slice(&self.levels[level]);
+                return root;
+            }
+            if !self.levels[level].is_empty() {
+                self.fold(level);
+            }
+            level += 1;
+        }
+    }
+
+    fn push(&mut self, level: usize, hash: &[u8]) {
+        self.levels[level].extend_from_slice(hash);
+        if self.levels[level].len() == BLOCK_LEN {
+            self.fold(level);
+        }
+    }
+
+    /// Hash the pending (zero-padded) block of `level` into the level above.
+    fn fold(&mut self, level: usize) {
+        if self.levels.len() == level + 1 {
+            self.levels.push(Vec::new());
+        }
+        let mut block = std::mem::take(&mut self.levels[level]);
+        block.resize(BLOCK_LEN, 0);
+        let hash = Sha256::digest(&block);
+        self.push(level + 1, &hash);
+    }
+}
@@ -0,0 +1,131 @@
+use crate::cli::Algorithm;
+use sha2::Digest;
+use std::collections::BTreeMap;
+
+/// Tree entry modes, as git writes them.
+pub const MODE_FILE: &str = "100644";
+pub const MODE_EXECUTABLE: &str = "100755";
+pub const MODE_SYMLINK: &str = "120000";
+pub const MODE_TREE: &str = "40000";
+
+/// Header git hashes ahead of every object body: `<kind> <len>\0`.
+pub fn object_header(kind: &str, len: u64) -> Vec<u8> {
+    format!("{kind} {len}\0").into_bytes()
+}
+
+/// Raw object id of `body` as a git object of `kind`, or `None` when
+/// `algorithm` is not a git object format.
+pub fn object_id(algorithm: Algorithm, kind: &str, body: &[u8]) -> Option<Vec<u8>> {
+    let header = object_header(kind, body.len() as u64);
+    match algorithm {
+        Algorithm::GitSha1 => Some(digest::<sha1::Sha1>(&header, body)),
+        Algorithm::GitSha256 => Some(digest::<sha2::Sha256>(&header, body)),
+        _ => None,
+    }
+}
+
+fn digest<D: Digest>(header: &[u8], body: &[u8]) -> Vec<u8> {
+    let mut hasher = D::new();
+    hasher.update(header);
+    hasher.update(body);
+    hasher.finalize().to_vec()
+}
+
+/// In-memory git tree assembled from blob ids at relative paths. Parent
+/// directories are implied by their contents, so empty directories never
+/// appear, exactly as in git.
+#[derive(Debug, Default)]
+pub struct GitTree {
+    entries: BTreeMap<Vec<u8>, TreeNode>,
+}
+
+#[derive(Debug)]
+enum TreeNode {
+    Blob { mode: &'static str, id: Vec<u8> },
+    Tree(GitTree),
+}
+
+impl GitTree {
+    /// Add a blob at `components` (path names below the tree root).
+    pub fn insert_blob(&mut self, components: &[Vec<u8>], mode: &'static str, id: Vec<u8>) {
+        let Some((name, rest)) = components.split_first() else {
+            return;
+        };
+        if rest.is_empty() {
+            self.entries
+                .insert(name.clone(), TreeNode::Blob { mode, id });
+            return;
+        }
+        let node = self
+            .entries
+            .entry(name.clone())
+            .or_insert_with(|| TreeNode::Tree(GitTree::default()));
+        if let TreeNode::Blob { .. } = node {
+            *node = TreeNode::Tree(GitTree::default());
+        }
+        if let TreeNode::Tree(subtree) = node {
+            subtree.insert_blob(rest, mode, id);
+        }
+    }
+
+    /// Raw tree object id under `algorithm`, or `None` for non-git algorithms.
+    pub fn id(&self, algorithm: Algorithm) -> Option<Vec<u8>> {
+        // Git orders entries bytewise with tree names compared as "name/".
+        let mut entries = Vec::with_capacity(self.entries.len());
+        for (name, node) in &self.entries {
+            let (mode, id, sort_key) = match node {
+                TreeNode::Blob { mode, id } => (*mode, id.clone(), name.clone()),
+                TreeNode::Tree(subtree) => {
+                    let mut sort_key = name.clone();
+                    sort_key.push(b'/');
+                    (MODE_TREE, subtree.id(algorithm)?, sort_key)
+                }
+            };
+            entries.push((sort_key, mode, name, id));
+        }
+        entries.sort_by(|left, right| left.0.cmp(&right.0));
+
+        let mut body = Vec::new();
+        for (_, mode, name, id) in entries {
+            body.extend_from_slice(mode.as_bytes());
+            body.push(b' ');
+            body.extend_from_slice(name);
+            body.push(0);
+            body.extend_from_slice(&id);
+        }
+        object_id(algorithm, "tree", &body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GitTree, MODE_FILE, object_id};
+    use crate::cli::Algorithm;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+
+    #[test]
+    fn empty_blob_and_tree_match_git() {
+        let blob = object_id(Algorithm::GitSha1, "blob", b"").expect("git algorithm");
+        assert_eq!(hex(&blob), "e69de29bb2d1d6434b8b29ae775ad8c2e48c5391");
+
+        let tree = GitTree::default().id(Algorithm::GitSha1).expect("git");
+        assert_eq!(hex(&tree), "4b825dc642cb6eb9a060e54bf8d69288fbee4904");
+
+        let tree = GitTree::default().id(Algorithm::GitSha256).expect("git");
+        assert_eq!(
+            hex(&tree),
+            "6ef19b41225c5369f1c104d45d8d85efa9b057b53b14b4b9b939dd74decc5321"
+        );
+    }
+
+    #[test]
+    fn non_git_algorithms_have_no_object_ids() {
+        assert!(object_id(Algorithm::Sha256, "blob", b"").is_none());
+        let mut tree = GitTree::default();
+        tree.insert_blob(&[b"a".to_vec()], MODE_FILE, vec![0; 20]);
+        assert!(tree.id(Algorithm::Blake3).is_none());
+    }
+}
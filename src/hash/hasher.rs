@@ -0,0 +1,101 @@
+use super::blake3::Blake3Options;
+use xxhash_rust::xxh3::Xxh3;
+
+/// Incremental digest over a file's bytes. Every algorithm in the registry,
+/// built-in or supplied by a downstream crate, is driven through this trait:
+/// the read pass feeds each buffer to `update`, then `finalize` yields the
+/// digest that is written as `<prefix>:<hex>`. The prefix belongs to the
+/// registry entry, not the hasher.
+pub trait ContentHasher: Send {
+    /// Feed the next bytes of content, in file order.
+    fn update(&mut self, buf: &[u8]);
+
+    /// Consume the hasher and return the lowercase hex digest.
+    fn finalize(self: Box<Self>) -> String;
+}
+
+/// Finalize `hasher` into a prefixed digest such as `sha256:<hex>`; `prefix`
+/// comes from [`HashOptions::digest_prefix`](super::HashOptions::digest_prefix).
+pub fn finalize_prefixed(prefix: &str, hasher: Box<dyn ContentHasher>) -> String {
+    format!("{prefix}:{}", hasher.finalize().to_ascii_lowercase())
+}
+
+/// Adapter for any RustCrypto [`sha2::Digest`] implementation (SHA-2, SHA-3).
+pub struct DigestHasher<D> {
+    digest: D,
+}
+
+impl<D: sha2::Digest> DigestHasher<D> {
+    pub fn new() -> Self {
+        Self { digest: D::new() }
+    }
+}
+
+impl<D: sha2::Digest> Default for DigestHasher<D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<D: sha2::Digest + Send> ContentHasher for DigestHasher<D> {
+    fn update(&mut self, buf: &[u8]) {
+        self.digest.update(buf);
+    }
+
+    fn finalize(self: Box<Self>) -> String {
+        to_hex(&self.digest.finalize())
+    }
+}
+
+/// BLAKE3 in whichever mode and output length the run selected.
+pub struct Blake3Hasher {
+    hasher: blake3::Hasher,
+    options: Blake3Options,
+}
+
+impl Blake3Hasher {
+    pub fn new(options: &Blake3Options) -> Self {
+        Self {
+            hasher: options.hasher(),
+            options: options.clone(),
+        }
+    }
+}
+
+impl ContentHasher for Blake3Hasher {
+    fn update(&mut self, buf: &[u8]) {
+        self.hasher.update(buf);
+    }
+
+    fn finalize(self: Box<Self>) -> String {
+        self.options.finalize_hex(&self.hasher)
+    }
+}
+
+pub struct Xxh3Hasher(Xxh3);
+
+impl Xxh3Hasher {
+    pub fn new() -> Self {
+        Self(Xxh3::new())
+    }
+}
+
+impl Default for Xxh3Hasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ContentHasher for Xxh3Hasher {
+    fn update(&mut self, buf: &[u8]) {
+        self.0.update(buf);
+    }
+
+    fn finalize(self: Box<Self>) -> String {
+        format!("{:032x}", self.0.digest128())
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
@@ -0,0 +1,160 @@
+use super::compute::HashOptions;
+use super::hasher::finalize_prefixed;
+use super::range::{ByteRange, open_range};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+use std::str::FromStr;
+
+/// Compressed container formats `--inner-hash` can see through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+    Xz,
+    Bz2,
+}
+
+impl Compression {
+    pub const ALL: [Self; 4] = [Self::Gzip, Self::Zstd, Self::Xz, Self::Bz2];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Gzip => "gzip",
+            Self::Zstd => "zstd",
+            Self::Xz => "xz",
+            Self::Bz2 => "bz2",
+        }
+    }
+
+    fn magic(&self) -> &'static [u8] {
+        match self {
+            Self::Gzip => &[0x1f, 0x8b],
+            Self::Zstd => &[0x28, 0xb5, 0x2f, 0xfd],
+            Self::Xz => &[0xfd, b'7', b'z', b'X', b'Z', 0x00],
+            Self::Bz2 => b"BZh",
+        }
+    }
+
+    /// Format among `enabled` whose magic bytes start `prefix`.
+    pub fn detect(prefix: &[u8], enabled: &[Self]) -> Option<Self> {
+        enabled
+            .iter()
+            .copied()
+            .find(|compression| prefix.starts_with(compression.magic()))
+    }
+
+    /// Decoder over `reader`. Concatenated streams (as written by `pigz`,
+    /// `pbzip2` or `xz -T`) are decoded in full.
+    pub(crate) fn decoder<'a, R: BufRead + 'a>(&self, reader: R) -> io::Result<Box<dyn Read + 'a>> {
+        Ok(match self {
+            Self::Gzip => Box::new(flate2::bufread::MultiGzDecoder::new(reader)),
+            Self::Zstd => Box::new(zstd::stream::read::Decoder::with_buffer(reader)?),
+            Self::Xz => Box::new(xz2::bufread::XzDecoder::new_multi_decoder(reader)),
+            Self::Bz2 => Box::new(bzip2::bufread::MultiBzDecoder::new(reader)),
+        })
+    }
+}
+
+impl FromStr for Compression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|compression| s.eq_ignore_ascii_case(compression.name()))
+            .ok_or_else(|| {
+                let expected = Self::ALL
+                    .iter()
+                    .map(Compression::name)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("Invalid compression '{s}'. Expected one of: {expected}")
+            })
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Parse a comma-separated `--inner-hash` value such as `gzip,zstd`.
+pub fn parse_compression_list(s: &str) -> Result<Vec<Compression>, String> {
+    let mut formats = Vec::new();
+    for entry in s.split(',') {
+        let compression = Compression::from_str(entry.trim())?;
+        if formats.contains(&compression) {
+            return Err(format!("Duplicate compression '{compression}' in '{s}'"));
+        }
+        formats.push(compression);
+    }
+    Ok(formats)
+}
+
+/// Primary-algorithm digest of a compressed file's decoded payload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InnerDigest {
+    pub compression: Compression,
+    /// The prefixed digest, or the decoder's error for a corrupt stream.
+    pub digest: Result<String, String>,
+}
+
+/// Decode the file, or only `range` of it, if it starts with an enabled
+/// format's magic bytes and hash the payload with the primary algorithm.
+/// Returns `None` for anything that is not compressed. Read failures are I/O errors; anything
+/// else the decoder rejects is reported as a corrupt stream.
+pub fn hash_inner(
+    path: &Path,
+    range: Option<ByteRange>,
+    options: &HashOptions,
+) -> io::Result<Option<InnerDigest>> {
+    let reader: Box<dyn Read> = match range {
+        Some(range) => Box::new(open_range(path, range)?.0),
+        None => Box::new(File::open(path)?),
+    };
+    let mut raw = RawReader {
+        inner: reader,
+        error: None,
+    };
+    let mut buffered = BufReader::with_capacity(super::stream::BUFFER_SIZE, &mut raw);
+    let Some(compression) = Compression::detect(buffered.fill_buf()?, &options.inner_hash) else {
+        return Ok(None);
+    };
+
+    // Payload length is unknown until decoded, so the hasher is never one
+    // that needs it up front; `--inner-hash` refuses those algorithms.
+    let mut hasher = options.new_hasher(options.primary(), 0);
+    let decoded = compression
+        .decoder(buffered)
+        .and_then(|decoder| super::stream::for_each_buffer_from(decoder, |buf| hasher.update(buf)));
+
+    if let Some(err) = raw.error.take() {
+        return Err(err);
+    }
+    Ok(Some(InnerDigest {
+        compression,
+        digest: decoded
+            .map(|()| finalize_prefixed(options.digest_prefix(options.primary()), hasher))
+            .map_err(|err| err.to_string()),
+    }))
+}
+
+/// Remembers a failure of the underlying file so it is not mistaken for a
+/// decode error.
+struct RawReader<R> {
+    inner: R,
+    error: Option<io::Error>,
+}
+
+impl<R: Read> Read for RawReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf).inspect_err(|err| {
+            if err.kind() != io::ErrorKind::Interrupted {
+                self.error = Some(io::Error::new(err.kind(), err.to_string()));
+            }
+        })
+    }
+}
@@ -0,0 +1,166 @@
+use super::encoding::push_varint;
+use sha2::{Digest, Sha256};
+
+/// Fixed-size chunker of `ipfs add`: `size-262144`.
+pub const CHUNK_LEN: u64 = 256 * 1024;
+/// Links per internal node in the balanced DAG layout.
+pub const MAX_LINKS: usize = 174;
+
+const CID_VERSION: u64 = 1;
+const CODEC_RAW: u64 = 0x55;
+const CODEC_DAG_PB: u64 = 0x70;
+const MULTIHASH_SHA2_256: u64 = 0x12;
+const UNIXFS_FILE: u64 = 2;
+
+/// One child of a DAG node: a raw leaf or an internal dag-pb node.
+#[derive(Debug, Clone)]
+struct Link {
+    cid: Vec<u8>,
+    /// Bytes of this block and every block below it (`Tsize`).
+    tsize: u64,
+    /// File bytes below this link (UnixFS `blocksizes` entry).
+    file_len: u64,
+}
+
+/// Streaming UnixFS builder matching `ipfs add --cid-version=1`: 256 KiB
+/// chunks stored as raw leaves, joined by a balanced DAG of dag-pb nodes
+/// with at most 174 links each, CIDs hashed with SHA-256. Only the CIDs of
+/// completed subtrees are kept, never file bytes.
+pub(super) struct IpfsCidBuilder {
+    leaf: Sha256,
+    leaf_len: u64,
+    leaves: u64,
+    /// Pending links per tree level, leaves first. A level is folded into a
+    /// node on the level above as soon as it holds `MAX_LINKS` links.
+    levels: Vec<Vec<Link>>,
+}
+
+impl IpfsCidBuilder {
+    pub(super) fn new() -> Self {
+        Self {
+            leaf: Sha256::new(),
+            leaf_len: 0,
+            leaves: 0,
+            levels: vec![Vec::new()],
+        }
+    }
+
+    pub(super) fn update(&mut self, buf: &[u8]) {
+        let mut rest = buf;
+        while !rest.is_empty() {
+            if self.leaf_len == CHUNK_LEN {
+                self.close_leaf();
+            }
+            let room = (CHUNK_LEN - self.leaf_len) as usize;
+            let (head, tail) = rest.split_at(room.min(rest.len()));
+            self.leaf.update(head);
+            self.leaf_len += head.len() as u64;
+            rest = tail;
+        }
+    }
+
+    /// The root CID in its canonical base32 spelling (`bafk...` when the
+    /// whole file is one raw leaf, `bafy...` otherwise).
+    pub(super) fn finish(mut self) -> String {
+        // An empty file is still one (empty) leaf.
+        if self.leaf_len > 0 || self.leaves == 0 {
+            self.close_leaf();
+        }
+
+        let mut level = 0;
+        loop {
+            let is_top = self.levels[level + 1..].iter().all(Vec::is_empty);
+            if is_top && self.levels[level].len() == 1 {
+                return format!(
+                    "b{}",
+                    data_encoding::BASE32_NOPAD
+                        .encode(&self.levels[level][0].cid)
+                        .to_ascii_lowercase()
+                );
+            }
+            if !self.levels[level].is_empty() {
+                self.fold(level);
+            }
+            level += 1;
+        }
+    }
+
+    fn close_leaf(&mut self) {
+        let leaf = std::mem::replace(&mut self.leaf, Sha256::new());
+        let link = Link {
+            cid: cid(CODEC_RAW, &leaf.finalize()),
+            tsize: self.leaf_len,
+            file_len: self.leaf_len,
+        };
+        self.leaf_len = 0;
+        self.leaves += 1;
+        self.push(0, link);
+    }
+
+    fn push(&mut self, level: usize, link: Link) {
+        self.levels[level].push(link);
+        if self.levels[level].len() == MAX_LINKS {
+            self.fold(level);
+        }
+    }
+
+    /// Replace the pending links of `level` with one dag-pb node linking
+    /// them, pushed onto the level above.
+    fn fold(&mut self, level: usize) {
+        if self.levels.len() == level + 1 {
+            self.levels.push(Vec::new());
+        }
+        let links = std::mem::take(&mut self.levels[level]);
+        let node = dag_pb_node(&links);
+        let link = Link {
+            cid: cid(CODEC_DAG_PB, &Sha256::digest(&node)),
+            tsize: node.len() as u64 + links.iter().map(|link| link.tsize).sum::<u64>(),
+            file_len: links.iter().map(|link| link.file_len).sum(),
+        };
+        self.push(level + 1, link);
+    }
+}
+
+fn cid(codec: u64, sha256: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(sha256.len() + 4);
+    push_varint(&mut bytes, CID_VERSION);
+    push_varint(&mut bytes, codec);
+    push_varint(&mut bytes, MULTIHASH_SHA2_256);
+    push_varint(&mut bytes, sha256.len() as u64);
+    bytes.extend_from_slice(sha256);
+    bytes
+}
+
+/// Canonical dag-pb encoding: every `Links` entry (hash, empty name, tsize),
+/// then `Data` holding the UnixFS file node with its total size and one
+/// `blocksizes` entry per link.
+fn dag_pb_node(links: &[Link]) -> Vec<u8> {
+    let mut unixfs = Vec::new();
+    push_varint_field(&mut unixfs, 1, UNIXFS_FILE);
+    push_varint_field(&mut unixfs, 3, links.iter().map(|link| link.file_len).sum());
+    for link in links {
+        push_varint_field(&mut unixfs, 4, link.file_len);
+    }
+
+    let mut node = Vec::new();
+    for link in links {
+        let mut encoded = Vec::new();
+        push_bytes_field(&mut encoded, 1, &link.cid);
+        push_bytes_field(&mut encoded, 2, b"");
+        push_varint_field(&mut encoded, 3, link.tsize);
+        push_bytes_field(&mut node, 2, &encoded);
+    }
+    push_bytes_field(&mut node, 1, &unixfs);
+    node
+}
+
+fn push_varint_field(bytes: &mut Vec<u8>, field: u64, value: u64) {
+    push_varint(bytes, field << 3);
+    push_varint(bytes, value);
+}
+
+fn push_bytes_field(bytes: &mut Vec<u8>, field: u64, value: &[u8]) {
+    push_varint(bytes, (field << 3) | 2);
+    push_varint(bytes, value.len() as u64);
+    bytes.extend_from_slice(value);
+}
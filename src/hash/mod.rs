@@ -1,5 +1,37 @@
+pub mod archive;
+pub mod bao;
 pub mod blake3;
+pub mod cdc;
+pub mod chunks;
 pub mod compute;
+pub mod encoding;
+pub mod fsverity;
+pub mod git;
+pub mod hasher;
+pub mod inner;
+pub mod ipfs;
+pub mod object_store;
+pub mod range;
+pub mod registry;
+pub mod sample;
 pub mod sha256;
+pub mod sha384;
+pub mod sha3_256;
+pub mod sha512;
+pub mod snapshot;
+pub mod stream;
+pub mod xxh3;
 
-pub use compute::hash_file;
+pub use cdc::{CdcChunk, CdcSummary};
+pub use chunks::ChunkDigests;
+pub use compute::{
+    FileDigests, HashOptions, hash_file, hash_file_multi, hash_file_range_with_options,
+    hash_file_with_options,
+};
+pub use encoding::DigestEncoding;
+pub use hasher::ContentHasher;
+pub use inner::{Compression, InnerDigest};
+pub use object_store::{ObjectStoreChecksums, ObjectStoreOptions};
+pub use range::ByteRange;
+pub use registry::AlgorithmEntry;
+pub use sample::{SampleDigest, SampleOptions};
@@ -0,0 +1,119 @@
+use crc::{CRC_32_ISCSI, CRC_64_NVME, Crc, Table};
+use md5::{Digest, Md5};
+
+/// Part size the AWS CLI and SDKs use unless configured otherwise; it is also
+/// their multipart threshold.
+pub const DEFAULT_S3_PART_SIZE: u64 = 8 * 1024 * 1024;
+/// S3 accepts parts between 5 MiB and 5 GiB (the last part may be smaller).
+pub const MIN_S3_PART_SIZE: u64 = 5 * 1024 * 1024;
+pub const MAX_S3_PART_SIZE: u64 = 5 * 1024 * 1024 * 1024;
+
+static CRC32C: Crc<u32, Table<16>> = Crc::<u32, Table<16>>::new(&CRC_32_ISCSI);
+static CRC64NVME: Crc<u64, Table<16>> = Crc::<u64, Table<16>>::new(&CRC_64_NVME);
+
+/// What `--object-store-checksums` computes alongside the main digest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ObjectStoreOptions {
+    /// Part size the object was (or will be) uploaded with; it determines
+    /// the S3 ETag.
+    pub s3_part_size: u64,
+}
+
+impl Default for ObjectStoreOptions {
+    fn default() -> Self {
+        Self {
+            s3_part_size: DEFAULT_S3_PART_SIZE,
+        }
+    }
+}
+
+/// Checksums an object store reports for the same bytes, spelled as the
+/// store does so they compare as strings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectStoreChecksums {
+    /// S3 ETag without quotes: plain MD5 hex for an object smaller than one
+    /// part (a single-part upload), otherwise the MD5 of the concatenated
+    /// part MD5s followed by `-<parts>`.
+    pub s3_etag: String,
+    pub s3_part_size: u64,
+    /// Base64 of the big-endian CRC32C, as in GCS `crc32c` and S3
+    /// `x-amz-checksum-crc32c`.
+    pub crc32c: String,
+    /// Base64 of the big-endian CRC-64/NVME, as in S3
+    /// `x-amz-checksum-crc64nvme`.
+    pub crc64nvme: String,
+}
+
+/// Streaming computation of every object-store checksum from the buffers of
+/// the main read pass.
+pub(super) struct ObjectStoreHasher {
+    part_size: u64,
+    part: Md5,
+    part_len: u64,
+    /// MD5 of each completed part, concatenated.
+    part_digests: Vec<u8>,
+    parts: u64,
+    crc32c: crc::Digest<'static, u32, Table<16>>,
+    crc64nvme: crc::Digest<'static, u64, Table<16>>,
+}
+
+impl ObjectStoreHasher {
+    pub(super) fn new(options: ObjectStoreOptions) -> Self {
+        Self {
+            part_size: options.s3_part_size,
+            part: Md5::new(),
+            part_len: 0,
+            part_digests: Vec::new(),
+            parts: 0,
+            crc32c: CRC32C.digest(),
+            crc64nvme: CRC64NVME.digest(),
+        }
+    }
+
+    pub(super) fn update(&mut self, buf: &[u8]) {
+        self.crc32c.update(buf);
+        self.crc64nvme.update(buf);
+
+        let mut rest = buf;
+        while !rest.is_empty() {
+            let room = usize::try_from(self.part_size - self.part_len).unwrap_or(usize::MAX);
+            let (head, tail) = rest.split_at(room.min(rest.len()));
+            self.part.update(head);
+            self.part_len += head.len() as u64;
+            rest = tail;
+            // The part size is also the multipart threshold, so an object of
+            // exactly one part size is already a one-part multipart upload.
+            if self.part_len == self.part_size {
+                self.close_part();
+            }
+        }
+    }
+
+    pub(super) fn finish(mut self) -> ObjectStoreChecksums {
+        let s3_etag = if self.parts == 0 {
+            data_encoding::HEXLOWER.encode(&self.part.finalize())
+        } else {
+            if self.part_len > 0 {
+                self.close_part();
+            }
+            format!(
+                "{}-{}",
+                data_encoding::HEXLOWER.encode(&Md5::digest(&self.part_digests)),
+                self.parts
+            )
+        };
+        ObjectStoreChecksums {
+            s3_etag,
+            s3_part_size: self.part_size,
+            crc32c: data_encoding::BASE64.encode(&self.crc32c.finalize().to_be_bytes()),
+            crc64nvme: data_encoding::BASE64.encode(&self.crc64nvme.finalize().to_be_bytes()),
+        }
+    }
+
+    fn close_part(&mut self) {
+        let part = std::mem::replace(&mut self.part, Md5::new());
+        self.part_digests.extend_from_slice(&part.finalize());
+        self.part_len = 0;
+        self.parts += 1;
+    }
+}
@@ -0,0 +1,72 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Region of a file to hash, from the record's `hash_offset`/`hash_length`.
+/// A missing length runs to end of file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ByteRange {
+    pub offset: u64,
+    pub length: Option<u64>,
+}
+
+/// The requested range does not fit inside the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RangePastEof {
+    pub range: ByteRange,
+    pub file_len: u64,
+}
+
+impl fmt::Display for RangePastEof {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.range.length {
+            Some(length) => write!(
+                f,
+                "range {}+{} extends past end of file ({} bytes)",
+                self.range.offset, length, self.file_len
+            ),
+            None => write!(
+                f,
+                "offset {} is past end of file ({} bytes)",
+                self.range.offset, self.file_len
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RangePastEof {}
+
+impl ByteRange {
+    /// Length of the range within a file of `file_len` bytes.
+    pub fn resolve(self, file_len: u64) -> Result<u64, RangePastEof> {
+        let past_eof = RangePastEof {
+            range: self,
+            file_len,
+        };
+        let available = file_len.checked_sub(self.offset).ok_or(past_eof)?;
+        match self.length {
+            Some(length) if length > available => Err(past_eof),
+            Some(length) => Ok(length),
+            None => Ok(available),
+        }
+    }
+}
+
+/// Open `path` positioned at the start of `range`, returning a reader limited
+/// to the range and its resolved length. A range past EOF fails with an
+/// `UnexpectedEof` error wrapping [`RangePastEof`].
+pub fn open_range(path: &Path, range: ByteRange) -> io::Result<(io::Take<File>, u64)> {
+    let mut file = File::open(path)?;
+    let file_len = file.metadata()?.len();
+    let length = range
+        .resolve(file_len)
+        .map_err(|err| io::Error::new(io::ErrorKind::UnexpectedEof, err))?;
+    file.seek(SeekFrom::Start(range.offset))?;
+    Ok((file.take(length), length))
+}
+
+/// The [`RangePastEof`] carried by an error from [`open_range`], if any.
+pub fn range_past_eof(err: &io::Error) -> Option<&RangePastEof> {
+    err.get_ref()?.downcast_ref()
+}
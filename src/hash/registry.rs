@@ -0,0 +1,220 @@
+use super::compute::HashOptions;
+use super::git;
+use super::hasher::{Blake3Hasher, ContentHasher, DigestHasher, Xxh3Hasher};
+use crate::cli::Algorithm;
+use std::fmt;
+use std::sync::RwLock;
+
+/// Everything hashbytes needs to know about one algorithm. Registering an
+/// entry makes it parseable by `--algorithm`, listed by `doctor capabilities`
+/// and `--describe`, and usable in every read pass.
+#[derive(Clone, Copy)]
+pub struct AlgorithmEntry {
+    /// Name accepted by `--algorithm` and recorded as `hash_algorithm`.
+    pub name: &'static str,
+    /// Prefix written in front of the hex digest in `bytes_hash`.
+    pub prefix: &'static str,
+    /// Number of lowercase hex characters in a digest after the prefix.
+    pub digest_hex_len: usize,
+    /// Non-cryptographic digests are fine for scratch and caching work but
+    /// must never be treated as identity by downstream tools.
+    pub cryptographic: bool,
+    /// Multicodec code of the hash function, used by `--encoding multihash`
+    /// and `multibase-base32`; `None` when multiformats has no code for it.
+    pub multihash_code: Option<u64>,
+    /// Build a fresh hasher for one file under the run's options, given the
+    /// exact number of content bytes that will be fed to it.
+    pub new_hasher: fn(&HashOptions, u64) -> Box<dyn ContentHasher>,
+    /// Label that replaces both `name` and `prefix` when the run's options
+    /// change what the digest means (BLAKE3 keyed, derive-key and XOF
+    /// output); `None` when the algorithm has a single label.
+    pub mode_label: Option<fn(&HashOptions) -> &'static str>,
+}
+
+impl fmt::Debug for AlgorithmEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AlgorithmEntry")
+            .field("name", &self.name)
+            .field("prefix", &self.prefix)
+            .field("digest_hex_len", &self.digest_hex_len)
+            .field("cryptographic", &self.cryptographic)
+            .field("multihash_code", &self.multihash_code)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for AlgorithmEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl Eq for AlgorithmEntry {}
+
+pub static SHA256: AlgorithmEntry = AlgorithmEntry {
+    name: "sha256",
+    prefix: "sha256",
+    digest_hex_len: 64,
+    cryptographic: true,
+    multihash_code: Some(0x12),
+    new_hasher: |_, _| Box::new(DigestHasher::<sha2::Sha256>::new()),
+    mode_label: None,
+};
+
+pub static BLAKE3: AlgorithmEntry = AlgorithmEntry {
+    name: "blake3",
+    prefix: "blake3",
+    digest_hex_len: 64,
+    cryptographic: true,
+    multihash_code: Some(0x1e),
+    new_hasher: |options, _| Box::new(Blake3Hasher::new(&options.blake3)),
+    mode_label: Some(|options| options.blake3.label()),
+};
+
+pub static SHA384: AlgorithmEntry = AlgorithmEntry {
+    name: "sha384",
+    prefix: "sha384",
+    digest_hex_len: 96,
+    cryptographic: true,
+    multihash_code: Some(0x20),
+    new_hasher: |_, _| Box::new(DigestHasher::<sha2::Sha384>::new()),
+    mode_label: None,
+};
+
+pub static SHA512: AlgorithmEntry = AlgorithmEntry {
+    name: "sha512",
+    prefix: "sha512",
+    digest_hex_len: 128,
+    cryptographic: true,
+    multihash_code: Some(0x13),
+    new_hasher: |_, _| Box::new(DigestHasher::<sha2::Sha512>::new()),
+    mode_label: None,
+};
+
+pub static SHA3_256: AlgorithmEntry = AlgorithmEntry {
+    name: "sha3-256",
+    prefix: "sha3-256",
+    digest_hex_len: 64,
+    cryptographic: true,
+    multihash_code: Some(0x16),
+    new_hasher: |_, _| Box::new(DigestHasher::<sha3::Sha3_256>::new()),
+    mode_label: None,
+};
+
+pub static XXH3_128: AlgorithmEntry = AlgorithmEntry {
+    name: "xxh3-128",
+    prefix: "xxh3",
+    digest_hex_len: 32,
+    cryptographic: false,
+    multihash_code: None,
+    new_hasher: |_, _| Box::new(Xxh3Hasher::new()),
+    mode_label: None,
+};
+
+pub static GIT_SHA1: AlgorithmEntry = AlgorithmEntry {
+    name: "git-sha1",
+    prefix: "gitsha1",
+    digest_hex_len: 40,
+    cryptographic: true,
+    multihash_code: Some(0x11),
+    new_hasher: |_, content_len| {
+        let mut hasher = DigestHasher::<sha1::Sha1>::new();
+        hasher.update(&git::object_header("blob", content_len));
+        Box::new(hasher)
+    },
+    mode_label: None,
+};
+
+pub static GIT_SHA256: AlgorithmEntry = AlgorithmEntry {
+    name: "git-sha256",
+    prefix: "gitsha256",
+    digest_hex_len: 64,
+    cryptographic: true,
+    multihash_code: Some(0x12),
+    new_hasher: |_, content_len| {
+        let mut hasher = DigestHasher::<sha2::Sha256>::new();
+        hasher.update(&git::object_header("blob", content_len));
+        Box::new(hasher)
+    },
+    mode_label: None,
+};
+
+/// Prefixes emitted by built-in BLAKE3 modes rather than by their own entry.
+const RESERVED_PREFIXES: [&str; 3] = ["blake3-keyed", "blake3-derive", "blake3-xof"];
+
+/// Algorithms added at runtime by [`register`], after the built-ins.
+static REGISTERED: RwLock<Vec<&'static AlgorithmEntry>> = RwLock::new(Vec::new());
+
+/// Every available algorithm: built-ins first, then registrations in order.
+pub fn algorithms() -> Vec<Algorithm> {
+    let mut algorithms = Algorithm::BUILTIN.to_vec();
+    if let Ok(registered) = REGISTERED.read() {
+        algorithms.extend(registered.iter().copied().map(Algorithm::Registered));
+    }
+    algorithms
+}
+
+/// Find an algorithm by name, case-insensitively.
+pub fn lookup(name: &str) -> Option<Algorithm> {
+    algorithms()
+        .into_iter()
+        .find(|algorithm| name.eq_ignore_ascii_case(algorithm.name()))
+}
+
+/// Add an algorithm for the rest of the process. Call this before
+/// [`crate::run`]; names and prefixes must not collide with existing entries,
+/// so a registration can never change what an existing digest means.
+///
+/// Registered digests fall outside the `hash.v0` schema's `bytes_hash`
+/// pattern, so consumers validating against that schema must allow them.
+pub fn register(entry: AlgorithmEntry) -> Result<Algorithm, String> {
+    let valid_name = |value: &str| {
+        !value.is_empty()
+            && value
+                .bytes()
+                .all(|byte| byte.is_ascii_lowercase() || byte.is_ascii_digit() || byte == b'-')
+    };
+    if !valid_name(entry.name) || !valid_name(entry.prefix) {
+        return Err(format!(
+            "algorithm name '{}' and prefix '{}' must be lowercase [a-z0-9-]",
+            entry.name, entry.prefix
+        ));
+    }
+    if entry.digest_hex_len == 0 {
+        return Err(format!(
+            "algorithm '{}' must declare a digest length",
+            entry.name
+        ));
+    }
+
+    if RESERVED_PREFIXES.iter().any(|reserved| {
+        reserved.eq_ignore_ascii_case(entry.name) || reserved.eq_ignore_ascii_case(entry.prefix)
+    }) {
+        return Err(format!(
+            "algorithm '{}' uses a reserved BLAKE3 prefix",
+            entry.name
+        ));
+    }
+
+    let mut registered = REGISTERED
+        .write()
+        .map_err(|_| "algorithm registry is poisoned".to_owned())?;
+    let taken = Algorithm::BUILTIN
+        .iter()
+        .map(Algorithm::entry)
+        .chain(registered.iter().copied())
+        .find(|existing| {
+            existing.name.eq_ignore_ascii_case(entry.name)
+                || existing.prefix.eq_ignore_ascii_case(entry.prefix)
+        });
+    if let Some(existing) = taken {
+        return Err(format!(
+            "algorithm '{}' collides with registered '{}'",
+            entry.name, existing.name
+        ));
+    }
+
+    let entry: &'static AlgorithmEntry = Box::leak(Box::new(entry));
+    registered.push(entry);
+    Ok(Algorithm::Registered(entry))
+}
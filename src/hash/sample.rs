@@ -0,0 +1,171 @@
+use super::compute::HashOptions;
+use super::hasher::finalize_prefixed;
+use super::range::ByteRange;
+use std::fs::File;
+use std::io::{self, Cursor, Read, Seek, SeekFrom};
+use std::path::Path;
+
+pub const DEFAULT_EDGE_LEN: u64 = 1024 * 1024;
+pub const DEFAULT_WINDOWS: u64 = 16;
+pub const DEFAULT_WINDOW_LEN: u64 = 64 * 1024;
+
+/// Fed ahead of everything else so a sample hash never equals the plain
+/// digest of some other byte string.
+const DOMAIN: &[u8] = b"hashbytes-sample-v1\0";
+
+/// Which bytes `--sample-hash` reads: the first and last `edge_len` bytes
+/// and `windows` windows of `window_len` bytes spread evenly between them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SampleOptions {
+    pub edge_len: u64,
+    pub windows: u64,
+    pub window_len: u64,
+}
+
+impl Default for SampleOptions {
+    fn default() -> Self {
+        Self {
+            edge_len: DEFAULT_EDGE_LEN,
+            windows: DEFAULT_WINDOWS,
+            window_len: DEFAULT_WINDOW_LEN,
+        }
+    }
+}
+
+impl SampleOptions {
+    /// Sorted, non-overlapping `(offset, length)` ranges to read from content
+    /// of `content_len` bytes. Content too small to sample is read whole.
+    pub fn ranges(&self, content_len: u64) -> Vec<(u64, u64)> {
+        let sampled = self
+            .edge_len
+            .saturating_mul(2)
+            .saturating_add(self.windows.saturating_mul(self.window_len));
+        if content_len <= sampled {
+            return vec![(0, content_len)];
+        }
+
+        let middle_len = content_len - 2 * self.edge_len;
+        let mut ranges = vec![(0, self.edge_len)];
+        for window in 1..=self.windows {
+            // Window starts divide the middle evenly, first and last
+            // windows kept clear of the edges.
+            let start = (u128::from(middle_len - self.window_len) * u128::from(window)
+                / u128::from(self.windows + 1)) as u64;
+            ranges.push((self.edge_len + start, self.window_len));
+        }
+        ranges.push((content_len - self.edge_len, self.edge_len));
+
+        let mut merged: Vec<(u64, u64)> = Vec::with_capacity(ranges.len());
+        for (offset, length) in ranges {
+            match merged.last_mut() {
+                Some((last_offset, last_length)) if *last_offset + *last_length >= offset => {
+                    *last_length = (*last_length).max(offset + length - *last_offset);
+                }
+                _ => merged.push((offset, length)),
+            }
+        }
+        merged
+    }
+}
+
+/// Result of sampling one file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SampleDigest {
+    /// Primary-algorithm digest, `<prefix>:<hex>`, of the domain tag, the
+    /// content length, the sample parameters and the sampled bytes in order.
+    pub digest: String,
+    pub sampled_len: u64,
+    /// The range that was sampled, with its length resolved, when only part
+    /// of the file was requested.
+    pub range: Option<ByteRange>,
+}
+
+/// Sample the file, or only `range` of it, with the primary algorithm,
+/// reading just the bytes [`SampleOptions::ranges`] selects. A whole file
+/// whose metadata size cannot be trusted — anything but a non-empty regular
+/// file, such as a FIFO or a pseudo-file — is read to EOF and sampled from
+/// memory.
+pub fn sample_file(
+    path: &Path,
+    range: Option<ByteRange>,
+    sample: SampleOptions,
+    options: &HashOptions,
+) -> io::Result<SampleDigest> {
+    let mut file = File::open(path)?;
+    let metadata = file.metadata()?;
+    let (digest, sampled_len, content_len) = match range {
+        Some(range) => {
+            let content_len = range
+                .resolve(metadata.len())
+                .map_err(|err| io::Error::new(io::ErrorKind::UnexpectedEof, err))?;
+            let (digest, sampled_len) =
+                sample_content(&mut file, range.offset, content_len, sample, options)?;
+            (digest, sampled_len, content_len)
+        }
+        None if metadata.is_file() && metadata.len() > 0 => {
+            let (digest, sampled_len) =
+                sample_content(&mut file, 0, metadata.len(), sample, options)?;
+            (digest, sampled_len, metadata.len())
+        }
+        None => {
+            let mut content = Vec::new();
+            file.read_to_end(&mut content)?;
+            let content_len = content.len() as u64;
+            let (digest, sampled_len) =
+                sample_content(&mut Cursor::new(content), 0, content_len, sample, options)?;
+            (digest, sampled_len, content_len)
+        }
+    };
+
+    Ok(SampleDigest {
+        digest,
+        sampled_len,
+        range: range.map(|range| ByteRange {
+            offset: range.offset,
+            length: Some(content_len),
+        }),
+    })
+}
+
+/// Hash the samples of the `content_len` bytes of `source` starting at
+/// `base`, returning the prefixed digest and the number of bytes read.
+fn sample_content<R: Read + Seek>(
+    source: &mut R,
+    base: u64,
+    content_len: u64,
+    sample: SampleOptions,
+    options: &HashOptions,
+) -> io::Result<(String, u64)> {
+    let mut hasher = options.new_hasher(options.primary(), 0);
+    hasher.update(DOMAIN);
+    for value in [
+        content_len,
+        sample.edge_len,
+        sample.windows,
+        sample.window_len,
+    ] {
+        hasher.update(&value.to_le_bytes());
+    }
+
+    let mut sampled_len = 0;
+    for (offset, length) in sample.ranges(content_len) {
+        source.seek(SeekFrom::Start(base + offset))?;
+        let mut read = 0;
+        super::stream::for_each_buffer_from((&mut *source).take(length), |buf| {
+            hasher.update(buf);
+            read += buf.len() as u64;
+        })?;
+        if read != length {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "file shrank while it was being sampled",
+            ));
+        }
+        sampled_len += read;
+    }
+
+    Ok((
+        finalize_prefixed(options.digest_prefix(options.primary()), hasher),
+        sampled_len,
+    ))
+}
@@ -1,22 +1,8 @@
-use sha2::{Digest, Sha256};
-use std::fs::File;
-use std::io::{self, BufRead, BufReader};
+use crate::cli::Algorithm;
+use std::io;
 use std::path::Path;
 
+/// SHA-256 of the file as `sha256:<hex>`, computed by the registry entry.
 pub fn hash_file(path: &Path) -> Result<String, io::Error> {
-    let file = File::open(path)?;
-    let mut reader = BufReader::with_capacity(64 * 1024, file); // 64 KB buffer
-
-    let mut hasher = Sha256::new();
-    loop {
-        let buf = reader.fill_buf()?;
-        if buf.is_empty() {
-            break;
-        }
-        hasher.update(buf);
-        let len = buf.len();
-        reader.consume(len);
-    }
-
-    Ok(format!("sha256:{:x}", hasher.finalize()))
+    super::compute::hash_file(path, Algorithm::Sha256)
 }
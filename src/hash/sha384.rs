@@ -0,0 +1,8 @@
+use crate::cli::Algorithm;
+use std::io;
+use std::path::Path;
+
+/// SHA-384 of the file as `sha384:<hex>`, computed by the registry entry.
+pub fn hash_file(path: &Path) -> Result<String, io::Error> {
+    super::compute::hash_file(path, Algorithm::Sha384)
+}
@@ -0,0 +1,173 @@
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::time::SystemTime;
+
+/// Retries after a file changes mid-hash before its record is skipped.
+pub const DEFAULT_CHANGE_RETRIES: u32 = 2;
+
+/// The metadata a writer touches: compared before and after each read pass
+/// so a digest never mixes bytes from two versions of a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileSnapshot {
+    pub size: u64,
+    pub mtime: Option<SystemTime>,
+    /// Seconds and nanoseconds; `None` off Unix.
+    pub ctime: Option<(i64, i64)>,
+    /// Device and inode; `None` off Unix.
+    pub inode: Option<(u64, u64)>,
+}
+
+impl FileSnapshot {
+    pub fn of(path: &Path) -> io::Result<Self> {
+        let metadata = std::fs::metadata(path)?;
+        Ok(Self {
+            size: metadata.len(),
+            mtime: metadata.modified().ok(),
+            ctime: ctime(&metadata),
+            inode: inode(&metadata),
+        })
+    }
+
+    /// Names of the fields that differ from `other`.
+    pub fn changed_fields(&self, other: &Self) -> Vec<&'static str> {
+        [
+            ("size", self.size != other.size),
+            ("mtime", self.mtime != other.mtime),
+            ("ctime", self.ctime != other.ctime),
+            ("inode", self.inode != other.inode),
+        ]
+        .into_iter()
+        .filter(|(_, changed)| *changed)
+        .map(|(field, _)| field)
+        .collect()
+    }
+}
+
+#[cfg(unix)]
+fn ctime(metadata: &std::fs::Metadata) -> Option<(i64, i64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.ctime(), metadata.ctime_nsec()))
+}
+
+#[cfg(not(unix))]
+fn ctime(_metadata: &std::fs::Metadata) -> Option<(i64, i64)> {
+    None
+}
+
+#[cfg(unix)]
+fn inode(metadata: &std::fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn inode(_metadata: &std::fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// The file kept changing while it was read, on every attempt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangedDuringHash {
+    pub attempts: u32,
+    /// Fields that differed across the last attempt.
+    pub changed: Vec<&'static str>,
+}
+
+impl fmt::Display for ChangedDuringHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "file changed while being hashed ({}) on {} attempt(s)",
+            self.changed.join(", "),
+            self.attempts
+        )
+    }
+}
+
+impl std::error::Error for ChangedDuringHash {}
+
+/// Run `hash` over `path` until the file's snapshot is the same before and
+/// after a pass, at most `retries + 1` times. A file that changes on every
+/// attempt fails with an error wrapping [`ChangedDuringHash`]. Anything but
+/// a regular file, such as a FIFO, is read once: it cannot be read again and
+/// its metadata does not track its content.
+pub fn hash_unchanged<T>(
+    path: &Path,
+    retries: u32,
+    mut hash: impl FnMut() -> io::Result<T>,
+) -> io::Result<T> {
+    if !std::fs::metadata(path)?.is_file() {
+        return hash();
+    }
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let before = FileSnapshot::of(path)?;
+        let hashed = hash()?;
+        let after = FileSnapshot::of(path)?;
+        if before == after {
+            return Ok(hashed);
+        }
+        if attempts > retries {
+            return Err(io::Error::other(ChangedDuringHash {
+                attempts,
+                changed: before.changed_fields(&after),
+            }));
+        }
+    }
+}
+
+/// The [`ChangedDuringHash`] carried by an error from [`hash_unchanged`], if any.
+pub fn changed_during_hash(err: &io::Error) -> Option<&ChangedDuringHash> {
+    err.get_ref()?.downcast_ref()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    fn temp_file(contents: &[u8]) -> std::path::PathBuf {
+        let counter = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let path =
+            std::env::temp_dir().join(format!("hash-snapshot-{}-{counter}", std::process::id()));
+        std::fs::write(&path, contents).expect("write temp file");
+        path
+    }
+
+    #[test]
+    fn a_write_during_the_first_pass_is_retried() {
+        let path = temp_file(b"before");
+        let mut passes = 0;
+        let hashed = hash_unchanged(&path, 1, || {
+            passes += 1;
+            if passes == 1 {
+                std::fs::write(&path, b"appended mid-read")?;
+            }
+            std::fs::read(&path)
+        })
+        .expect("second pass is stable");
+        assert_eq!(passes, 2);
+        assert_eq!(hashed, b"appended mid-read");
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn a_file_that_keeps_changing_fails_after_the_retries() {
+        let path = temp_file(b"");
+        let mut passes = 0;
+        let err = hash_unchanged(&path, 2, || {
+            passes += 1;
+            std::fs::write(&path, vec![b'x'; passes])
+        })
+        .expect_err("never stable");
+        assert_eq!(passes, 3);
+        let changed = changed_during_hash(&err).expect("changed during hash");
+        assert_eq!(changed.attempts, 3);
+        assert!(changed.changed.contains(&"size"));
+        let _ = std::fs::remove_file(&path);
+    }
+}
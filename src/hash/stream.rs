@@ -0,0 +1,65 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+
+pub const BUFFER_SIZE: usize = 64 * 1024; // 64 KB buffer
+
+/// Stream a file through a fixed-size buffer, handing every filled buffer to
+/// `consume` in file order.
+pub fn for_each_buffer<F>(path: &Path, consume: F) -> Result<(), io::Error>
+where
+    F: FnMut(&[u8]),
+{
+    for_each_buffer_from(File::open(path)?, consume)
+}
+
+/// Like [`for_each_buffer`], for an already opened (possibly range-limited)
+/// reader.
+pub fn for_each_buffer_from<R, F>(source: R, mut consume: F) -> Result<(), io::Error>
+where
+    R: Read,
+    F: FnMut(&[u8]),
+{
+    let mut reader = BufReader::with_capacity(BUFFER_SIZE, source);
+
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            break;
+        }
+        consume(buf);
+        let len = buf.len();
+        reader.consume(len);
+    }
+
+    Ok(())
+}
+
+/// Reader adapter that shows every byte it yields to `observe`, letting a
+/// pull-style encoder drive the same read pass as push-style hashers.
+pub struct TeeReader<R, F> {
+    inner: R,
+    observe: F,
+}
+
+impl<R, F> TeeReader<R, F>
+where
+    R: Read,
+    F: FnMut(&[u8]),
+{
+    pub fn new(inner: R, observe: F) -> Self {
+        Self { inner, observe }
+    }
+}
+
+impl<R, F> Read for TeeReader<R, F>
+where
+    R: Read,
+    F: FnMut(&[u8]),
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        (self.observe)(&buf[..read]);
+        Ok(read)
+    }
+}
@@ -0,0 +1,20 @@
+use crate::cli::Algorithm;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use xxhash_rust::xxh3::Xxh3;
+
+/// XXH3-128 is a fast non-cryptographic checksum for scratch and caching
+/// workloads; its digests are never identity-grade.
+pub fn hash_file(path: &Path) -> Result<String, io::Error> {
+    super::compute::hash_file(path, Algorithm::Xxh3_128)
+}
+
+/// XXH3-128 of at most the first `len` bytes of the file, used to pre-screen
+/// same-sized files under `--dedup-only` before any cryptographic hash.
+pub fn hash_prefix(path: &Path, len: u64) -> Result<String, io::Error> {
+    let mut hasher = Xxh3::new();
+    super::stream::for_each_buffer_from(File::open(path)?.take(len), |buf| hasher.update(buf))?;
+
+    Ok(format!("xxh3:{:032x}", hasher.digest128()))
+}
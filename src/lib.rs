@@ -3,8 +3,10 @@
 use clap::Parser;
 use serde_json::{Map, Value};
 
+pub mod bao_verify;
 pub mod cli;
 pub mod doctor;
+pub mod duplicates;
 pub mod hash;
 pub mod output;
 pub mod pipeline;
@@ -44,6 +46,11 @@ struct ProcessedRecord {
     record: Value,
     warning_event: Option<progress::WarningEvent>,
     skipped: bool,
+    cdc_chunks: Option<Vec<hash::CdcChunk>>,
+    /// Directory record waiting for its git tree id (`--git-tree`).
+    git_tree_dir: bool,
+    /// Archive member records emitted right after this one (`--expand-archives`).
+    members: Vec<ProcessedRecord>,
 }
 
 struct StreamState<'a> {
@@ -54,6 +61,17 @@ struct StreamState<'a> {
     processed: &'a mut usize,
     any_skipped: &'a mut bool,
     progress_started_at: std::time::Instant,
+    cdc_summary: Option<hash::CdcSummary>,
+    /// Records held back until the whole manifest is hashed, so directory
+    /// records can see every file below them (`--git-tree`) and the first
+    /// copy of some content can name later ones (`--annotate-duplicates`).
+    deferred: Option<Vec<ProcessedRecord>>,
+    /// Write checksum-file lines instead of JSONL (`--emit-sums`).
+    sums_format: Option<pipeline::sums::SumsFormat>,
+    /// Previous run each record is compared with (`--baseline`).
+    baseline: Option<pipeline::baseline::Baseline>,
+    /// Leave `unchanged` records out (`--changed-only`).
+    changed_only: bool,
 }
 
 /// Main entry point that handles all errors internally and returns exit code
@@ -84,6 +102,23 @@ pub fn run_with_cli(cli: cli::Cli) -> u8 {
         return 0;
     }
 
+    // Duplicate reports only read the named manifest
+    if let Some(cli::Command::Duplicates { manifest }) = &cli.command {
+        return duplicates::handle_command(manifest);
+    }
+
+    // Bao verification only reads the named file and outboard
+    if let Some(cli::Command::BaoVerify {
+        path,
+        outboard,
+        hash,
+        offset,
+        length,
+    }) = &cli.command
+    {
+        return bao_verify::handle_command(path, outboard, hash, *offset, *length);
+    }
+
     // Handle witness subcommands
     if let Some(cli::Command::Witness { action }) = &cli.command {
         return handle_witness_command(action);
@@ -95,8 +130,57 @@ pub fn run_with_cli(cli: cli::Cli) -> u8 {
     result.exit_code()
 }
 
+const OPERATOR_MANIFEST: &str = include_str!("../operator.json");
+
+/// The `choices` list of the `algorithm` option in the compiled manifest.
+pub(crate) fn operator_algorithm_choices() -> Option<Vec<String>> {
+    let manifest: Value = serde_json::from_str(OPERATOR_MANIFEST).ok()?;
+    algorithm_option(&manifest)?
+        .get("choices")?
+        .as_array()?
+        .iter()
+        .map(|choice| choice.as_str().map(str::to_owned))
+        .collect()
+}
+
+fn algorithm_option(manifest: &Value) -> Option<&Value> {
+    manifest
+        .get("options")?
+        .as_array()?
+        .iter()
+        .find(|option| option.get("name").and_then(Value::as_str) == Some("algorithm"))
+}
+
+fn registered_algorithm_names() -> Vec<String> {
+    cli::Algorithm::all()
+        .iter()
+        .map(|algorithm| algorithm.name().to_owned())
+        .collect()
+}
+
 fn print_operator_json() {
-    const OPERATOR_MANIFEST: &str = include_str!("../operator.json");
+    let registered = registered_algorithm_names();
+
+    // The shipped manifest lists the built-ins verbatim; re-render only when a
+    // downstream crate has registered more.
+    if operator_algorithm_choices().as_ref() != Some(&registered)
+        && let Ok(mut manifest) = serde_json::from_str::<Value>(OPERATOR_MANIFEST)
+        && let Some(option) = manifest
+            .get_mut("options")
+            .and_then(Value::as_array_mut)
+            .and_then(|options| {
+                options
+                    .iter_mut()
+                    .find(|option| option.get("name").and_then(Value::as_str) == Some("algorithm"))
+            })
+    {
+        option["choices"] = Value::from(registered);
+        if let Ok(rendered) = serde_json::to_string_pretty(&manifest) {
+            println!("{rendered}");
+            return;
+        }
+    }
+
     print!("{OPERATOR_MANIFEST}");
 
     if !OPERATOR_MANIFEST.ends_with('\n') {
@@ -121,9 +205,9 @@ fn handle_witness_command(action: &cli::WitnessAction) -> u8 {
 }
 
 fn handle_main_workflow(cli: &cli::Cli) -> RunResult {
-    // Validate and parse algorithm
-    let algorithm = match cli.algorithm.parse::<cli::Algorithm>() {
-        Ok(alg) => alg,
+    // Validate and parse algorithms; the first one is primary
+    let algorithms = match cli::parse_algorithm_list(&cli.algorithm) {
+        Ok(algorithms) => algorithms,
         Err(err) => {
             return refusal_result(refusal::RefusalEnvelope::from_code(
                 refusal::RefusalCode::BadInput,
@@ -135,21 +219,47 @@ fn handle_main_workflow(cli: &cli::Cli) -> RunResult {
         }
     };
 
-    // Open input source (file or stdin)
-    let input_reader: Box<dyn std::io::BufRead> = match &cli.input {
-        Some(path) => match std::fs::File::open(path) {
+    let hash_options = match hash_options(cli, algorithms) {
+        Ok(options) => options,
+        Err(refusal_envelope) => return refusal_result(*refusal_envelope),
+    };
+
+    // Open input source (file or stdin); `verify` names its manifest, and
+    // `check` and `verify --lockfile` turn their files into one
+    let input = match &cli.command {
+        Some(cli::Command::Verify { manifest, .. }) => manifest.as_ref(),
+        _ => cli.input.as_ref(),
+    };
+    let input_reader: Box<dyn std::io::BufRead> = match (&cli.command, input) {
+        (
+            Some(cli::Command::Verify {
+                lockfile: Some(lockfile),
+                manifest,
+            }),
+            _,
+        ) => match pipeline::lockfile::read_lockfile(lockfile, manifest.as_deref()) {
+            Ok(records) => Box::new(std::io::Cursor::new(records)),
+            Err(refusal_envelope) => return refusal_result(*refusal_envelope),
+        },
+        (Some(cli::Command::Check { sums, algorithm }), _) => {
+            match pipeline::sums::read_sums_file(sums, algorithm.as_deref()) {
+                Ok(records) => Box::new(std::io::Cursor::new(records)),
+                Err(refusal_envelope) => return refusal_result(*refusal_envelope),
+            }
+        }
+        (_, Some(path)) => match std::fs::File::open(path) {
             Ok(file) => Box::new(std::io::BufReader::new(file)),
             Err(err) => {
                 return refusal_result(refusal::RefusalEnvelope::io_error(err.to_string()));
             }
         },
-        None => Box::new(std::io::BufReader::new(std::io::stdin())),
+        (_, None) => Box::new(std::io::BufReader::new(std::io::stdin())),
     };
 
     let jobs = pipeline::parallel::normalized_jobs(cli.jobs);
 
     // Process JSONL stream
-    match process_jsonl_stream(input_reader, algorithm, jobs, cli.progress) {
+    match process_jsonl_stream(input_reader, &hash_options, jobs, cli.progress) {
         Ok(stream_outcome) => RunResult::new(stream_outcome.outcome, stream_outcome.output_hash),
         Err(refusal_envelope) => refusal_result(*refusal_envelope),
     }
@@ -157,7 +267,7 @@ fn handle_main_workflow(cli: &cli::Cli) -> RunResult {
 
 fn process_jsonl_stream(
     mut reader: Box<dyn std::io::BufRead>,
-    algorithm: cli::Algorithm,
+    hash_options: &hash::HashOptions,
     jobs: usize,
     progress_enabled: bool,
 ) -> Result<StreamOutcome, Box<refusal::RefusalEnvelope>> {
@@ -180,6 +290,15 @@ fn process_jsonl_stream(
         processed: &mut processed,
         any_skipped: &mut any_skipped,
         progress_started_at: std::time::Instant::now(),
+        cdc_summary: hash_options.cdc.then(hash::CdcSummary::default),
+        deferred: (hash_options.git_tree || hash_options.annotate_duplicates).then(Vec::new),
+        sums_format: hash_options.emit_sums,
+        baseline: hash_options
+            .baseline
+            .as_deref()
+            .map(pipeline::baseline::Baseline::read)
+            .transpose()?,
+        changed_only: hash_options.changed_only,
     };
 
     loop {
@@ -207,17 +326,52 @@ fn process_jsonl_stream(
             record: parsed_line.record,
         });
 
-        if pending_records.len() >= batch_size {
+        // Dedup screening needs every size before it can settle any record.
+        if !hash_options.dedup_only && pending_records.len() >= batch_size {
             flush_pending_records(
                 std::mem::take(&mut pending_records),
-                algorithm,
+                hash_options,
                 jobs,
                 &mut stream_state,
             )?;
         }
     }
 
-    flush_pending_records(pending_records, algorithm, jobs, &mut stream_state)?;
+    if hash_options.dedup_only {
+        flush_dedup_records(pending_records, hash_options, jobs, &mut stream_state)?;
+    } else {
+        flush_pending_records(pending_records, hash_options, jobs, &mut stream_state)?;
+    }
+
+    if let Some(mut deferred) = stream_state.deferred.take() {
+        if hash_options.git_tree {
+            resolve_git_trees(&mut deferred, hash_options);
+        }
+        if hash_options.annotate_duplicates {
+            annotate_duplicates(&mut deferred);
+        }
+        for processed_record in deferred {
+            finish_processed_record(processed_record, &mut stream_state)?;
+        }
+    }
+
+    // Baseline records nothing in this run matched trail the output.
+    if let Some(baseline) = stream_state.baseline.take() {
+        for mut record in baseline.removed() {
+            pipeline::enricher::set_change(&mut record, pipeline::baseline::Change::Removed);
+            emit_processed_record(&record, &mut stream_state)?;
+        }
+    }
+
+    // Every --cdc run ends with the summary, in the stderr format in use.
+    if let Some(summary) = &stream_state.cdc_summary {
+        let summary_event = progress::CdcSummaryEvent::new(summary);
+        let _ = if progress_enabled {
+            progress::write_cdc_summary(stream_state.stderr, &summary_event)
+        } else {
+            progress::write_cdc_summary_line(stream_state.stderr, &summary_event)
+        };
+    }
 
     // Determine final outcome based on whether any records were skipped
     let outcome = if any_skipped {
@@ -232,18 +386,317 @@ fn process_jsonl_stream(
     })
 }
 
+fn hash_options(
+    cli: &cli::Cli,
+    algorithms: Vec<cli::Algorithm>,
+) -> Result<hash::HashOptions, Box<refusal::RefusalEnvelope>> {
+    let bad_input = |flag: &str, error: String| {
+        Box::new(refusal::RefusalEnvelope::from_code(
+            refusal::RefusalCode::BadInput,
+            serde_json::json!({ "flag": flag, "error": error }),
+        ))
+    };
+
+    let verify_command = match cli.command {
+        Some(cli::Command::Verify { .. }) => Some("verify"),
+        Some(cli::Command::Check { .. }) => Some("check"),
+        _ => None,
+    };
+
+    let blake3_flag = if cli.blake3_key_file.is_some() {
+        Some("--blake3-key-file")
+    } else if cli.blake3_context.is_some() {
+        Some("--blake3-context")
+    } else if cli.digest_length.is_some() {
+        Some("--digest-length")
+    } else {
+        None
+    };
+    // Verification takes the key or context for the keyed and derive-key
+    // records it re-hashes, whatever `--algorithm` says; `--digest-length`
+    // is refused with the other hashing flags below.
+    if let Some(flag) = blake3_flag
+        && !algorithms.contains(&cli::Algorithm::Blake3)
+        && verify_command.is_none()
+    {
+        return Err(bad_input(flag, "requires blake3 in --algorithm".to_owned()));
+    }
+
+    if cli.bao_outboard.is_some() && !algorithms.contains(&cli::Algorithm::Blake3) {
+        return Err(bad_input(
+            "--bao-outboard",
+            "requires blake3 in --algorithm".to_owned(),
+        ));
+    }
+
+    let mut options = hash::HashOptions::new(algorithms);
+
+    if let Some(key_path) = &cli.blake3_key_file {
+        let contents = std::fs::read(key_path).map_err(|err| {
+            Box::new(refusal::RefusalEnvelope::from_code(
+                refusal::RefusalCode::Io,
+                serde_json::json!({
+                    "path": key_path.to_string_lossy(),
+                    "error": err.to_string()
+                }),
+            ))
+        })?;
+        let key = hash::blake3::parse_key(&contents)
+            .map_err(|err| bad_input("--blake3-key-file", err))?;
+        options.blake3.mode = hash::blake3::Blake3Mode::Keyed(key);
+    }
+
+    if let Some(context) = &cli.blake3_context {
+        if context.is_empty() {
+            return Err(bad_input(
+                "--blake3-context",
+                "context must not be empty".to_owned(),
+            ));
+        }
+        options.blake3.mode = hash::blake3::Blake3Mode::DeriveKey(context.clone());
+    }
+
+    if let Some(digest_length) = cli.digest_length {
+        if !(hash::blake3::MIN_DIGEST_LENGTH..=hash::blake3::MAX_DIGEST_LENGTH)
+            .contains(&digest_length)
+        {
+            return Err(bad_input(
+                "--digest-length",
+                format!(
+                    "digest length must be between {} and {} bytes",
+                    hash::blake3::MIN_DIGEST_LENGTH,
+                    hash::blake3::MAX_DIGEST_LENGTH
+                ),
+            ));
+        }
+        options.blake3.digest_length = digest_length;
+    }
+
+    if let Some(dir) = &cli.bao_outboard {
+        // Bao trees are defined over plain BLAKE3 with a 32-byte root.
+        if !options.blake3.is_default() {
+            return Err(bad_input(
+                "--bao-outboard",
+                "requires plain blake3 (no key, context or digest length)".to_owned(),
+            ));
+        }
+        std::fs::create_dir_all(dir).map_err(|err| {
+            Box::new(refusal::RefusalEnvelope::from_code(
+                refusal::RefusalCode::Io,
+                serde_json::json!({
+                    "path": dir.to_string_lossy(),
+                    "error": err.to_string()
+                }),
+            ))
+        })?;
+        options.bao_outboard_dir = Some(dir.clone());
+    }
+
+    if let Some(chunk_size) = &cli.chunk_size {
+        let chunk_size =
+            cli::parse_byte_size(chunk_size).map_err(|err| bad_input("--chunk-size", err))?;
+        options.chunk_size = Some(chunk_size);
+    }
+    options.cdc = cli.cdc;
+
+    if let Some(part_size) = &cli.s3_part_size {
+        let part_size =
+            cli::parse_byte_size(part_size).map_err(|err| bad_input("--s3-part-size", err))?;
+        if !(hash::object_store::MIN_S3_PART_SIZE..=hash::object_store::MAX_S3_PART_SIZE)
+            .contains(&part_size)
+        {
+            return Err(bad_input(
+                "--s3-part-size",
+                format!("S3 part size must be between 5MiB and 5GiB, got {part_size} bytes"),
+            ));
+        }
+        options.object_store = Some(hash::ObjectStoreOptions {
+            s3_part_size: part_size,
+        });
+    } else if cli.object_store_checksums {
+        options.object_store = Some(hash::ObjectStoreOptions::default());
+    }
+    options.ipfs_cid = cli.ipfs_cid;
+    options.fsverity = cli.fsverity;
+
+    if let Some(encoding) = &cli.encoding {
+        let encoding = encoding
+            .parse::<hash::DigestEncoding>()
+            .map_err(|err| bad_input("--encoding", err))?;
+        encoding
+            .check(&options)
+            .map_err(|err| bad_input("--encoding", err))?;
+        options.encoding = Some(encoding);
+    }
+
+    if let Some(formats) = &cli.inner_hash {
+        // Git blob ids hash the length first, which a stream cannot know.
+        if matches!(
+            options.primary(),
+            cli::Algorithm::GitSha1 | cli::Algorithm::GitSha256
+        ) {
+            return Err(bad_input(
+                "--inner-hash",
+                "requires a primary algorithm other than git-sha1 or git-sha256".to_owned(),
+            ));
+        }
+        options.inner_hash = hash::inner::parse_compression_list(formats)
+            .map_err(|err| bad_input("--inner-hash", err))?;
+    }
+
+    if cli.git_tree {
+        if !matches!(
+            options.primary(),
+            cli::Algorithm::GitSha1 | cli::Algorithm::GitSha256
+        ) {
+            return Err(bad_input(
+                "--git-tree",
+                "requires git-sha1 or git-sha256 as the primary --algorithm".to_owned(),
+            ));
+        }
+        options.git_tree = true;
+    }
+
+    options.expand_archives = cli.expand_archives;
+    if let Some(retries) = cli.change_retries {
+        options.change_retries = retries;
+    }
+    options.annotate_duplicates = cli.annotate_duplicates;
+
+    if cli.sample_hash {
+        options.sample =
+            Some(sample_options(cli, &options).map_err(|err| bad_input("--sample-hash", err))?);
+    } else {
+        let sample_flags = [
+            ("--sample-edge", cli.sample_edge.is_some()),
+            ("--sample-windows", cli.sample_windows.is_some()),
+            ("--sample-window-size", cli.sample_window_size.is_some()),
+        ];
+        if let Some((flag, _)) = sample_flags.iter().find(|(_, set)| *set) {
+            return Err(bad_input(flag, "requires --sample-hash".to_owned()));
+        }
+    }
+
+    if let Some(command) = verify_command {
+        // Verification re-hashes with each record's own algorithm and adds
+        // nothing else, so options that shape new records do not apply.
+        let hashing_flag = full_read_flag(cli, &options).or_else(|| {
+            [
+                ("--sample-hash", cli.sample_hash),
+                ("--dedup-only", cli.dedup_only),
+                ("--digest-length", cli.digest_length.is_some()),
+            ]
+            .into_iter()
+            .find(|(_, set)| *set)
+            .map(|(flag, _)| flag)
+        });
+        if let Some(flag) = hashing_flag {
+            return Err(bad_input(
+                command,
+                format!("cannot be combined with {flag}"),
+            ));
+        }
+        options.verify = true;
+        options.lock_drift = matches!(
+            cli.command,
+            Some(cli::Command::Verify {
+                lockfile: Some(_),
+                ..
+            })
+        );
+    }
+
+    if cli.dedup_only {
+        // Screened records are never read in full, so they could not carry
+        // the side outputs these flags promise for every record.
+        if cli.sample_hash {
+            return Err(bad_input(
+                "--dedup-only",
+                "cannot be combined with --sample-hash".to_owned(),
+            ));
+        }
+        if let Some(flag) = full_read_flag(cli, &options) {
+            return Err(bad_input(
+                "--dedup-only",
+                format!("cannot be combined with {flag}"),
+            ));
+        }
+        options.dedup_only = true;
+    }
+
+    if let Some(format) = &cli.emit_sums {
+        let format = format
+            .parse::<pipeline::sums::SumsFormat>()
+            .map_err(|err| bad_input("--emit-sums", err))?;
+        // Checksum files hold one whole-file digest per listed file.
+        let conflict = [
+            ("--sample-hash", cli.sample_hash),
+            ("--dedup-only", cli.dedup_only),
+            ("--expand-archives", cli.expand_archives),
+        ]
+        .into_iter()
+        .find(|(_, set)| *set);
+        if let Some((flag, _)) = conflict {
+            return Err(bad_input(
+                "--emit-sums",
+                format!("cannot be combined with {flag}"),
+            ));
+        }
+        // Verification keeps each record's own algorithm; otherwise the
+        // primary one must be something checksum tools can name.
+        if !options.verify
+            && (pipeline::sums::bsd_tag(options.primary()).is_none()
+                || (options.primary() == cli::Algorithm::Blake3 && !options.blake3.is_default()))
+        {
+            return Err(bad_input(
+                "--emit-sums",
+                format!(
+                    "primary algorithm {} has no checksum-file format",
+                    options.primary()
+                ),
+            ));
+        }
+        options.emit_sums = Some(format);
+    }
+
+    if let Some(baseline) = &cli.baseline {
+        // Comparison needs a whole-file bytes_hash per record, and the
+        // trailer of removed records is JSONL only.
+        let conflict = [
+            ("--sample-hash", cli.sample_hash),
+            ("--dedup-only", cli.dedup_only),
+            ("--emit-sums", cli.emit_sums.is_some()),
+        ]
+        .into_iter()
+        .find(|(_, set)| *set)
+        .map(|(flag, _)| flag)
+        .or(verify_command);
+        if let Some(flag) = conflict {
+            return Err(bad_input(
+                "--baseline",
+                format!("cannot be combined with {flag}"),
+            ));
+        }
+        options.baseline = Some(baseline.clone());
+        options.changed_only = cli.changed_only;
+    } else if cli.changed_only {
+        return Err(bad_input(
+            "--changed-only",
+            "requires --baseline".to_owned(),
+        ));
+    }
+
+    // A single worker keeps every file single-threaded; otherwise large files
+    // borrow idle workers from the same --jobs pool.
+    if pipeline::parallel::normalized_jobs(cli.jobs) > 1 {
+        options.parallel_blake3_min_len = Some(hash::blake3::PARALLEL_MIN_LEN);
+    }
+
+    Ok(options)
+}
+
 fn refusal_result(refusal: refusal::RefusalEnvelope) -> RunResult {
-    let rendered = serde_json::to_string(&refusal).unwrap_or_else(|err| {
-        serde_json::json!({
-            "code": "E_INTERNAL",
-            "message": "Failed to render refusal envelope",
-            "detail": {
-                "error": err.to_string()
-            },
-            "next_command": null
-        })
-        .to_string()
-    });
+    let rendered = refusal.render();
     println!("{rendered}");
     RunResult::new(
         cli::Outcome::Refusal,
@@ -295,7 +748,130 @@ fn append_witness_non_fatal(cli: &cli::Cli, result: &RunResult) {
 
 fn witness_params(cli: &cli::Cli) -> Map<String, Value> {
     let mut params = Map::new();
+    match &cli.command {
+        Some(cli::Command::Verify { lockfile, .. }) => {
+            params.insert("verify".to_owned(), Value::Bool(true));
+            if let Some(lockfile) = lockfile {
+                params.insert(
+                    "lockfile".to_owned(),
+                    Value::from(lockfile.to_string_lossy().into_owned()),
+                );
+            }
+        }
+        Some(cli::Command::Check { algorithm, .. }) => {
+            params.insert("check".to_owned(), Value::Bool(true));
+            if let Some(algorithm) = algorithm {
+                params.insert(
+                    "check_algorithm".to_owned(),
+                    Value::from(algorithm.as_str()),
+                );
+            }
+        }
+        _ => {}
+    }
     params.insert("algorithm".to_owned(), Value::String(cli.algorithm.clone()));
+    // Record the BLAKE3 mode but never the key material itself.
+    if cli.blake3_key_file.is_some() {
+        params.insert("blake3_mode".to_owned(), Value::from("keyed"));
+    }
+    if let Some(context) = &cli.blake3_context {
+        params.insert("blake3_mode".to_owned(), Value::from("derive_key"));
+        params.insert("blake3_context".to_owned(), Value::from(context.as_str()));
+    }
+    if let Some(digest_length) = cli.digest_length {
+        params.insert("digest_length".to_owned(), Value::from(digest_length));
+    }
+    if let Some(chunk_size) = cli
+        .chunk_size
+        .as_deref()
+        .and_then(|size| cli::parse_byte_size(size).ok())
+    {
+        params.insert("chunk_size".to_owned(), Value::from(chunk_size));
+    }
+    if cli.cdc {
+        params.insert("cdc".to_owned(), Value::Bool(true));
+    }
+    if cli.object_store_checksums || cli.s3_part_size.is_some() {
+        params.insert("object_store_checksums".to_owned(), Value::Bool(true));
+    }
+    if let Some(part_size) = cli
+        .s3_part_size
+        .as_deref()
+        .and_then(|size| cli::parse_byte_size(size).ok())
+    {
+        params.insert("s3_part_size".to_owned(), Value::from(part_size));
+    }
+    if cli.ipfs_cid {
+        params.insert("ipfs_cid".to_owned(), Value::Bool(true));
+    }
+    if cli.fsverity {
+        params.insert("fsverity".to_owned(), Value::Bool(true));
+    }
+    if cli.sample_hash {
+        params.insert("sample_hash".to_owned(), Value::Bool(true));
+        if let Some(edge) = cli
+            .sample_edge
+            .as_deref()
+            .and_then(|size| cli::parse_byte_size(size).ok())
+        {
+            params.insert("sample_edge".to_owned(), Value::from(edge));
+        }
+        if let Some(windows) = cli.sample_windows {
+            params.insert("sample_windows".to_owned(), Value::from(windows));
+        }
+        if let Some(window) = cli
+            .sample_window_size
+            .as_deref()
+            .and_then(|size| cli::parse_byte_size(size).ok())
+        {
+            params.insert("sample_window_size".to_owned(), Value::from(window));
+        }
+    }
+    if cli.dedup_only {
+        params.insert("dedup_only".to_owned(), Value::Bool(true));
+    }
+    if cli.annotate_duplicates {
+        params.insert("annotate_duplicates".to_owned(), Value::Bool(true));
+    }
+    if let Some(format) = &cli.emit_sums {
+        params.insert("emit_sums".to_owned(), Value::from(format.as_str()));
+    }
+    if let Some(baseline) = &cli.baseline {
+        params.insert(
+            "baseline".to_owned(),
+            Value::from(baseline.to_string_lossy().into_owned()),
+        );
+    }
+    if cli.changed_only {
+        params.insert("changed_only".to_owned(), Value::Bool(true));
+    }
+    if let Some(retries) = cli.change_retries {
+        params.insert("change_retries".to_owned(), Value::from(retries));
+    }
+    if let Some(encoding) = &cli.encoding {
+        params.insert(
+            "encoding".to_owned(),
+            Value::String(encoding.to_ascii_lowercase()),
+        );
+    }
+    if let Some(formats) = &cli.inner_hash {
+        params.insert(
+            "inner_hash".to_owned(),
+            Value::String(formats.to_ascii_lowercase()),
+        );
+    }
+    if cli.git_tree {
+        params.insert("git_tree".to_owned(), Value::Bool(true));
+    }
+    if cli.expand_archives {
+        params.insert("expand_archives".to_owned(), Value::Bool(true));
+    }
+    if let Some(dir) = &cli.bao_outboard {
+        params.insert(
+            "bao_outboard".to_owned(),
+            Value::String(dir.to_string_lossy().into_owned()),
+        );
+    }
     if let Some(jobs) = cli.jobs {
         params.insert(
             "jobs".to_owned(),
@@ -346,12 +922,10 @@ fn stream_batch_size(jobs: usize) -> usize {
 
 fn flush_pending_records(
     pending_records: Vec<PendingRecord>,
-    algorithm: cli::Algorithm,
+    hash_options: &hash::HashOptions,
     jobs: usize,
     stream_state: &mut StreamState<'_>,
 ) -> Result<(), Box<refusal::RefusalEnvelope>> {
-    use std::io::Write;
-
     if pending_records.is_empty() {
         return Ok(());
     }
@@ -359,37 +933,351 @@ fn flush_pending_records(
     // Keep memory bounded while still honoring deterministic ordered output.
     let processed_records =
         pipeline::parallel::process_indexed_in_parallel(pending_records, jobs, |(_, pending)| {
-            process_record(pending, algorithm)
+            if hash_options.verify {
+                verify_record(pending, hash_options)
+            } else {
+                process_record(pending, hash_options)
+            }
         });
 
     for processed_record in processed_records {
-        let processed_record = processed_record?;
+        defer_or_finish_processed_record(processed_record?, stream_state)?;
+    }
 
-        if let Some(warning_event) = processed_record.warning_event.as_ref() {
-            if stream_state.progress_enabled {
-                let _ = progress::write_warning(stream_state.stderr, warning_event);
-            } else {
-                let _ = writeln!(
-                    stream_state.stderr,
-                    "hash: warning: {}: {}",
-                    warning_event.path, warning_event.message
-                );
-            }
+    Ok(())
+}
+
+fn defer_or_finish_processed_record(
+    processed_record: ProcessedRecord,
+    stream_state: &mut StreamState<'_>,
+) -> Result<(), Box<refusal::RefusalEnvelope>> {
+    match stream_state.deferred.as_mut() {
+        Some(deferred) => {
+            deferred.push(processed_record);
+            Ok(())
         }
+        None => finish_processed_record(processed_record, stream_state),
+    }
+}
+
+/// What the `--dedup-only` screens decided for one record.
+enum DedupScreen {
+    /// Proved unique without the full hash.
+    Unique(pipeline::dedup::DedupTier, Option<String>),
+    /// Still a possible duplicate, with its prefix hash when it was screened.
+    Candidate(Option<String>),
+    /// The pre-screen could not read the file.
+    Failed(std::io::Error),
+}
+
+/// Settle every record with the cheapest tier that proves it unique. A size
+/// no other record shares needs no read at all, same-sized files are
+/// pre-screened by an XXH3 of their first block, and only files that still
+/// collide are hashed in full. Output keeps manifest order.
+fn flush_dedup_records(
+    pending_records: Vec<PendingRecord>,
+    hash_options: &hash::HashOptions,
+    jobs: usize,
+    stream_state: &mut StreamState<'_>,
+) -> Result<(), Box<refusal::RefusalEnvelope>> {
+    use pipeline::dedup::{self, DedupTier};
+
+    let size_keys: Vec<dedup::SizeKey> = pending_records
+        .iter()
+        .map(|pending| dedup::size_key(&pending.record))
+        .collect();
+    let sizes = dedup::screen_sizes(&size_keys);
+    let size_unique = dedup::unique_keys(&sizes);
+
+    let prefixes = pipeline::parallel::process_indexed_in_parallel(
+        pending_records.iter().collect(),
+        jobs,
+        |(index, pending)| {
+            if sizes[index].is_none() || size_unique[index] {
+                return None;
+            }
+            let path = std::path::Path::new(pending.record.get("path").and_then(Value::as_str)?);
+            Some(hash::snapshot::hash_unchanged(
+                path,
+                hash_options.change_retries,
+                || hash::xxh3::hash_prefix(path, dedup::PRESCREEN_LEN),
+            ))
+        },
+    );
+    let prefix_keys: Vec<Option<(u64, &str)>> = sizes
+        .iter()
+        .zip(&prefixes)
+        .map(|(size, prefix)| Some(((*size)?, prefix.as_ref()?.as_ref().ok()?.as_str())))
+        .collect();
+    let prefix_unique = dedup::unique_keys(&prefix_keys);
+
+    let screens: Vec<DedupScreen> = prefixes
+        .into_iter()
+        .enumerate()
+        .map(|(index, prefix)| match prefix {
+            _ if size_unique[index] => DedupScreen::Unique(DedupTier::Size, None),
+            None => DedupScreen::Candidate(None),
+            Some(Err(io_err)) => DedupScreen::Failed(io_err),
+            Some(Ok(prefix)) if prefix_unique[index] => {
+                DedupScreen::Unique(DedupTier::Prefix, Some(prefix))
+            }
+            Some(Ok(prefix)) => DedupScreen::Candidate(Some(prefix)),
+        })
+        .collect();
+
+    let processed_records: Vec<Result<ProcessedRecord, Box<refusal::RefusalEnvelope>>> =
+        pipeline::parallel::process_indexed_in_parallel(
+            pending_records.into_iter().zip(screens).collect(),
+            jobs,
+            |(_, (pending, screen))| match screen {
+                DedupScreen::Unique(tier, prefix_hash) => Ok(ProcessedRecord {
+                    record: pipeline::enricher::process_dedup_screened_record(
+                        pending.record,
+                        tier,
+                        prefix_hash.as_deref(),
+                    ),
+                    warning_event: None,
+                    skipped: false,
+                    cdc_chunks: None,
+                    git_tree_dir: false,
+                    members: Vec::new(),
+                }),
+                DedupScreen::Failed(io_err) => {
+                    let path_str = pending
+                        .record
+                        .get("path")
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                        .to_owned();
+                    Ok(failed_record(pending.record, &path_str, &io_err))
+                }
+                DedupScreen::Candidate(prefix_hash) => {
+                    let mut processed_record = process_record(pending, hash_options)?;
+                    if !processed_record.skipped {
+                        pipeline::enricher::set_dedup_tier(
+                            &mut processed_record.record,
+                            DedupTier::Full,
+                            prefix_hash.as_deref(),
+                        );
+                    }
+                    Ok(processed_record)
+                }
+            },
+        );
+
+    for processed_record in processed_records {
+        defer_or_finish_processed_record(processed_record?, stream_state)?;
+    }
 
-        emit_processed_record(&processed_record.record, stream_state)?;
+    Ok(())
+}
+
+fn finish_processed_record(
+    mut processed_record: ProcessedRecord,
+    stream_state: &mut StreamState<'_>,
+) -> Result<(), Box<refusal::RefusalEnvelope>> {
+    if let Some(warning_event) = processed_record.warning_event.as_ref() {
+        write_warning_event(warning_event, stream_state);
+    }
 
-        if processed_record.skipped {
-            *stream_state.any_skipped = true;
+    let compared = stream_state
+        .baseline
+        .as_mut()
+        .map(|baseline| baseline.compare(&processed_record.record));
+    let change = match compared {
+        Some(Ok(change)) => change,
+        Some(Err(mismatch)) => {
+            let path_str = processed_record
+                .record
+                .get("path")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            let warning_event = progress::WarningEvent::new(path_str, &mismatch.to_string());
+            write_warning_event(&warning_event, stream_state);
+            pipeline::enricher::add_baseline_warning(&mut processed_record.record, &mismatch);
+            None
         }
+        None => None,
+    };
+    if let Some(change) = change {
+        pipeline::enricher::set_change(&mut processed_record.record, change);
+    }
+    let hidden = stream_state.changed_only && change == Some(pipeline::baseline::Change::Unchanged);
+
+    let listed = hidden || emit_processed_record(&processed_record.record, stream_state)?;
+    // A checksum file has no line for a record without a whole-file digest;
+    // skipped records already warned, anything else makes the list partial.
+    if !listed && !processed_record.skipped {
+        let path_str = processed_record
+            .record
+            .get("path")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let warning_event =
+            progress::WarningEvent::new(path_str, "not listed: no whole-file digest to emit");
+        write_warning_event(&warning_event, stream_state);
+        *stream_state.any_skipped = true;
+    }
+
+    // Archive members are bytes their archive's chunks already cover, so
+    // counting them too would inflate both totals.
+    let is_member = processed_record
+        .record
+        .get("parent_bytes_hash")
+        .is_some_and(|parent| !parent.is_null());
+    if let (Some(summary), Some(cdc_chunks)) = (
+        stream_state.cdc_summary.as_mut(),
+        processed_record.cdc_chunks.as_deref(),
+    ) && !is_member
+    {
+        summary.add(cdc_chunks);
+    }
+
+    if processed_record.skipped {
+        *stream_state.any_skipped = true;
+    }
+
+    for member in processed_record.members {
+        finish_processed_record(member, stream_state)?;
     }
 
     Ok(())
 }
 
+fn write_warning_event(warning_event: &progress::WarningEvent, stream_state: &mut StreamState<'_>) {
+    use std::io::Write;
+
+    if stream_state.progress_enabled {
+        let _ = progress::write_warning(stream_state.stderr, warning_event);
+    } else {
+        let _ = writeln!(
+            stream_state.stderr,
+            "hash: warning: {}: {}",
+            warning_event.path, warning_event.message
+        );
+    }
+}
+
+/// Fill deferred directory records with git tree ids now that every file
+/// record in the manifest has been hashed.
+fn resolve_git_trees(records: &mut [ProcessedRecord], hash_options: &hash::HashOptions) {
+    let algorithm = hash_options.primary();
+    let mut index = pipeline::git_tree::TreeIndex::default();
+    for processed in records.iter().filter(|processed| !processed.git_tree_dir) {
+        index.add_file_record(&processed.record, algorithm);
+    }
+
+    for processed in records
+        .iter_mut()
+        .filter(|processed| processed.git_tree_dir)
+    {
+        let path_str = processed
+            .record
+            .get("path")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_owned();
+        let record = std::mem::take(&mut processed.record);
+        match index.tree_id(std::path::Path::new(&path_str), algorithm) {
+            Ok(tree_id) => {
+                processed.record =
+                    pipeline::enricher::process_git_tree_record(record, tree_id, hash_options);
+            }
+            Err(incomplete) => {
+                processed.record = pipeline::enricher::process_git_tree_failed_record(
+                    record,
+                    &path_str,
+                    &incomplete.unresolved,
+                );
+                processed.warning_event = Some(progress::WarningEvent::new(
+                    &path_str,
+                    &format!("skipped: {incomplete}"),
+                ));
+                processed.skipped = true;
+            }
+        }
+    }
+}
+
+/// Add duplicate annotations to deferred records, archive members included,
+/// now that every `bytes_hash` in the manifest is known. Records are indexed
+/// in output order so `first_path` is the copy emitted first.
+fn annotate_duplicates(records: &mut [ProcessedRecord]) {
+    fn index_all(records: &[ProcessedRecord], index: &mut pipeline::duplicates::DuplicateIndex) {
+        for processed in records {
+            index.add_record(&processed.record);
+            index_all(&processed.members, index);
+        }
+    }
+    fn annotate_all(records: &mut [ProcessedRecord], index: &pipeline::duplicates::DuplicateIndex) {
+        for processed in records {
+            index.annotate(&mut processed.record);
+            annotate_all(&mut processed.members, index);
+        }
+    }
+
+    let mut index = pipeline::duplicates::DuplicateIndex::default();
+    index_all(records, &mut index);
+    annotate_all(records, &index);
+}
+
+/// The first flag set whose output needs every byte of every file.
+fn full_read_flag(cli: &cli::Cli, options: &hash::HashOptions) -> Option<&'static str> {
+    let full_read_flags = [
+        ("--bao-outboard", cli.bao_outboard.is_some()),
+        ("--chunk-size", cli.chunk_size.is_some()),
+        ("--cdc", cli.cdc),
+        ("--encoding", cli.encoding.is_some()),
+        ("--inner-hash", cli.inner_hash.is_some()),
+        ("--git-tree", cli.git_tree),
+        ("--expand-archives", cli.expand_archives),
+        ("--object-store-checksums", options.object_store.is_some()),
+        ("--ipfs-cid", cli.ipfs_cid),
+        ("--fsverity", cli.fsverity),
+    ];
+    full_read_flags
+        .iter()
+        .find(|(_, set)| *set)
+        .map(|(flag, _)| *flag)
+}
+
+/// Sampling replaces the full read, so nothing that needs every byte can be
+/// combined with it.
+fn sample_options(
+    cli: &cli::Cli,
+    options: &hash::HashOptions,
+) -> Result<hash::SampleOptions, String> {
+    if let Some(flag) = full_read_flag(cli, options) {
+        return Err(format!("cannot be combined with {flag}"));
+    }
+    if options.algorithms.len() > 1 {
+        return Err("takes a single --algorithm".to_owned());
+    }
+    if matches!(
+        options.primary(),
+        cli::Algorithm::GitSha1 | cli::Algorithm::GitSha256
+    ) {
+        return Err("requires a primary algorithm other than git-sha1 or git-sha256".to_owned());
+    }
+
+    let mut sample = hash::SampleOptions::default();
+    if let Some(edge) = &cli.sample_edge {
+        sample.edge_len =
+            cli::parse_byte_size(edge).map_err(|err| format!("--sample-edge: {err}"))?;
+    }
+    if let Some(windows) = cli.sample_windows {
+        sample.windows = windows;
+    }
+    if let Some(window) = &cli.sample_window_size {
+        sample.window_len =
+            cli::parse_byte_size(window).map_err(|err| format!("--sample-window-size: {err}"))?;
+    }
+    Ok(sample)
+}
+
 fn process_record(
     pending: PendingRecord,
-    algorithm: cli::Algorithm,
+    hash_options: &hash::HashOptions,
 ) -> Result<ProcessedRecord, Box<refusal::RefusalEnvelope>> {
     let PendingRecord {
         line_number,
@@ -411,6 +1299,9 @@ fn process_record(
             record: pipeline::enricher::process_skipped_record(record),
             warning_event: None,
             skipped: true,
+            cdc_chunks: None,
+            git_tree_dir: false,
+            members: Vec::new(),
         });
     }
 
@@ -425,41 +1316,234 @@ fn process_record(
         })?
         .to_owned();
 
-    match hash::hash_file(std::path::Path::new(&path_str), algorithm) {
-        Ok(file_hash) => Ok(ProcessedRecord {
-            record: pipeline::enricher::process_hashed_record(
-                record,
-                file_hash,
-                algorithm.prefix(),
-            ),
+    let path = std::path::Path::new(&path_str);
+    if hash_options.git_tree && path.is_dir() {
+        return Ok(ProcessedRecord {
+            record,
             warning_event: None,
             skipped: false,
-        }),
-        Err(io_err) => {
-            let warning_message = format!("skipped: {io_err}");
+            cdc_chunks: None,
+            git_tree_dir: true,
+            members: Vec::new(),
+        });
+    }
+
+    let range = pipeline::reader::record_byte_range(&record, line_number)?;
+    let retries = hash_options.change_retries;
+    if let Some(sample) = hash_options.sample {
+        return Ok(
+            match hash::snapshot::hash_unchanged(path, retries, || {
+                hash::sample::sample_file(path, range, sample, hash_options)
+            }) {
+                Ok(sample_digest) => ProcessedRecord {
+                    record: pipeline::enricher::process_sample_record(
+                        record,
+                        &sample_digest,
+                        sample,
+                    ),
+                    warning_event: None,
+                    skipped: false,
+                    cdc_chunks: None,
+                    git_tree_dir: false,
+                    members: Vec::new(),
+                },
+                Err(io_err) => failed_record(record, &path_str, &io_err),
+            },
+        );
+    }
+
+    // Members are read in a second pass over the same archive, so one
+    // snapshot spans both: members always come from the bytes that
+    // `bytes_hash` (their `parent_bytes_hash`) covers. Only whole files are
+    // expanded; a byte range is not an archive.
+    let expand = hash_options.expand_archives && range.is_none();
+    let hashed = hash::snapshot::hash_unchanged(path, retries, || {
+        let file_digests = match range {
+            Some(range) => hash::hash_file_range_with_options(path, range, hash_options)?,
+            None => hash::hash_file_with_options(path, hash_options)?,
+        };
+        let listing = expand.then(|| hash::archive::hash_archive_members(path, hash_options));
+        Ok((file_digests, listing))
+    });
+
+    match hashed {
+        Ok((file_digests, listing)) => {
+            let mut record =
+                pipeline::enricher::process_file_digests(record, &file_digests, hash_options);
+            let mut warning_event = file_digests.inner.as_ref().and_then(|inner| {
+                inner.digest.as_ref().err().map(|err| {
+                    progress::WarningEvent::new(
+                        &path_str,
+                        &format!("inner hash: corrupt {} stream: {err}", inner.compression),
+                    )
+                })
+            });
+
+            let mut members = Vec::new();
+            if let Some(listing) = listing {
+                let archive_warning;
+                (members, archive_warning) =
+                    expand_archive(&mut record, path, listing, hash_options);
+                warning_event = warning_event.or(archive_warning);
+            }
 
             Ok(ProcessedRecord {
-                record: pipeline::enricher::process_io_failed_record(
-                    record,
-                    &path_str,
-                    &io_err.to_string(),
-                ),
-                warning_event: Some(progress::WarningEvent::new(&path_str, &warning_message)),
-                skipped: true,
+                record,
+                warning_event,
+                skipped: false,
+                cdc_chunks: file_digests.cdc_chunks,
+                git_tree_dir: false,
+                members,
             })
         }
+        Err(io_err) => Ok(failed_record(record, &path_str, &io_err)),
+    }
+}
+
+/// Check one hash.v0 record against the disk (`verify`). Anything but a
+/// match, including a record with nothing to check, makes the run PARTIAL.
+fn verify_record(
+    pending: PendingRecord,
+    hash_options: &hash::HashOptions,
+) -> Result<ProcessedRecord, Box<refusal::RefusalEnvelope>> {
+    let PendingRecord {
+        line_number,
+        record,
+    } = pending;
+
+    let Some(verification) = pipeline::verify::verify_record(&record, line_number, hash_options)?
+    else {
+        return Ok(ProcessedRecord {
+            skipped: !pipeline::verify::nothing_to_check(&record),
+            record,
+            warning_event: None,
+            cdc_chunks: None,
+            git_tree_dir: false,
+            members: Vec::new(),
+        });
+    };
+
+    let warning_event = (verification != pipeline::verify::Verification::Match).then(|| {
+        let path_str = record
+            .get("path")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        progress::WarningEvent::new(path_str, &format!("verification: {}", verification.label()))
+    });
+    let mut record = pipeline::enricher::process_verified_record(record, &verification);
+    if hash_options.lock_drift
+        && let Some(drift) = pipeline::lockfile::Drift::of(&verification)
+    {
+        pipeline::enricher::set_drift(&mut record, drift);
+    }
+    Ok(ProcessedRecord {
+        record,
+        skipped: warning_event.is_some(),
+        warning_event,
+        cdc_chunks: None,
+        git_tree_dir: false,
+        members: Vec::new(),
+    })
+}
+
+fn failed_record(record: Value, path_str: &str, io_err: &std::io::Error) -> ProcessedRecord {
+    let warning_message = format!("skipped: {io_err}");
+
+    let record = if let Some(past_eof) = hash::range::range_past_eof(io_err) {
+        pipeline::enricher::process_range_failed_record(record, path_str, past_eof)
+    } else if let Some(changed) = hash::snapshot::changed_during_hash(io_err) {
+        pipeline::enricher::process_changed_record(record, path_str, changed)
+    } else {
+        pipeline::enricher::process_io_failed_record(record, path_str, &io_err.to_string())
+    };
+
+    ProcessedRecord {
+        record,
+        warning_event: Some(progress::WarningEvent::new(path_str, &warning_message)),
+        skipped: true,
+        cdc_chunks: None,
+        git_tree_dir: false,
+        members: Vec::new(),
     }
 }
 
+/// Turn the hashed members of an archive record's file into their records,
+/// in archive order. A file that is not a recognized archive has no members.
+fn expand_archive(
+    record: &mut Value,
+    path: &std::path::Path,
+    listing: std::io::Result<Option<hash::archive::ArchiveListing>>,
+    hash_options: &hash::HashOptions,
+) -> (Vec<ProcessedRecord>, Option<progress::WarningEvent>) {
+    let path_str = path.to_string_lossy();
+    let listing = match listing {
+        Ok(Some(listing)) => listing,
+        Ok(None) => return (Vec::new(), None),
+        Err(err) => {
+            pipeline::enricher::set_archive_listing(record, None, Some(&err.to_string()));
+            let message = format!("archive: {err}");
+            return (
+                Vec::new(),
+                Some(progress::WarningEvent::new(&path_str, &message)),
+            );
+        }
+    };
+
+    pipeline::enricher::set_archive_listing(record, Some(listing.format), listing.error.as_deref());
+    let warning_event = listing
+        .error
+        .as_ref()
+        .map(|err| progress::WarningEvent::new(&path_str, &format!("archive: {err}")));
+
+    let members = listing
+        .members
+        .iter()
+        .map(|member| {
+            let member_record =
+                pipeline::enricher::process_archive_member_record(record, member, hash_options);
+            let warning_event = member.digests.as_ref().err().map(|err| {
+                progress::WarningEvent::new(
+                    &format!("{path_str}!/{}", member.name),
+                    &format!("skipped: {err}"),
+                )
+            });
+            ProcessedRecord {
+                record: member_record,
+                skipped: warning_event.is_some(),
+                warning_event,
+                cdc_chunks: member
+                    .digests
+                    .as_ref()
+                    .ok()
+                    .and_then(|digests| digests.cdc_chunks.clone()),
+                git_tree_dir: false,
+                members: Vec::new(),
+            }
+        })
+        .collect();
+    (members, warning_event)
+}
+
+/// Write one record to stdout. Returns `false` when `--emit-sums` has no
+/// checksum line for it, in which case nothing is written.
 fn emit_processed_record(
     record: &Value,
     stream_state: &mut StreamState<'_>,
-) -> Result<(), Box<refusal::RefusalEnvelope>> {
+) -> Result<bool, Box<refusal::RefusalEnvelope>> {
     use std::io::Write;
 
-    let mut rendered = Vec::new();
-    output::jsonl::write_json_line(&mut rendered, record)
-        .map_err(|err| Box::new(refusal::RefusalEnvelope::io_error(err.to_string())))?;
+    let rendered = match stream_state.sums_format {
+        Some(format) => match pipeline::sums::render_line(record, format) {
+            Some(line) => line.into_bytes(),
+            None => return Ok(false),
+        },
+        None => {
+            let mut rendered = Vec::new();
+            output::jsonl::write_json_line(&mut rendered, record)
+                .map_err(|err| Box::new(refusal::RefusalEnvelope::io_error(err.to_string())))?;
+            rendered
+        }
+    };
     stream_state
         .stdout
         .write_all(&rendered)
@@ -478,5 +1562,5 @@ fn emit_processed_record(
         let _ = progress::write_progress(stream_state.stderr, &progress_event);
     }
 
-    Ok(())
+    Ok(true)
 }
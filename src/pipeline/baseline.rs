@@ -0,0 +1,166 @@
+use crate::refusal::{RefusalCode, RefusalEnvelope};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::BufRead;
+use std::path::Path;
+
+/// How a record compares with the previous run (`--baseline`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    Unchanged,
+    Modified,
+    /// Not in the baseline.
+    New,
+    /// In the baseline but not in this run; only on trailer records.
+    Removed,
+}
+
+impl Change {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Unchanged => "unchanged",
+            Self::Modified => "modified",
+            Self::New => "new",
+            Self::Removed => "removed",
+        }
+    }
+}
+
+/// A record and its baseline entry share no algorithm, so their digests say
+/// nothing about whether the bytes changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoCommonAlgorithm {
+    pub hash_algorithm: String,
+    pub baseline_hash_algorithm: String,
+}
+
+impl fmt::Display for NoCommonAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "baseline record is hashed with {} and this run with {}; rerun with both in --algorithm to compare",
+            self.baseline_hash_algorithm, self.hash_algorithm
+        )
+    }
+}
+
+/// A previous hash.v0 manifest, indexed by `relative_path` and `path`.
+pub struct Baseline {
+    records: Vec<Value>,
+    by_key: HashMap<String, usize>,
+    seen: Vec<bool>,
+}
+
+impl Baseline {
+    pub fn read(path: &Path) -> Result<Self, Box<RefusalEnvelope>> {
+        let io_refusal = |err: std::io::Error| {
+            Box::new(RefusalEnvelope::from_code(
+                RefusalCode::Io,
+                json!({ "path": path.to_string_lossy(), "error": err.to_string() }),
+            ))
+        };
+        let file = std::fs::File::open(path).map_err(io_refusal)?;
+        let mut reader = std::io::BufReader::new(file);
+
+        let mut baseline = Self {
+            records: Vec::new(),
+            by_key: HashMap::new(),
+            seen: Vec::new(),
+        };
+        let mut buffer = String::new();
+        let mut line_number = 0;
+        loop {
+            buffer.clear();
+            if reader.read_line(&mut buffer).map_err(io_refusal)? == 0 {
+                break;
+            }
+            line_number += 1;
+            if buffer.trim().is_empty() {
+                continue;
+            }
+            let record = super::reader::parse_json_line(&buffer, line_number)?.record;
+            let index = baseline.records.len();
+            for key in match_keys(&record) {
+                baseline.by_key.entry(key).or_insert(index);
+            }
+            baseline.records.push(record);
+            baseline.seen.push(false);
+        }
+        Ok(baseline)
+    }
+
+    /// Compare one record of this run with its baseline entry, matched on
+    /// `relative_path` first, then `path`. Digests are compared only under
+    /// the same algorithm: `bytes_hash` when `hash_algorithm` agrees,
+    /// otherwise a matching entry of either side's `hashes`.
+    ///
+    /// `Ok(None)` when the record has no `bytes_hash` to compare (skipped or
+    /// `non_identity`), whether or not the baseline has it; its baseline
+    /// entry still counts as seen, so it is never reported `removed`.
+    pub fn compare(&mut self, record: &Value) -> Result<Option<Change>, NoCommonAlgorithm> {
+        let index = match_keys(record).find_map(|key| self.by_key.get(&key).copied());
+        if let Some(index) = index {
+            self.seen[index] = true;
+        }
+        let Some(bytes_hash) = record.get("bytes_hash").and_then(Value::as_str) else {
+            return Ok(None);
+        };
+        let Some(index) = index else {
+            return Ok(Some(Change::New));
+        };
+
+        let previous = &self.records[index];
+        let algorithm = hash_algorithm(record);
+        let previous_algorithm = hash_algorithm(previous);
+        let pair = digest_under(previous, algorithm)
+            .map(|previous_hash| (bytes_hash, previous_hash))
+            .or_else(|| {
+                let previous_hash = previous.get("bytes_hash")?.as_str()?;
+                Some((digest_under(record, previous_algorithm)?, previous_hash))
+            });
+        match pair {
+            Some((current, previous)) if current == previous => Ok(Some(Change::Unchanged)),
+            Some(_) => Ok(Some(Change::Modified)),
+            None => Err(NoCommonAlgorithm {
+                hash_algorithm: algorithm.to_owned(),
+                baseline_hash_algorithm: previous_algorithm.to_owned(),
+            }),
+        }
+    }
+
+    /// Baseline records no record of this run matched, in baseline order.
+    pub fn removed(self) -> impl Iterator<Item = Value> {
+        self.records
+            .into_iter()
+            .zip(self.seen)
+            .filter(|(_, seen)| !seen)
+            .map(|(record, _)| record)
+    }
+}
+
+fn match_keys(record: &Value) -> impl Iterator<Item = String> + '_ {
+    ["relative_path", "path"].into_iter().filter_map(|field| {
+        let value = record.get(field)?.as_str()?;
+        Some(format!("{field}\0{value}"))
+    })
+}
+
+fn hash_algorithm(record: &Value) -> &str {
+    record
+        .get("hash_algorithm")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+}
+
+/// A record's digest under `algorithm`: its `bytes_hash` when that is its
+/// `hash_algorithm`, else the `hashes` entry of that name.
+fn digest_under<'a>(record: &'a Value, algorithm: &str) -> Option<&'a str> {
+    if algorithm.is_empty() {
+        return None;
+    }
+    if hash_algorithm(record) == algorithm {
+        return record.get("bytes_hash")?.as_str();
+    }
+    record.get("hashes")?.get(algorithm)?.as_str()
+}
@@ -0,0 +1,141 @@
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Bytes the `--dedup-only` pre-screen reads from the start of each file.
+pub const PRESCREEN_LEN: u64 = 64 * 1024;
+
+/// Which `--dedup-only` step settled a record, cheapest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DedupTier {
+    /// No other record has the same upstream `size`; the file was not read.
+    Size,
+    /// Same size as another record, but a different XXH3 of the first block.
+    Prefix,
+    /// Still a candidate after both screens, so hashed in full.
+    Full,
+}
+
+impl DedupTier {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Size => "size",
+            Self::Prefix => "prefix",
+            Self::Full => "full",
+        }
+    }
+}
+
+/// What `--dedup-only` knows of a record's size before reading it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeKey {
+    /// Skipped, ranged or pathless: never screened against whole files.
+    Excluded,
+    /// Upstream `size`, or the length of the regular file at `path`.
+    Known(u64),
+    /// A whole file whose length only a full read can tell, such as a FIFO.
+    /// While one remains nothing can be proved unique by size or prefix.
+    Unknown,
+}
+
+/// Size key of a record: its upstream `size` when present, else a stat of
+/// `path`. A path that cannot be stat'ed is excluded, since it cannot be
+/// read either.
+pub fn size_key(record: &Value) -> SizeKey {
+    let Some(map) = record.as_object() else {
+        return SizeKey::Excluded;
+    };
+    let ranged = ["hash_offset", "hash_length"]
+        .iter()
+        .any(|field| map.get(*field).is_some_and(|value| !value.is_null()));
+    let path = match map.get("path").and_then(Value::as_str) {
+        Some(path) if !super::enricher::is_skipped(map) && !ranged => path,
+        _ => return SizeKey::Excluded,
+    };
+    if let Some(size) = map.get("size").and_then(Value::as_u64) {
+        return SizeKey::Known(size);
+    }
+    match std::fs::metadata(path) {
+        Ok(metadata) if metadata.is_file() => SizeKey::Known(metadata.len()),
+        Ok(_) => SizeKey::Unknown,
+        Err(_) => SizeKey::Excluded,
+    }
+}
+
+/// Sizes the screens may group on: every known size, or none at all while
+/// any record's size is unknown.
+pub fn screen_sizes(keys: &[SizeKey]) -> Vec<Option<u64>> {
+    let any_unknown = keys.contains(&SizeKey::Unknown);
+    keys.iter()
+        .map(|key| match key {
+            SizeKey::Known(size) if !any_unknown => Some(*size),
+            _ => None,
+        })
+        .collect()
+}
+
+/// For every key, whether no other entry shares it. Entries without a key
+/// are never unique.
+pub fn unique_keys<K: Ord>(keys: &[Option<K>]) -> Vec<bool> {
+    let mut counts = BTreeMap::new();
+    for key in keys.iter().flatten() {
+        *counts.entry(key).or_insert(0usize) += 1;
+    }
+    keys.iter()
+        .map(|key| key.as_ref().is_some_and(|key| counts[key] == 1))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn whole_file_records_are_sized_from_the_record_or_a_stat() {
+        let dir = std::env::temp_dir();
+        let dir = dir.to_str().expect("utf8 temp dir");
+        assert_eq!(
+            size_key(&json!({ "path": "a", "size": 3 })),
+            SizeKey::Known(3)
+        );
+        assert_eq!(
+            size_key(&json!({ "path": "/nonexistent/hash-dedup" })),
+            SizeKey::Excluded
+        );
+        assert_eq!(size_key(&json!({ "path": dir })), SizeKey::Unknown);
+        assert_eq!(size_key(&json!({ "size": 3 })), SizeKey::Excluded);
+        assert_eq!(
+            size_key(&json!({ "path": "a", "size": 3, "_skipped": true })),
+            SizeKey::Excluded
+        );
+        assert_eq!(
+            size_key(&json!({ "path": "a", "size": 3, "hash_offset": 1 })),
+            SizeKey::Excluded
+        );
+        assert_eq!(
+            size_key(&json!({ "path": "a", "size": 3, "hash_length": null })),
+            SizeKey::Known(3)
+        );
+    }
+
+    #[test]
+    fn an_unknown_size_blocks_every_size_screen() {
+        use SizeKey::{Excluded, Known, Unknown};
+        assert_eq!(
+            screen_sizes(&[Known(1), Excluded, Known(2)]),
+            vec![Some(1), None, Some(2)]
+        );
+        assert_eq!(
+            screen_sizes(&[Known(1), Unknown, Known(2)]),
+            vec![None, None, None]
+        );
+    }
+
+    #[test]
+    fn keys_shared_by_two_entries_are_not_unique() {
+        assert_eq!(
+            unique_keys(&[Some(1), Some(2), None, Some(1), None]),
+            vec![false, true, false, false, false]
+        );
+    }
+}
@@ -0,0 +1,118 @@
+use serde_json::{Value, json};
+use std::collections::HashMap;
+
+/// Records of one manifest grouped by `bytes_hash`, in the order each digest
+/// first appears.
+#[derive(Debug, Default)]
+pub struct DuplicateIndex {
+    groups: Vec<DuplicateGroup>,
+    by_digest: HashMap<String, usize>,
+}
+
+/// Every record sharing one `bytes_hash`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateGroup {
+    pub bytes_hash: String,
+    /// Paths in manifest order; the first one is `first_path`.
+    pub paths: Vec<String>,
+    /// Bytes each copy holds, from the first record's upstream `size`.
+    pub size: Option<u64>,
+}
+
+impl DuplicateGroup {
+    /// Bytes freed by keeping one copy.
+    pub fn reclaimable_bytes(&self) -> u64 {
+        self.size
+            .unwrap_or(0)
+            .saturating_mul(self.paths.len() as u64 - 1)
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "bytes_hash": self.bytes_hash,
+            "count": self.paths.len(),
+            "size": self.size,
+            "reclaimable_bytes": self.reclaimable_bytes(),
+            "paths": self.paths,
+        })
+    }
+}
+
+impl DuplicateIndex {
+    /// Index one output record. Records without a `bytes_hash`, such as
+    /// skipped ones, are ignored, and so are archive members (deleting one
+    /// frees nothing on disk), ranged records (their digest covers part of a
+    /// file) and `non_cryptographic` digests (equal XXH3 does not prove equal
+    /// bytes).
+    pub fn add_record(&mut self, record: &Value) {
+        if !is_groupable(record) {
+            return;
+        }
+        let Some(bytes_hash) = record.get("bytes_hash").and_then(Value::as_str) else {
+            return;
+        };
+        let path = record
+            .get("path")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_owned();
+        match self.by_digest.get(bytes_hash) {
+            Some(&group) => self.groups[group].paths.push(path),
+            None => {
+                self.by_digest
+                    .insert(bytes_hash.to_owned(), self.groups.len());
+                self.groups.push(DuplicateGroup {
+                    bytes_hash: bytes_hash.to_owned(),
+                    paths: vec![path],
+                    size: record.get("size").and_then(Value::as_u64),
+                });
+            }
+        }
+    }
+
+    /// Groups with more than one record, in order of first appearance.
+    pub fn duplicate_groups(&self) -> impl Iterator<Item = &DuplicateGroup> {
+        self.groups.iter().filter(|group| group.paths.len() > 1)
+    }
+
+    /// Add `duplicate_group`, `duplicate_count` and `first_path` to a record
+    /// whose `bytes_hash` appears more than once.
+    pub fn annotate(&self, record: &mut Value) {
+        if !is_groupable(record) {
+            return;
+        }
+        let Some(group) = record
+            .get("bytes_hash")
+            .and_then(Value::as_str)
+            .and_then(|bytes_hash| self.by_digest.get(bytes_hash))
+            .map(|&group| &self.groups[group])
+            .filter(|group| group.paths.len() > 1)
+        else {
+            return;
+        };
+        if let Some(map) = record.as_object_mut() {
+            map.insert(
+                "duplicate_group".to_owned(),
+                Value::String(group.bytes_hash.clone()),
+            );
+            map.insert("duplicate_count".to_owned(), Value::from(group.paths.len()));
+            map.insert(
+                "first_path".to_owned(),
+                Value::String(group.paths[0].clone()),
+            );
+        }
+    }
+}
+
+/// Whether a record is a standalone whole file with an identity-grade digest.
+fn is_groupable(record: &Value) -> bool {
+    let present = |field: &str| record.get(field).is_some_and(|value| !value.is_null());
+    let non_cryptographic = record
+        .get("non_cryptographic")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    !present("parent_bytes_hash")
+        && !present("hash_offset")
+        && !present("hash_length")
+        && !non_cryptographic
+}
@@ -1,4 +1,12 @@
-use serde_json::{Map, Value};
+use crate::hash::archive::{ArchiveFormat, MemberDigests};
+use crate::hash::range::RangePastEof;
+use crate::hash::snapshot::ChangedDuringHash;
+use crate::hash::{FileDigests, HashOptions, InnerDigest, SampleDigest, SampleOptions};
+use crate::pipeline::baseline::{Change, NoCommonAlgorithm};
+use crate::pipeline::dedup::DedupTier;
+use crate::pipeline::lockfile::Drift;
+use crate::pipeline::verify::Verification;
+use serde_json::{Map, Value, json};
 
 pub const HASH_VERSION: &str = "hash.v0";
 
@@ -83,6 +91,463 @@ pub fn process_hashed_record(mut record: Value, bytes_hash: String, algorithm: &
     record
 }
 
+/// Enrich a record with everything one read pass produced: the primary
+/// digest, any secondary digests, and side outputs such as a Bao outboard or
+/// chunk hash list.
+pub fn process_file_digests(
+    record: Value,
+    file_digests: &FileDigests,
+    options: &HashOptions,
+) -> Value {
+    let primary = options.primary();
+    let mut record = process_hashed_record(
+        record,
+        file_digests.digests[0].clone(),
+        options.algorithm_name(primary),
+    );
+
+    if !primary.is_cryptographic() {
+        mark_non_cryptographic(&mut record);
+    }
+
+    set_bytes_hash_encoded(&mut record, options);
+
+    if options.algorithms.len() > 1 {
+        let hashes: Vec<(&str, String)> = options
+            .algorithms
+            .iter()
+            .map(|algorithm| options.algorithm_name(*algorithm))
+            .zip(file_digests.digests.iter().cloned())
+            .collect();
+        set_hashes(&mut record, &hashes);
+    }
+
+    // Echo the resolved range so the digest's identity is unambiguous.
+    if let Some(range) = &file_digests.range
+        && let Some(map) = record.as_object_mut()
+    {
+        map.insert("hash_offset".to_owned(), Value::from(range.offset));
+        map.insert("hash_length".to_owned(), Value::from(range.length));
+    }
+
+    if let Some(outboard) = &file_digests.bao_outboard
+        && let Some(map) = record.as_object_mut()
+    {
+        map.insert(
+            "bao_outboard".to_owned(),
+            Value::String(outboard.to_string_lossy().into_owned()),
+        );
+    }
+
+    if let Some(chunks) = &file_digests.chunks
+        && let Some(map) = record.as_object_mut()
+    {
+        map.insert("chunk_size".to_owned(), Value::from(chunks.chunk_size));
+        map.insert(
+            "chunk_hashes".to_owned(),
+            Value::Array(chunks.hashes.iter().cloned().map(Value::String).collect()),
+        );
+        map.insert("chunk_root".to_owned(), Value::String(chunks.root.clone()));
+    }
+
+    if let Some(inner) = &file_digests.inner {
+        set_inner_digest(&mut record, inner);
+    }
+
+    if let Some(ipfs_cid) = &file_digests.ipfs_cid
+        && let Some(map) = record.as_object_mut()
+    {
+        map.insert("ipfs_cid".to_owned(), Value::String(ipfs_cid.clone()));
+    }
+
+    if let Some(fsverity_digest) = &file_digests.fsverity_digest
+        && let Some(map) = record.as_object_mut()
+    {
+        map.insert(
+            "fsverity_digest".to_owned(),
+            Value::String(fsverity_digest.clone()),
+        );
+    }
+
+    if let Some(checksums) = &file_digests.object_store
+        && let Some(map) = record.as_object_mut()
+    {
+        map.insert(
+            "object_store_checksums".to_owned(),
+            json!({
+                "s3_etag": checksums.s3_etag,
+                "s3_part_size": checksums.s3_part_size,
+                "crc32c": checksums.crc32c,
+                "crc64nvme": checksums.crc64nvme,
+            }),
+        );
+    }
+
+    if let Some(cdc_chunks) = &file_digests.cdc_chunks
+        && let Some(map) = record.as_object_mut()
+    {
+        let cdc_chunks = cdc_chunks
+            .iter()
+            .map(|chunk| {
+                json!({
+                    "offset": chunk.offset,
+                    "length": chunk.length,
+                    "blake3": chunk.blake3,
+                })
+            })
+            .collect();
+        map.insert("cdc_chunks".to_owned(), Value::Array(cdc_chunks));
+    }
+
+    record
+}
+
+/// Enrich a directory record with the git tree id built from the manifest's
+/// file records below it (`--git-tree`).
+pub fn process_git_tree_record(record: Value, tree_id: String, options: &HashOptions) -> Value {
+    let mut record = process_hashed_record(record, tree_id, options.primary().name());
+    set_bytes_hash_encoded(&mut record, options);
+    if let Some(map) = record.as_object_mut() {
+        map.insert(
+            "git_object_type".to_owned(),
+            Value::String("tree".to_owned()),
+        );
+    }
+    record
+}
+
+/// Enrich a record sampled with `--sample-hash`. The digest goes only into
+/// `sample_hash`, next to the parameters needed to reproduce it; `bytes_hash`
+/// stays null and `non_identity` tells `lock` not to pin the record.
+pub fn process_sample_record(
+    mut record: Value,
+    sample_digest: &SampleDigest,
+    sample: SampleOptions,
+) -> Value {
+    let Some(map) = record.as_object_mut() else {
+        return record;
+    };
+
+    set_hash_version(map);
+    map.insert("bytes_hash".to_owned(), Value::Null);
+    map.insert("hash_algorithm".to_owned(), Value::Null);
+    map.insert(
+        "sample_hash".to_owned(),
+        Value::String(sample_digest.digest.clone()),
+    );
+    map.insert(
+        "sample_params".to_owned(),
+        json!({
+            "edge_bytes": sample.edge_len,
+            "windows": sample.windows,
+            "window_bytes": sample.window_len,
+            "sampled_bytes": sample_digest.sampled_len,
+        }),
+    );
+    map.insert("non_identity".to_owned(), Value::Bool(true));
+    if let Some(range) = &sample_digest.range {
+        map.insert("hash_offset".to_owned(), Value::from(range.offset));
+        map.insert("hash_length".to_owned(), Value::from(range.length));
+    }
+    update_tool_versions(map);
+
+    record
+}
+
+/// Record that `--dedup-only` proved unique without the full hash. Like a
+/// sample record it carries no identity digest.
+pub fn process_dedup_screened_record(
+    mut record: Value,
+    tier: DedupTier,
+    prefix_hash: Option<&str>,
+) -> Value {
+    let Some(map) = record.as_object_mut() else {
+        return record;
+    };
+
+    set_hash_version(map);
+    map.insert("bytes_hash".to_owned(), Value::Null);
+    map.insert("hash_algorithm".to_owned(), Value::Null);
+    map.insert("non_identity".to_owned(), Value::Bool(true));
+    update_tool_versions(map);
+    set_dedup_tier(&mut record, tier, prefix_hash);
+
+    record
+}
+
+/// Add `dedup_tier` and, when the record was pre-screened, `prefix_hash`.
+pub fn set_dedup_tier(record: &mut Value, tier: DedupTier, prefix_hash: Option<&str>) {
+    let Some(map) = record.as_object_mut() else {
+        return;
+    };
+    map.insert(
+        "dedup_tier".to_owned(),
+        Value::String(tier.label().to_owned()),
+    );
+    if let Some(prefix_hash) = prefix_hash {
+        map.insert(
+            "prefix_hash".to_owned(),
+            Value::String(prefix_hash.to_owned()),
+        );
+    }
+}
+
+/// Add the `verify` result to a hash.v0 record, which otherwise passes
+/// through unchanged: `actual_bytes_hash` (and `mismatched_chunks` when the
+/// record has `chunk_hashes`) on a mismatch, an `E_IO` warning when the
+/// file is missing or unreadable, `E_CHANGED_DURING_HASH` when it kept
+/// changing while re-hashed, and an `E_BAD_INPUT` warning naming
+/// `hash_algorithm` when the digest cannot be reproduced. The recorded
+/// `bytes_hash` is never overwritten.
+pub fn process_verified_record(mut record: Value, verification: &Verification) -> Value {
+    let path = record
+        .get("path")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_owned();
+    let Some(map) = record.as_object_mut() else {
+        return record;
+    };
+
+    map.insert(
+        "verification".to_owned(),
+        Value::String(verification.label().to_owned()),
+    );
+    match verification {
+        Verification::Match => {}
+        Verification::Mismatch { actual, chunks } => {
+            map.insert(
+                "actual_bytes_hash".to_owned(),
+                Value::String(actual.clone()),
+            );
+            if let Some(chunks) = chunks {
+                map.insert("mismatched_chunks".to_owned(), Value::from(chunks.clone()));
+            }
+        }
+        Verification::Missing(error) | Verification::Unreadable(error) => {
+            append_warning(
+                map,
+                json!({
+                    "tool": "hash",
+                    "code": "E_IO",
+                    "message": "Cannot read file",
+                    "detail": {
+                        "path": path,
+                        "error": error
+                    }
+                }),
+            );
+        }
+        Verification::Changed(changed) => {
+            append_warning(map, changed_warning(&path, changed));
+        }
+        Verification::Unverifiable(error) => {
+            append_warning(
+                map,
+                json!({
+                    "tool": "hash",
+                    "code": "E_BAD_INPUT",
+                    "message": "Cannot re-hash record",
+                    "detail": {
+                        "path": path,
+                        "field": "hash_algorithm",
+                        "error": error
+                    }
+                }),
+            );
+        }
+    }
+
+    record
+}
+
+/// Add `change` against the previous run (`--baseline`).
+pub fn set_change(record: &mut Value, change: Change) {
+    if let Some(map) = record.as_object_mut() {
+        map.insert(
+            "change".to_owned(),
+            Value::String(change.label().to_owned()),
+        );
+    }
+}
+
+/// Add an `E_BASELINE_ALGORITHM` warning to a record that could not be
+/// compared with its `--baseline` entry; it gets no `change`.
+pub fn add_baseline_warning(record: &mut Value, mismatch: &NoCommonAlgorithm) {
+    let path = record
+        .get("path")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_owned();
+    if let Some(map) = record.as_object_mut() {
+        append_warning(
+            map,
+            json!({
+                "tool": "hash",
+                "code": "E_BASELINE_ALGORITHM",
+                "message": "Cannot compare with baseline",
+                "detail": {
+                    "path": path,
+                    "hash_algorithm": mismatch.hash_algorithm,
+                    "baseline_hash_algorithm": mismatch.baseline_hash_algorithm,
+                    "error": mismatch.to_string()
+                }
+            }),
+        );
+    }
+}
+
+/// Add `drift` to a record checked against a lockfile (`verify --lockfile`).
+pub fn set_drift(record: &mut Value, drift: Drift) {
+    if let Some(map) = record.as_object_mut() {
+        map.insert("drift".to_owned(), Value::String(drift.label().to_owned()));
+    }
+}
+
+/// Build the record for one archive member (`--expand-archives`). Its path is
+/// `<archive path>!/<member name>` and `parent_bytes_hash` ties it to the
+/// archive record it follows.
+pub fn process_archive_member_record(
+    archive: &Value,
+    member: &MemberDigests,
+    options: &HashOptions,
+) -> Value {
+    let mut map = Map::new();
+    for field in ["path", "relative_path"] {
+        if let Some(parent) = archive.get(field).and_then(Value::as_str) {
+            map.insert(
+                field.to_owned(),
+                Value::String(format!("{parent}!/{}", member.name)),
+            );
+        }
+    }
+    if let Some(root) = archive.get("root") {
+        map.insert("root".to_owned(), root.clone());
+    }
+    map.insert("size".to_owned(), Value::from(member.size));
+    if let Some(parent_hash) = archive.get("bytes_hash") {
+        map.insert("parent_bytes_hash".to_owned(), parent_hash.clone());
+    }
+    if let Some(tool_versions) = archive.get("tool_versions") {
+        map.insert("tool_versions".to_owned(), tool_versions.clone());
+    }
+
+    let record = Value::Object(map);
+    match &member.digests {
+        Ok(file_digests) => process_file_digests(record, file_digests, options),
+        Err(err) => process_file_io_error(record, err.to_string()),
+    }
+}
+
+/// Note the container format on an expanded archive record, and warn with
+/// `E_ARCHIVE_READ` when its members could not all be listed. The archive's
+/// own `bytes_hash` stands either way.
+pub fn set_archive_listing(record: &mut Value, format: Option<ArchiveFormat>, error: Option<&str>) {
+    let path = record
+        .get("path")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_owned();
+    let Some(map) = record.as_object_mut() else {
+        return;
+    };
+
+    if let Some(format) = format {
+        map.insert("archive_format".to_owned(), Value::String(format.label()));
+    }
+    if let Some(error) = error {
+        append_warning(
+            map,
+            json!({
+                "tool": "hash",
+                "code": "E_ARCHIVE_READ",
+                "message": "Archive members could not all be read",
+                "detail": {
+                    "path": path,
+                    "error": error
+                }
+            }),
+        );
+    }
+}
+
+/// Add `bytes_hash_encoded` when `--encoding` asked for another spelling of
+/// the primary digest. `bytes_hash` itself is never changed.
+pub fn set_bytes_hash_encoded(record: &mut Value, options: &HashOptions) {
+    let Some(encoding) = options.encoding else {
+        return;
+    };
+    let Some(map) = record.as_object_mut() else {
+        return;
+    };
+    let encoded = map
+        .get("bytes_hash")
+        .and_then(Value::as_str)
+        .and_then(|bytes_hash| encoding.encode(bytes_hash, options));
+    if let Some(encoded) = encoded {
+        map.insert("bytes_hash_encoded".to_owned(), Value::String(encoded));
+    }
+}
+
+/// Record the decoded payload's digest as `inner_bytes_hash`, or warn with
+/// `E_INNER_DECODE` when the stream is corrupt. Either way the raw
+/// `bytes_hash` stands and the record is not skipped.
+pub fn set_inner_digest(record: &mut Value, inner: &InnerDigest) {
+    let path = record
+        .get("path")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_owned();
+    let Some(map) = record.as_object_mut() else {
+        return;
+    };
+
+    map.insert(
+        "inner_compression".to_owned(),
+        Value::String(inner.compression.name().to_owned()),
+    );
+    match &inner.digest {
+        Ok(digest) => {
+            map.insert("inner_bytes_hash".to_owned(), Value::String(digest.clone()));
+        }
+        Err(error) => append_warning(
+            map,
+            json!({
+                "tool": "hash",
+                "code": "E_INNER_DECODE",
+                "message": "Compressed stream could not be decoded",
+                "detail": {
+                    "path": path,
+                    "compression": inner.compression.name(),
+                    "error": error
+                }
+            }),
+        ),
+    }
+}
+
+/// Record every digest from a multi-algorithm pass under `hashes`, keyed by
+/// algorithm name. `bytes_hash` keeps the primary digest.
+pub fn set_hashes(record: &mut Value, hashes: &[(&str, String)]) {
+    let Some(map) = record.as_object_mut() else {
+        return;
+    };
+
+    let hashes = hashes
+        .iter()
+        .map(|(algorithm, digest)| ((*algorithm).to_owned(), Value::String(digest.clone())))
+        .collect::<Map<String, Value>>();
+    map.insert("hashes".to_owned(), Value::Object(hashes));
+}
+
+/// Flag a record whose `bytes_hash` comes from a non-cryptographic algorithm
+/// so downstream tools never treat it as identity.
+pub fn mark_non_cryptographic(record: &mut Value) {
+    if let Some(map) = record.as_object_mut() {
+        map.insert("non_cryptographic".to_owned(), Value::Bool(true));
+    }
+}
+
 pub fn process_file_io_error(record: Value, error: impl Into<String>) -> Value {
     let path = record
         .get("path")
@@ -110,7 +575,74 @@ pub fn update_tool_versions(record: &mut Map<String, Value>) {
 }
 
 /// Process a record where file hashing failed due to IO error
-pub fn process_io_failed_record(mut record: Value, path: &str, io_error: &str) -> Value {
+pub fn process_io_failed_record(record: Value, path: &str, io_error: &str) -> Value {
+    process_failed_record(
+        record,
+        json!({
+            "tool": "hash",
+            "code": "E_IO",
+            "message": "Cannot read file",
+            "detail": {
+                "path": path,
+                "error": io_error
+            }
+        }),
+    )
+}
+
+/// Process a record whose `hash_offset`/`hash_length` reach past end of file
+pub fn process_range_failed_record(record: Value, path: &str, past_eof: &RangePastEof) -> Value {
+    process_failed_record(
+        record,
+        json!({
+            "tool": "hash",
+            "code": "E_RANGE_PAST_EOF",
+            "message": "Hash range extends past end of file",
+            "detail": {
+                "path": path,
+                "hash_offset": past_eof.range.offset,
+                "hash_length": past_eof.range.length,
+                "file_size": past_eof.file_len
+            }
+        }),
+    )
+}
+
+/// Process a record whose file kept changing while it was hashed
+pub fn process_changed_record(record: Value, path: &str, changed: &ChangedDuringHash) -> Value {
+    process_failed_record(record, changed_warning(path, changed))
+}
+
+fn changed_warning(path: &str, changed: &ChangedDuringHash) -> Value {
+    json!({
+        "tool": "hash",
+        "code": "E_CHANGED_DURING_HASH",
+        "message": "File changed while being hashed",
+        "detail": {
+            "path": path,
+            "attempts": changed.attempts,
+            "changed": changed.changed
+        }
+    })
+}
+
+/// Process a directory record whose git tree has files without a blob id
+pub fn process_git_tree_failed_record(record: Value, path: &str, unresolved: &[String]) -> Value {
+    process_failed_record(
+        record,
+        json!({
+            "tool": "hash",
+            "code": "E_GIT_TREE_INCOMPLETE",
+            "message": "Directory contains files without a git blob id",
+            "detail": {
+                "path": path,
+                "unresolved": unresolved
+            }
+        }),
+    )
+}
+
+fn process_failed_record(mut record: Value, warning: Value) -> Value {
     let Some(map) = record.as_object_mut() else {
         return record;
     };
@@ -123,8 +655,7 @@ pub fn process_io_failed_record(mut record: Value, path: &str, io_error: &str) -
     map.insert("bytes_hash".to_owned(), Value::Null);
     map.insert("hash_algorithm".to_owned(), Value::Null);
 
-    // Append IO failure warning
-    append_io_warning(map, path, io_error);
+    append_warning(map, warning);
 
     // Update tool_versions
     update_tool_versions(map);
@@ -132,24 +663,14 @@ pub fn process_io_failed_record(mut record: Value, path: &str, io_error: &str) -
     record
 }
 
-/// Append an IO warning to the _warnings array
-fn append_io_warning(record: &mut Map<String, Value>, path: &str, error: &str) {
+/// Append a warning to the _warnings array
+fn append_warning(record: &mut Map<String, Value>, warning: Value) {
     let mut warnings = record
         .get("_warnings")
         .and_then(|v| v.as_array())
         .cloned()
         .unwrap_or_default();
 
-    let warning = serde_json::json!({
-        "tool": "hash",
-        "code": "E_IO",
-        "message": "Cannot read file",
-        "detail": {
-            "path": path,
-            "error": error
-        }
-    });
-
     warnings.push(warning);
     record.insert("_warnings".to_owned(), Value::Array(warnings));
 }
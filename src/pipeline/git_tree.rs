@@ -0,0 +1,129 @@
+use crate::cli::Algorithm;
+use crate::hash::chunks::digest_bytes;
+use crate::hash::git::{self, GitTree};
+use serde_json::Value;
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+
+/// Blob ids of every file record in a manifest, used to resolve directory
+/// records to git tree ids under `--git-tree`.
+#[derive(Debug, Default)]
+pub struct TreeIndex {
+    files: Vec<IndexedFile>,
+}
+
+#[derive(Debug)]
+struct IndexedFile {
+    path: PathBuf,
+    /// Tree entry mode and raw blob id; `None` when the record was skipped or
+    /// hashed only a byte range, so no blob id exists for it.
+    blob: Option<(&'static str, Vec<u8>)>,
+}
+
+/// A directory whose tree cannot be built because some files beneath it
+/// have no blob id.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IncompleteTree {
+    pub unresolved: Vec<String>,
+}
+
+impl fmt::Display for IncompleteTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "git tree incomplete: {} file(s) below the directory have no blob id",
+            self.unresolved.len()
+        )
+    }
+}
+
+impl std::error::Error for IncompleteTree {}
+
+impl TreeIndex {
+    /// Index one processed file record hashed with the git `algorithm`.
+    pub fn add_file_record(&mut self, record: &Value, algorithm: Algorithm) {
+        let Some(path) = record.get("path").and_then(Value::as_str) else {
+            return;
+        };
+        let path = PathBuf::from(path);
+        let ranged = record
+            .get("hash_offset")
+            .is_some_and(|value| !value.is_null());
+        let blob = record
+            .get("bytes_hash")
+            .and_then(Value::as_str)
+            .filter(|digest| {
+                !ranged
+                    && digest
+                        .strip_prefix(algorithm.prefix())
+                        .is_some_and(|rest| rest.starts_with(':'))
+            })
+            .and_then(|digest| blob_entry(&path, digest, algorithm));
+        self.files.push(IndexedFile { path, blob });
+    }
+
+    /// Prefixed git tree id of `dir`, built from every indexed file below it.
+    pub fn tree_id(&self, dir: &Path, algorithm: Algorithm) -> Result<String, IncompleteTree> {
+        let mut tree = GitTree::default();
+        let mut unresolved = Vec::new();
+        for file in &self.files {
+            let Some(components) = relative_components(&file.path, dir) else {
+                continue;
+            };
+            match &file.blob {
+                Some((mode, id)) => tree.insert_blob(&components, mode, id.clone()),
+                None => unresolved.push(file.path.to_string_lossy().into_owned()),
+            }
+        }
+        if !unresolved.is_empty() {
+            return Err(IncompleteTree { unresolved });
+        }
+
+        let id = tree.id(algorithm).ok_or_else(|| IncompleteTree {
+            unresolved: Vec::new(),
+        })?;
+        let hex: String = id.iter().map(|byte| format!("{byte:02x}")).collect();
+        Ok(algorithm.format_bytes_hash(&hex))
+    }
+}
+
+/// Entry mode and blob id for a hashed file. Symlinks are stored as git
+/// stores them, a blob of the link target, rather than the followed content.
+fn blob_entry(path: &Path, digest: &str, algorithm: Algorithm) -> Option<(&'static str, Vec<u8>)> {
+    let metadata = std::fs::symlink_metadata(path).ok()?;
+    if metadata.file_type().is_symlink() {
+        let target = std::fs::read_link(path).ok()?;
+        let id = git::object_id(algorithm, "blob", target.as_os_str().as_encoded_bytes())?;
+        return Some((git::MODE_SYMLINK, id));
+    }
+    let mode = if is_executable(&metadata) {
+        git::MODE_EXECUTABLE
+    } else {
+        git::MODE_FILE
+    };
+    Some((mode, digest_bytes(digest)))
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o100 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_metadata: &std::fs::Metadata) -> bool {
+    false
+}
+
+/// Names of `path` below `dir`, or `None` when it is not strictly inside.
+fn relative_components(path: &Path, dir: &Path) -> Option<Vec<Vec<u8>>> {
+    let relative = path.strip_prefix(dir).ok()?;
+    let mut components = Vec::new();
+    for component in relative.components() {
+        match component {
+            Component::Normal(name) => components.push(name.as_encoded_bytes().to_vec()),
+            _ => return None,
+        }
+    }
+    (!components.is_empty()).then_some(components)
+}
@@ -0,0 +1,195 @@
+use super::verify::Verification;
+use crate::cli::Algorithm;
+use crate::refusal::{RefusalCode, RefusalEnvelope};
+use serde_json::{Map, Value, json};
+use std::collections::BTreeSet;
+use std::io::BufRead;
+use std::path::Path;
+
+/// How a pinned artifact differs from the disk (`verify --lockfile`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Drift {
+    /// Listed in the current manifest but not pinned.
+    Added,
+    /// Pinned but no longer on disk.
+    Missing,
+    /// Pinned and on disk with other bytes.
+    Changed,
+}
+
+impl Drift {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Added => "added",
+            Self::Missing => "missing",
+            Self::Changed => "changed",
+        }
+    }
+
+    /// Drift a verification result amounts to; `None` for a match and for
+    /// files that exist but could not be read or checked.
+    pub fn of(verification: &Verification) -> Option<Self> {
+        match verification {
+            Verification::Mismatch { .. } => Some(Self::Changed),
+            Verification::Missing(_) => Some(Self::Missing),
+            Verification::Match
+            | Verification::Unreadable(_)
+            | Verification::Changed(_)
+            | Verification::Unverifiable(_) => None,
+        }
+    }
+}
+
+/// Read the pinned artifacts of a `lock` lockfile into hash.v0 JSONL, ready
+/// for the `verify` pipeline. Artifacts come from the lockfile's `members`
+/// array, the only place `lock` writes them, and keep their fields; relative
+/// paths resolve against the lockfile's directory, so a lockfile checked in
+/// next to its data verifies from anywhere. When `current` names a manifest
+/// of what is on disk now (vacuum or hash.v0), its records whose path is not
+/// pinned follow as `drift: "added"` records with nothing to check.
+pub fn read_lockfile(
+    lockfile: &Path,
+    current: Option<&Path>,
+) -> Result<Vec<u8>, Box<RefusalEnvelope>> {
+    let contents = std::fs::read(lockfile).map_err(|err| io_refusal(lockfile, &err))?;
+    let lock: Value = serde_json::from_slice(&contents).map_err(|err| {
+        Box::new(RefusalEnvelope::from_code(
+            RefusalCode::BadInput,
+            json!({ "lockfile": lockfile.to_string_lossy(), "error": err.to_string() }),
+        ))
+    })?;
+    let artifacts = lock
+        .get("members")
+        .and_then(Value::as_array)
+        .ok_or_else(|| {
+            Box::new(RefusalEnvelope::from_code(
+                RefusalCode::BadInput,
+                json!({
+                    "lockfile": lockfile.to_string_lossy(),
+                    "error": "expected a lock lockfile with a members array of pinned artifacts"
+                }),
+            ))
+        })?;
+    let base_dir = lockfile.parent().unwrap_or(Path::new(""));
+
+    let mut pinned_keys = BTreeSet::new();
+    let mut records = Vec::with_capacity(artifacts.len());
+    for (index, artifact) in artifacts.iter().enumerate() {
+        let record = pinned_record(artifact, base_dir).map_err(|(field, error)| {
+            Box::new(RefusalEnvelope::from_code(
+                RefusalCode::BadInput,
+                json!({ "artifact": index, "field": field, "error": error }),
+            ))
+        })?;
+        // Listings may spell the path as pinned or as resolved.
+        pinned_keys.extend(path_keys(artifact));
+        pinned_keys.extend(path_keys(&record));
+        records.push(record);
+    }
+
+    if let Some(current) = current {
+        let file = std::fs::File::open(current).map_err(|err| io_refusal(current, &err))?;
+        let mut reader = std::io::BufReader::new(file);
+        let mut buffer = String::new();
+        let mut line_number = 0;
+        loop {
+            buffer.clear();
+            let bytes_read = reader
+                .read_line(&mut buffer)
+                .map_err(|err| io_refusal(current, &err))?;
+            if bytes_read == 0 {
+                break;
+            }
+            line_number += 1;
+            if buffer.trim().is_empty() {
+                continue;
+            }
+            let listed = super::reader::parse_json_line(&buffer, line_number)?.record;
+            if !path_keys(&listed).any(|key| pinned_keys.contains(&key)) {
+                records.push(added_record(&listed));
+            }
+        }
+    }
+
+    let mut jsonl = Vec::new();
+    for record in &records {
+        crate::output::jsonl::write_json_line(&mut jsonl, record)
+            .map_err(|err| Box::new(RefusalEnvelope::io_error(err.to_string())))?;
+    }
+    Ok(jsonl)
+}
+
+/// A pinned artifact as a hash.v0 record `verify` can check: `path` (or
+/// `relative_path`) is resolved against `base_dir` and made absolute, and
+/// `hash_algorithm` is filled from the `bytes_hash` prefix when the lockfile
+/// leaves it out.
+fn pinned_record(artifact: &Value, base_dir: &Path) -> Result<Value, (&'static str, String)> {
+    let mut record = artifact
+        .as_object()
+        .cloned()
+        .ok_or(("artifact", "expected an object".to_owned()))?;
+
+    let path = ["path", "relative_path"]
+        .iter()
+        .find_map(|field| record.get(*field).and_then(Value::as_str))
+        .ok_or(("path", "missing".to_owned()))?;
+    let path = std::path::absolute(base_dir.join(path)).map_err(|err| ("path", err.to_string()))?;
+    record.insert("path".to_owned(), json!(path.to_string_lossy()));
+
+    let bytes_hash = record
+        .get("bytes_hash")
+        .and_then(Value::as_str)
+        .ok_or(("bytes_hash", "missing".to_owned()))?;
+    if record
+        .get("hash_algorithm")
+        .and_then(Value::as_str)
+        .is_none()
+    {
+        let algorithm = Algorithm::all()
+            .into_iter()
+            .find(|algorithm| {
+                bytes_hash
+                    .strip_prefix(algorithm.prefix())
+                    .is_some_and(|rest| rest.starts_with(':'))
+            })
+            .ok_or((
+                "bytes_hash",
+                format!("unknown algorithm prefix in '{bytes_hash}'"),
+            ))?;
+        record.insert("hash_algorithm".to_owned(), json!(algorithm.name()));
+    }
+
+    super::enricher::set_hash_version(&mut record);
+    super::enricher::merge_tool_versions(&mut record);
+    Ok(Value::Object(record))
+}
+
+fn added_record(listed: &Value) -> Value {
+    let mut record = Map::new();
+    super::enricher::set_hash_version(&mut record);
+    for field in ["path", "relative_path", "size"] {
+        if let Some(value) = listed.get(field) {
+            record.insert(field.to_owned(), value.clone());
+        }
+    }
+    record.insert("bytes_hash".to_owned(), Value::Null);
+    record.insert("hash_algorithm".to_owned(), Value::Null);
+    record.insert("drift".to_owned(), json!(Drift::Added.label()));
+    super::enricher::merge_tool_versions(&mut record);
+    Value::Object(record)
+}
+
+/// Keys a record can be matched on across the lockfile and a listing.
+fn path_keys(record: &Value) -> impl Iterator<Item = String> + '_ {
+    ["path", "relative_path"].into_iter().filter_map(|field| {
+        let value = record.get(field)?.as_str()?;
+        Some(format!("{field}\0{value}"))
+    })
+}
+
+fn io_refusal(path: &Path, err: &std::io::Error) -> Box<RefusalEnvelope> {
+    Box::new(RefusalEnvelope::from_code(
+        RefusalCode::Io,
+        json!({ "path": path.to_string_lossy(), "error": err.to_string() }),
+    ))
+}
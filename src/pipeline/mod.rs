@@ -1,3 +1,10 @@
+pub mod baseline;
+pub mod dedup;
+pub mod duplicates;
 pub mod enricher;
+pub mod git_tree;
+pub mod lockfile;
 pub mod parallel;
 pub mod reader;
+pub mod sums;
+pub mod verify;
@@ -1,6 +1,7 @@
 use serde_json::Value;
 use std::io::{self, BufRead};
 
+use crate::hash::ByteRange;
 use crate::refusal::{RefusalCode, RefusalEnvelope};
 
 #[derive(Debug, Clone, PartialEq)]
@@ -29,6 +30,39 @@ pub fn parse_json_line(line: &str, line_number: usize) -> Result<ParsedLine, Box
     })
 }
 
+/// Read the optional `hash_offset`/`hash_length` fields. Either one selects
+/// range hashing; both must be non-negative integers when present.
+pub fn record_byte_range(
+    record: &Value,
+    line_number: usize,
+) -> Result<Option<ByteRange>, Box<RefusalEnvelope>> {
+    let field = |name: &str| -> Result<Option<u64>, Box<RefusalEnvelope>> {
+        match record.get(name) {
+            None | Some(Value::Null) => Ok(None),
+            Some(value) => value.as_u64().map(Some).ok_or_else(|| {
+                Box::new(RefusalEnvelope::from_code(
+                    RefusalCode::BadInput,
+                    serde_json::json!({
+                        "line": line_number,
+                        "field": name,
+                        "error": "must be a non-negative integer"
+                    }),
+                ))
+            }),
+        }
+    };
+
+    let offset = field("hash_offset")?;
+    let length = field("hash_length")?;
+    if offset.is_none() && length.is_none() {
+        return Ok(None);
+    }
+    Ok(Some(ByteRange {
+        offset: offset.unwrap_or(0),
+        length,
+    }))
+}
+
 fn ensure_required_fields(record: &Value, line_number: usize) -> Result<(), Box<RefusalEnvelope>> {
     let Some(object) = record.as_object() else {
         return Err(Box::new(RefusalEnvelope::from_code(
@@ -0,0 +1,365 @@
+use crate::cli::Algorithm;
+use crate::refusal::{RefusalCode, RefusalEnvelope};
+use serde_json::{Value, json};
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Checksum-file line formats read by `hashbytes check` and written by
+/// `--emit-sums`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SumsFormat {
+    /// `<hex>  <name>`, as `sha256sum` and `b3sum` print it.
+    Gnu,
+    /// `<TAG> (<name>) = <hex>`, as `sha256sum --tag` and BSD `sha256` print it.
+    Bsd,
+}
+
+impl SumsFormat {
+    pub const ALL: [Self; 2] = [Self::Gnu, Self::Bsd];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Gnu => "gnu",
+            Self::Bsd => "bsd",
+        }
+    }
+}
+
+impl FromStr for SumsFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|format| s.eq_ignore_ascii_case(format.name()))
+            .ok_or_else(|| format!("Invalid checksum format '{s}'. Expected one of: gnu, bsd"))
+    }
+}
+
+impl fmt::Display for SumsFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// BSD tags of the algorithms that have a checksum-file spelling.
+const BSD_TAGS: [(&str, &str); 6] = [
+    ("sha256", "SHA256"),
+    ("sha384", "SHA384"),
+    ("sha512", "SHA512"),
+    ("sha3-256", "SHA3-256"),
+    ("blake3", "BLAKE3"),
+    ("xxh3-128", "XXH128"),
+];
+
+/// The BSD tag for `algorithm`, or `None` when checksum files have no way
+/// to name it (git object ids, registered algorithms).
+pub fn bsd_tag(algorithm: Algorithm) -> Option<&'static str> {
+    BSD_TAGS
+        .iter()
+        .find(|(name, _)| *name == algorithm.name())
+        .map(|(_, tag)| *tag)
+}
+
+fn tagged_algorithm(tag: &str) -> Option<Algorithm> {
+    BSD_TAGS
+        .iter()
+        .find(|(_, known)| known.eq_ignore_ascii_case(tag))
+        .and_then(|(name, _)| Algorithm::from_str(name).ok())
+}
+
+/// Checksum-file stems and the algorithm they name, matched exactly against
+/// `<STEM>SUMS` (`B3SUMS`, `sha256sums`) or a `.<stem>` extension
+/// (`release.tar.b3`, `image.sha512`), case-insensitively.
+const FILE_NAME_STEMS: [(&str, &str); 8] = [
+    ("b3", "blake3"),
+    ("blake3", "blake3"),
+    ("sha256", "sha256"),
+    ("sha384", "sha384"),
+    ("sha512", "sha512"),
+    ("sha3", "sha3-256"),
+    ("sha3-256", "sha3-256"),
+    ("xxh128", "xxh3-128"),
+];
+
+/// Algorithm for untagged lines when `--algorithm` is not given: from the
+/// file name (`B3SUMS`, `SHA512SUMS`, `x.sha256`, ...), else from the
+/// digest length.
+fn untagged_algorithm(sums_path: &Path, hex_len: usize) -> Option<Algorithm> {
+    let file_name = sums_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+    let stem = file_name
+        .strip_suffix("sums")
+        .or_else(|| file_name.rsplit_once('.').map(|(_, extension)| extension));
+    let by_name = stem.and_then(|stem| {
+        FILE_NAME_STEMS
+            .iter()
+            .find(|(known, _)| *known == stem)
+            .map(|(_, name)| *name)
+    });
+    let by_len = match hex_len {
+        64 => Some("sha256"),
+        96 => Some("sha384"),
+        128 => Some("sha512"),
+        _ => None,
+    };
+    Algorithm::from_str(by_name.or(by_len)?).ok()
+}
+
+/// Read a checksum file into hash.v0 JSONL, one record per listed file,
+/// ready for the `verify` pipeline. Relative names resolve against the
+/// working directory, as `sha256sum -c` does, and are kept as
+/// `relative_path`.
+pub fn read_sums_file(
+    sums_path: &Path,
+    algorithm: Option<&str>,
+) -> Result<Vec<u8>, Box<RefusalEnvelope>> {
+    let algorithm = algorithm
+        .map(Algorithm::from_str)
+        .transpose()
+        .map_err(|error| {
+            Box::new(RefusalEnvelope::from_code(
+                RefusalCode::BadInput,
+                json!({ "flag": "--algorithm", "error": error }),
+            ))
+        })?;
+    let contents = std::fs::read_to_string(sums_path).map_err(|err| {
+        Box::new(RefusalEnvelope::from_code(
+            RefusalCode::Io,
+            json!({
+                "path": sums_path.to_string_lossy(),
+                "error": err.to_string()
+            }),
+        ))
+    })?;
+
+    let mut jsonl = Vec::new();
+    for (index, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let bad_line = |error: String| {
+            Box::new(RefusalEnvelope::from_code(
+                RefusalCode::BadInput,
+                json!({ "line": index + 1, "error": error }),
+            ))
+        };
+        let entry = parse_line(line).map_err(bad_line)?;
+        let algorithm = entry
+            .algorithm
+            .or(algorithm)
+            .or_else(|| untagged_algorithm(sums_path, entry.hex.len()))
+            .ok_or_else(|| {
+                bad_line("cannot tell the algorithm from the digest; pass --algorithm".to_owned())
+            })?;
+        if entry.hex.len() != algorithm.digest_hex_len() {
+            return Err(bad_line(format!(
+                "expected {} hex chars for {algorithm}",
+                algorithm.digest_hex_len()
+            )));
+        }
+
+        let path = std::path::absolute(&entry.name).map_err(|err| bad_line(err.to_string()))?;
+        let record = json!({
+            "version": super::enricher::HASH_VERSION,
+            "path": path.to_string_lossy(),
+            "relative_path": entry.name,
+            "bytes_hash": algorithm.format_bytes_hash(&entry.hex),
+            "hash_algorithm": algorithm.name(),
+            "tool_versions": { "hash": env!("CARGO_PKG_VERSION") },
+        });
+        crate::output::jsonl::write_json_line(&mut jsonl, &record)
+            .map_err(|err| Box::new(RefusalEnvelope::io_error(err.to_string())))?;
+    }
+    Ok(jsonl)
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct SumsEntry {
+    /// Set by a BSD tag; GNU lines do not name their algorithm.
+    algorithm: Option<Algorithm>,
+    hex: String,
+    name: String,
+}
+
+fn parse_line(line: &str) -> Result<SumsEntry, String> {
+    let (escaped, line) = match line.strip_prefix('\\') {
+        Some(rest) => (true, rest),
+        None => (false, line),
+    };
+    let unescape = |name: &str| {
+        if escaped {
+            unescape_name(name)
+        } else {
+            Ok(name.to_owned())
+        }
+    };
+    let is_hex = |hex: &str| !hex.is_empty() && hex.bytes().all(|b| b.is_ascii_hexdigit());
+
+    // BSD tag: `TAG (name) = hex`; the name itself may contain ") = ".
+    if let Some((head, hex)) = line.rsplit_once(") = ")
+        && let Some((tag, name)) = head.split_once(" (")
+        && !tag.contains(' ')
+        && is_hex(hex)
+    {
+        let algorithm =
+            tagged_algorithm(tag).ok_or_else(|| format!("unsupported checksum tag '{tag}'"))?;
+        return Ok(SumsEntry {
+            algorithm: Some(algorithm),
+            hex: hex.to_ascii_lowercase(),
+            name: unescape(name)?,
+        });
+    }
+
+    // GNU: `hex  name` (text) or `hex *name` (binary).
+    let (hex, rest) = line
+        .split_once(' ')
+        .ok_or_else(|| "expected '<hex>  <name>' or '<TAG> (<name>) = <hex>'".to_owned())?;
+    let name = rest
+        .strip_prefix(' ')
+        .or_else(|| rest.strip_prefix('*'))
+        .filter(|name| !name.is_empty())
+        .ok_or_else(|| "expected two spaces or ' *' between digest and name".to_owned())?;
+    if !is_hex(hex) {
+        return Err("digest is not hexadecimal".to_owned());
+    }
+    Ok(SumsEntry {
+        algorithm: None,
+        hex: hex.to_ascii_lowercase(),
+        name: unescape(name)?,
+    })
+}
+
+fn unescape_name(name: &str) -> Result<String, String> {
+    let mut unescaped = String::with_capacity(name.len());
+    let mut chars = name.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('\\') => unescaped.push('\\'),
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            _ => return Err("invalid escape in file name".to_owned()),
+        }
+    }
+    Ok(unescaped)
+}
+
+/// One checksum line (with newline) for a hash.v0 record, named by its
+/// `relative_path` when it has one. `None` when the record has no whole-file
+/// digest a checksum tool could check: skipped, ranged or `non_identity`
+/// records, or algorithms without a checksum-file spelling.
+pub fn render_line(record: &Value, format: SumsFormat) -> Option<String> {
+    if ["hash_offset", "hash_length"]
+        .iter()
+        .any(|field| record.get(*field).is_some_and(|value| !value.is_null()))
+    {
+        return None;
+    }
+    let algorithm = Algorithm::from_str(record.get("hash_algorithm")?.as_str()?).ok()?;
+    let tag = bsd_tag(algorithm)?;
+    let hex = record
+        .get("bytes_hash")?
+        .as_str()?
+        .strip_prefix(algorithm.prefix())?
+        .strip_prefix(':')?;
+    let name = ["relative_path", "path"]
+        .iter()
+        .find_map(|field| record.get(*field).and_then(Value::as_str))?;
+
+    // GNU tools mark a line whose name needs escaping with a leading '\'.
+    let needs_escape = name.contains(['\\', '\n', '\r']);
+    let name = if needs_escape {
+        name.replace('\\', "\\\\")
+            .replace('\n', "\\n")
+            .replace('\r', "\\r")
+    } else {
+        name.to_owned()
+    };
+    let escape = if needs_escape { "\\" } else { "" };
+    Some(match format {
+        SumsFormat::Gnu => format!("{escape}{hex}  {name}\n"),
+        SumsFormat::Bsd => format!("{escape}{tag} ({name}) = {hex}\n"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gnu_bsd_and_escaped_lines_parse() {
+        let hex = "ab".repeat(32);
+        let gnu = parse_line(&format!("{hex}  dir/a b.txt")).expect("gnu line");
+        assert_eq!(gnu.algorithm, None);
+        assert_eq!(gnu.name, "dir/a b.txt");
+
+        let binary = parse_line(&format!("{}  *x", hex.to_ascii_uppercase()));
+        assert_eq!(binary.expect("binary line").name, "*x");
+        let binary = parse_line(&format!("{hex} *x")).expect("binary line");
+        assert_eq!(
+            (binary.hex.as_str(), binary.name.as_str()),
+            (hex.as_str(), "x")
+        );
+
+        let bsd = parse_line(&format!("BLAKE3 (odd) = name) = {hex}")).expect("bsd line");
+        assert_eq!(
+            bsd.algorithm.map(|algorithm| algorithm.name()),
+            Some("blake3")
+        );
+        assert_eq!(bsd.name, "odd) = name");
+
+        let escaped = parse_line(&format!("\\{hex}  a\\nb\\\\c")).expect("escaped line");
+        assert_eq!(escaped.name, "a\nb\\c");
+
+        assert!(parse_line("MD5 (x) = d41d8cd98f00b204e9800998ecf8427e").is_err());
+        assert!(parse_line(&format!("{hex} x")).is_err());
+    }
+
+    #[test]
+    fn file_names_match_known_stems_exactly() {
+        let named = |name: &str| untagged_algorithm(Path::new(name), 64).map(|a| a.name());
+        assert_eq!(named("B3SUMS"), Some("blake3"));
+        assert_eq!(named("dist/b3sums"), Some("blake3"));
+        assert_eq!(named("release.tar.b3"), Some("blake3"));
+        assert_eq!(named("SHA3-256SUMS"), Some("sha3-256"));
+        assert_eq!(named("image.SHA512"), Some("sha512"));
+        assert_eq!(named("XXH128SUMS"), Some("xxh3-128"));
+
+        // Names that merely contain a stem fall back to the digest length.
+        assert_eq!(named("web3sums.txt"), Some("sha256"));
+        assert_eq!(named("b3_results"), Some("sha256"));
+        assert_eq!(named("sha512-notes"), Some("sha256"));
+        assert_eq!(named("checksums"), Some("sha256"));
+        assert_eq!(
+            untagged_algorithm(Path::new("checksums"), 128).map(|a| a.name()),
+            Some("sha512")
+        );
+        assert_eq!(untagged_algorithm(Path::new("checksums"), 40), None);
+    }
+
+    #[test]
+    fn rendered_lines_round_trip() {
+        let record = json!({
+            "path": "/data/a\nb",
+            "bytes_hash": format!("sha256:{}", "0f".repeat(32)),
+            "hash_algorithm": "sha256",
+        });
+        for format in SumsFormat::ALL {
+            let line = render_line(&record, format).expect("rendered line");
+            let entry = parse_line(line.trim_end_matches('\n')).expect("parsed line");
+            assert_eq!(entry.name, "/data/a\nb");
+            assert_eq!(entry.hex, "0f".repeat(32));
+        }
+        assert_eq!(
+            render_line(&record, SumsFormat::Bsd).as_deref(),
+            Some(format!("\\SHA256 (/data/a\\nb) = {}\n", "0f".repeat(32)).as_str())
+        );
+    }
+}
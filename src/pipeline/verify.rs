@@ -0,0 +1,215 @@
+use crate::cli::Algorithm;
+use crate::hash::blake3::{Blake3Mode, Blake3Options, MAX_DIGEST_LENGTH, MIN_DIGEST_LENGTH};
+use crate::hash::{self, HashOptions};
+use crate::refusal::{RefusalCode, RefusalEnvelope};
+use serde_json::{Value, json};
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+/// How a recorded `bytes_hash` compares with the file on disk (`verify`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verification {
+    Match,
+    /// The file now hashes to `actual`. When the record carries
+    /// `chunk_hashes`, `chunks` lists the indices of chunks that changed.
+    Mismatch {
+        actual: String,
+        chunks: Option<Vec<usize>>,
+    },
+    /// The file no longer exists; carries the open error.
+    Missing(String),
+    /// The file exists but could not be read in full.
+    Unreadable(String),
+    /// The file changed during every re-hash attempt; reported as
+    /// `unreadable`, with an `E_CHANGED_DURING_HASH` warning.
+    Changed(hash::snapshot::ChangedDuringHash),
+    /// The recorded digest cannot be reproduced by this run, such as a
+    /// keyed BLAKE3 record without `--blake3-key-file`; carries why.
+    Unverifiable(String),
+}
+
+impl Verification {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Match => "match",
+            Self::Mismatch { .. } => "mismatch",
+            Self::Missing(_) => "missing",
+            Self::Unreadable(_) | Self::Changed(_) => "unreadable",
+            Self::Unverifiable(_) => "unverifiable",
+        }
+    }
+}
+
+/// Re-hash the file behind one hash.v0 record with its recorded
+/// `hash_algorithm`, over the recorded byte range if there is one. Recorded
+/// `chunk_hashes` are recomputed in the same pass so a mismatch can name the
+/// chunks that changed. A file that changes mid-read is read again, up to
+/// `change_retries` more times, as when hashing.
+///
+/// Returns `None` for records that carry nothing to check: skipped and
+/// `non_identity` records (no `bytes_hash`), git tree and archive member
+/// records (no file of their own at `path`). [`nothing_to_check`] tells the
+/// ones left unchecked by design from skipped ones.
+/// Whether `verify` passes a record over by design rather than for want of
+/// a digest: `non_identity` records never had one, a git tree record has no
+/// file at `path`, and an archive member's bytes are covered by its
+/// archive's own record. Skipped records are never among them.
+pub fn nothing_to_check(record: &Value) -> bool {
+    let Some(map) = record.as_object() else {
+        return false;
+    };
+    let present = |field: &str| map.get(field).is_some_and(|value| !value.is_null());
+    !super::enricher::is_skipped(map)
+        && (map.get("non_identity").and_then(Value::as_bool) == Some(true)
+            || present("git_object_type")
+            || present("parent_bytes_hash"))
+}
+
+pub fn verify_record(
+    record: &Value,
+    line_number: usize,
+    options: &HashOptions,
+) -> Result<Option<Verification>, Box<RefusalEnvelope>> {
+    let Some(expected) = record.get("bytes_hash").and_then(Value::as_str) else {
+        return Ok(None);
+    };
+    if ["git_object_type", "parent_bytes_hash"]
+        .iter()
+        .any(|field| record.get(*field).is_some())
+    {
+        return Ok(None);
+    }
+
+    let bad_algorithm = |error: String| {
+        Box::new(RefusalEnvelope::from_code(
+            RefusalCode::BadInput,
+            json!({
+                "line": line_number,
+                "field": "hash_algorithm",
+                "error": error
+            }),
+        ))
+    };
+    let label = record
+        .get("hash_algorithm")
+        .and_then(Value::as_str)
+        .ok_or_else(|| bad_algorithm("missing".to_owned()))?;
+    let (algorithm, blake3) = if BLAKE3_MODE_LABELS.contains(&label) {
+        match recorded_blake3(label, expected, &options.blake3) {
+            Ok(blake3) => (Algorithm::Blake3, blake3),
+            Err(error) => return Ok(Some(Verification::Unverifiable(error))),
+        }
+    } else {
+        let algorithm = Algorithm::from_str(label).map_err(bad_algorithm)?;
+        (algorithm, Blake3Options::default())
+    };
+    let path = record.get("path").and_then(Value::as_str).ok_or_else(|| {
+        Box::new(RefusalEnvelope::bad_input_missing_field(
+            line_number,
+            "path",
+        ))
+    })?;
+    let range = super::reader::record_byte_range(record, line_number)?;
+
+    let recorded_chunks = recorded_chunk_hashes(record);
+    let retries = options.change_retries;
+    let options = HashOptions {
+        algorithms: vec![algorithm],
+        blake3,
+        chunk_size: recorded_chunks.as_ref().map(|(chunk_size, _)| *chunk_size),
+        parallel_blake3_min_len: options.parallel_blake3_min_len,
+        ..HashOptions::new(Vec::new())
+    };
+    let path = Path::new(path);
+    let hashed = hash::snapshot::hash_unchanged(path, retries, || match range {
+        Some(range) => hash::hash_file_range_with_options(path, range, &options),
+        None => hash::hash_file_with_options(path, &options),
+    });
+
+    Ok(Some(match hashed {
+        Ok(mut file_digests) => {
+            let actual = file_digests.digests.remove(0);
+            if actual == expected {
+                Verification::Match
+            } else {
+                let chunks = recorded_chunks
+                    .zip(file_digests.chunks)
+                    .map(|((_, recorded), computed)| changed_chunks(&recorded, &computed.hashes));
+                Verification::Mismatch { actual, chunks }
+            }
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Verification::Missing(err.to_string()),
+        Err(err) => match hash::snapshot::changed_during_hash(&err) {
+            Some(changed) => Verification::Changed(changed.clone()),
+            None => Verification::Unreadable(err.to_string()),
+        },
+    }))
+}
+
+/// `hash_algorithm` labels of the BLAKE3 modes, which are not algorithm
+/// names of their own.
+const BLAKE3_MODE_LABELS: [&str; 3] = ["blake3-keyed", "blake3-derive", "blake3-xof"];
+
+/// BLAKE3 options that reproduce a `blake3-keyed`, `blake3-derive` or
+/// `blake3-xof` digest. The output length comes from the recorded digest;
+/// the key or context must come from the run (`--blake3-key-file`,
+/// `--blake3-context`), since a manifest never carries them.
+fn recorded_blake3(
+    label: &str,
+    expected: &str,
+    run: &Blake3Options,
+) -> Result<Blake3Options, String> {
+    let hex_len = expected
+        .strip_prefix(label)
+        .and_then(|rest| rest.strip_prefix(':'))
+        .map(str::len)
+        .ok_or_else(|| format!("bytes_hash does not start with '{label}:'"))?;
+    let digest_length = hex_len / 2;
+    if hex_len % 2 != 0 || !(MIN_DIGEST_LENGTH..=MAX_DIGEST_LENGTH).contains(&digest_length) {
+        return Err(format!(
+            "{hex_len} hex chars is not a BLAKE3 output length ({MIN_DIGEST_LENGTH}-{MAX_DIGEST_LENGTH} bytes)"
+        ));
+    }
+    let mode = match (label, &run.mode) {
+        ("blake3-keyed", Blake3Mode::Keyed(key)) => Blake3Mode::Keyed(*key),
+        ("blake3-keyed", _) => return Err("blake3-keyed records need --blake3-key-file".to_owned()),
+        ("blake3-derive", Blake3Mode::DeriveKey(context)) => Blake3Mode::DeriveKey(context.clone()),
+        ("blake3-derive", _) => {
+            return Err("blake3-derive records need --blake3-context".to_owned());
+        }
+        _ => Blake3Mode::Hash,
+    };
+    let blake3 = Blake3Options {
+        mode,
+        digest_length,
+    };
+    // A 32-byte `blake3-xof` digest would be written as plain `blake3`.
+    if blake3.label() != label {
+        return Err(format!(
+            "a {digest_length}-byte digest is not labelled {label}"
+        ));
+    }
+    Ok(blake3)
+}
+
+fn recorded_chunk_hashes(record: &Value) -> Option<(u64, Vec<&str>)> {
+    let chunk_size = record
+        .get("chunk_size")?
+        .as_u64()
+        .filter(|size| *size > 0)?;
+    let hashes = record
+        .get("chunk_hashes")?
+        .as_array()?
+        .iter()
+        .map(Value::as_str)
+        .collect::<Option<Vec<_>>>()?;
+    Some((chunk_size, hashes))
+}
+
+/// Indices of chunks that differ, including chunks present on only one side.
+fn changed_chunks(recorded: &[&str], computed: &[String]) -> Vec<usize> {
+    (0..recorded.len().max(computed.len()))
+        .filter(|&index| recorded.get(index).copied() != computed.get(index).map(String::as_str))
+        .collect()
+}
@@ -1,3 +1,6 @@
 pub mod reporter;
 
-pub use reporter::{ProgressEvent, WarningEvent, write_progress, write_warning};
+pub use reporter::{
+    CdcSummaryEvent, ProgressEvent, WarningEvent, write_cdc_summary, write_cdc_summary_line,
+    write_progress, write_warning,
+};
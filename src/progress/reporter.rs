@@ -51,6 +51,35 @@ impl WarningEvent {
     }
 }
 
+/// Manifest-wide dedup totals for `--cdc`, emitted once after the last record.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct CdcSummaryEvent {
+    #[serde(rename = "type")]
+    pub event_type: &'static str,
+    pub tool: &'static str,
+    pub files: u64,
+    pub chunks: u64,
+    pub unique_chunks: u64,
+    pub total_bytes: u64,
+    pub unique_bytes: u64,
+    pub shared_bytes: u64,
+}
+
+impl CdcSummaryEvent {
+    pub fn new(summary: &crate::hash::CdcSummary) -> Self {
+        Self {
+            event_type: "cdc_summary",
+            tool: "hash",
+            files: summary.files,
+            chunks: summary.chunks,
+            unique_chunks: summary.unique_chunks,
+            total_bytes: summary.total_bytes,
+            unique_bytes: summary.unique_bytes,
+            shared_bytes: summary.shared_bytes(),
+        }
+    }
+}
+
 pub fn write_progress<W: Write>(writer: &mut W, event: &ProgressEvent) -> io::Result<()> {
     serde_json::to_writer(&mut *writer, event).map_err(io::Error::other)?;
     writer.write_all(b"\n")
@@ -60,3 +89,23 @@ pub fn write_warning<W: Write>(writer: &mut W, event: &WarningEvent) -> io::Resu
     serde_json::to_writer(&mut *writer, event).map_err(io::Error::other)?;
     writer.write_all(b"\n")
 }
+
+pub fn write_cdc_summary<W: Write>(writer: &mut W, event: &CdcSummaryEvent) -> io::Result<()> {
+    serde_json::to_writer(&mut *writer, event).map_err(io::Error::other)?;
+    writer.write_all(b"\n")
+}
+
+/// Plain-stderr form of the summary, for runs without `--progress`:
+/// `hash: cdc_summary: files=N chunks=N ...`.
+pub fn write_cdc_summary_line<W: Write>(writer: &mut W, event: &CdcSummaryEvent) -> io::Result<()> {
+    writeln!(
+        writer,
+        "hash: cdc_summary: files={} chunks={} unique_chunks={} total_bytes={} unique_bytes={} shared_bytes={}",
+        event.files,
+        event.chunks,
+        event.unique_chunks,
+        event.total_bytes,
+        event.unique_bytes,
+        event.shared_bytes
+    )
+}
@@ -58,6 +58,22 @@ impl RefusalEnvelope {
         self
     }
 
+    /// Render the envelope as one JSON line, falling back to a minimal
+    /// internal-error envelope if serialization fails.
+    pub fn render(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|err| {
+            json!({
+                "code": "E_INTERNAL",
+                "message": "Failed to render refusal envelope",
+                "detail": {
+                    "error": err.to_string()
+                },
+                "next_command": null
+            })
+            .to_string()
+        })
+    }
+
     pub fn to_value(&self) -> serde_json::Result<Value> {
         serde_json::to_value(self)
     }
@@ -0,0 +1,135 @@
+use hashbytes::cli::{Algorithm, parse_algorithm_list};
+use hashbytes::hash::registry::{self, AlgorithmEntry};
+use hashbytes::hash::{ContentHasher, HashOptions, compute};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+fn unique_path(label: &str) -> PathBuf {
+    let counter = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!(
+        "hash-algorithm-registry-{}-{label}-{counter}",
+        std::process::id()
+    ))
+}
+
+/// Toy downstream algorithm: byte count and wrapping byte sum.
+struct ByteSum {
+    len: u64,
+    sum: u32,
+}
+
+impl ContentHasher for ByteSum {
+    fn update(&mut self, buf: &[u8]) {
+        self.len += buf.len() as u64;
+        for byte in buf {
+            self.sum = self.sum.wrapping_add(u32::from(*byte));
+        }
+    }
+
+    fn finalize(self: Box<Self>) -> String {
+        format!("{:016x}{:08x}", self.len, self.sum)
+    }
+}
+
+fn bytesum_entry(name: &'static str, prefix: &'static str) -> AlgorithmEntry {
+    AlgorithmEntry {
+        name,
+        prefix,
+        digest_hex_len: 24,
+        cryptographic: false,
+        multihash_code: None,
+        new_hasher: |_, _| Box::new(ByteSum { len: 0, sum: 0 }),
+        mode_label: None,
+    }
+}
+
+#[test]
+fn builtins_come_from_the_registry() {
+    let names: Vec<&str> = Algorithm::BUILTIN.iter().map(Algorithm::name).collect();
+    assert_eq!(
+        names,
+        [
+            "sha256",
+            "blake3",
+            "sha384",
+            "sha512",
+            "sha3-256",
+            "xxh3-128",
+            "git-sha1",
+            "git-sha256"
+        ]
+    );
+    assert_eq!(Algorithm::Xxh3_128.prefix(), "xxh3");
+    assert!(!Algorithm::Xxh3_128.is_cryptographic());
+    assert_eq!(
+        Algorithm::from_str("SHA3-256").expect("parse"),
+        Algorithm::Sha3_256
+    );
+}
+
+#[test]
+fn registered_algorithm_parses_and_hashes_in_the_shared_read_pass() {
+    let bytesum = registry::register(bytesum_entry("bytesum", "bytesum")).expect("register");
+    assert!(Algorithm::all().contains(&bytesum));
+    assert_eq!(Algorithm::from_str("ByteSum").expect("parse"), bytesum);
+    assert_eq!(
+        parse_algorithm_list("sha256,bytesum").expect("parse list"),
+        vec![Algorithm::Sha256, bytesum]
+    );
+
+    let path = unique_path("data");
+    std::fs::write(&path, b"\x01\x02\x03").expect("write data");
+    let digests =
+        compute::hash_file_with_options(&path, &HashOptions::new(vec![bytesum, Algorithm::Sha256]))
+            .expect("hash");
+    assert_eq!(digests.digests[0], "bytesum:000000000000000300000006");
+    assert!(digests.digests[1].starts_with("sha256:"));
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn digest_prefix_and_label_come_from_the_entry() {
+    let summed = registry::register(bytesum_entry("bytesum-short", "bsum")).expect("register");
+    let options = HashOptions::new(vec![summed]);
+    assert_eq!(options.algorithm_name(summed), "bytesum-short");
+    assert_eq!(options.digest_prefix(summed), "bsum");
+
+    let path = unique_path("prefix");
+    std::fs::write(&path, b"\x05").expect("write data");
+    let digests = compute::hash_file_with_options(&path, &options).expect("hash");
+    assert_eq!(digests.digests[0], "bsum:000000000000000100000005");
+
+    let keyed = HashOptions {
+        blake3: hashbytes::hash::blake3::Blake3Options {
+            mode: hashbytes::hash::blake3::Blake3Mode::Keyed([1; 32]),
+            ..Default::default()
+        },
+        ..HashOptions::new(vec![Algorithm::Blake3])
+    };
+    assert_eq!(keyed.algorithm_name(Algorithm::Blake3), "blake3-keyed");
+    assert_eq!(keyed.digest_prefix(Algorithm::Blake3), "blake3-keyed");
+    assert_eq!(options.digest_prefix(Algorithm::Xxh3_128), "xxh3");
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn registration_rejects_collisions_and_reserved_prefixes() {
+    for (name, prefix) in [
+        ("sha256", "other"),
+        ("other", "xxh3"),
+        ("blake3-keyed", "fine"),
+        ("fine", "blake3-xof"),
+        ("Upper", "upper"),
+        ("", "empty"),
+    ] {
+        assert!(
+            registry::register(bytesum_entry(name, prefix)).is_err(),
+            "{name}/{prefix} should be rejected"
+        );
+    }
+}
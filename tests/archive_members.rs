@@ -0,0 +1,210 @@
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+const CSV: &[u8] = b"id,amount\n1,10.00\n2,20.00\n";
+const README: &[u8] = b"quarterly bundle\n";
+
+fn unique_path(label: &str) -> PathBuf {
+    let counter = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!(
+        "hash-archive-members-{}-{label}-{counter}",
+        std::process::id()
+    ))
+}
+
+fn sha256_prefixed(data: &[u8]) -> String {
+    format!("sha256:{:x}", Sha256::digest(data))
+}
+
+fn zip_bytes() -> Vec<u8> {
+    use zip::write::SimpleFileOptions;
+
+    let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    writer
+        .add_directory("inner/", SimpleFileOptions::default())
+        .expect("add dir");
+    writer
+        .start_file(
+            "inner/file.csv",
+            SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated),
+        )
+        .expect("start csv");
+    writer.write_all(CSV).expect("write csv");
+    writer
+        .start_file(
+            "README",
+            SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored),
+        )
+        .expect("start readme");
+    writer.write_all(README).expect("write readme");
+    writer.finish().expect("finish zip").into_inner()
+}
+
+fn tar_bytes() -> Vec<u8> {
+    let mut builder = tar::Builder::new(Vec::new());
+    for (name, data) in [("./inner/file.csv", CSV), ("./README", README)] {
+        let mut header = tar::Header::new_ustar();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, name, data)
+            .expect("append member");
+    }
+    builder.into_inner().expect("finish tar")
+}
+
+fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(data).expect("gzip");
+    encoder.finish().expect("finish gzip")
+}
+
+fn write_manifest(path: &Path, paths: &[&Path]) {
+    let mut file = fs::File::create(path).expect("create manifest");
+    for entry in paths {
+        let record = json!({ "version": "vacuum.v0", "path": entry.to_string_lossy() });
+        writeln!(file, "{record}").expect("write manifest line");
+    }
+}
+
+fn run_hashbytes(args: &[&str], manifest: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_hashbytes"))
+        .arg("--no-witness")
+        .args(args)
+        .arg(manifest)
+        .output()
+        .expect("hashbytes should run")
+}
+
+fn parse_records(output: &Output) -> Vec<Value> {
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("json record"))
+        .collect()
+}
+
+#[test]
+fn members_follow_their_archive_in_manifest_order() {
+    let zip = unique_path("bundle.zip");
+    let tar = unique_path("bundle.tar");
+    let tgz = unique_path("bundle.tgz");
+    let plain = unique_path("plain.csv");
+    fs::write(&zip, zip_bytes()).expect("write zip");
+    fs::write(&tar, tar_bytes()).expect("write tar");
+    fs::write(&tgz, gzip(&tar_bytes())).expect("write tgz");
+    fs::write(&plain, CSV).expect("write plain");
+
+    let manifest = unique_path("manifest");
+    write_manifest(&manifest, &[&zip, &plain, &tar, &tgz]);
+    let output = run_hashbytes(&["--expand-archives", "--jobs", "4"], &manifest);
+    assert_eq!(output.status.code(), Some(0));
+    let records = parse_records(&output);
+    let paths: Vec<&str> = records
+        .iter()
+        .map(|record| record["path"].as_str().expect("path"))
+        .collect();
+    let zip_str = zip.to_string_lossy();
+    let tar_str = tar.to_string_lossy();
+    let tgz_str = tgz.to_string_lossy();
+    assert_eq!(
+        paths,
+        [
+            zip_str.to_string(),
+            format!("{zip_str}!/inner/file.csv"),
+            format!("{zip_str}!/README"),
+            plain.to_string_lossy().to_string(),
+            tar_str.to_string(),
+            format!("{tar_str}!/inner/file.csv"),
+            format!("{tar_str}!/README"),
+            tgz_str.to_string(),
+            format!("{tgz_str}!/inner/file.csv"),
+            format!("{tgz_str}!/README"),
+        ]
+    );
+
+    assert_eq!(records[0]["archive_format"], "zip");
+    assert_eq!(records[4]["archive_format"], "tar");
+    assert_eq!(records[7]["archive_format"], "tar+gzip");
+    assert_eq!(records[3].get("archive_format"), None);
+    for (archive, member, data) in [(0, 1, CSV), (0, 2, README), (4, 5, CSV), (7, 9, README)] {
+        assert_eq!(records[member]["version"], "hash.v0");
+        assert_eq!(records[member]["bytes_hash"], sha256_prefixed(data));
+        assert_eq!(records[member]["size"], data.len());
+        assert_eq!(
+            records[member]["parent_bytes_hash"],
+            records[archive]["bytes_hash"]
+        );
+    }
+    // The same member bytes hash alike whatever the container.
+    assert_eq!(records[1]["bytes_hash"], records[3]["bytes_hash"]);
+
+    let output = run_hashbytes(&[], &manifest);
+    assert_eq!(parse_records(&output).len(), 4);
+
+    for path in [zip, tar, tgz, plain, manifest] {
+        let _ = fs::remove_file(path);
+    }
+}
+
+#[test]
+fn truncated_archive_keeps_complete_members_and_warns() {
+    let tar = tar_bytes();
+    // Cut inside the second member's data block.
+    let cut = unique_path("cut.tar");
+    fs::write(&cut, &tar[..512 * 3 + 4]).expect("write cut tar");
+
+    let manifest = unique_path("manifest");
+    write_manifest(&manifest, &[&cut]);
+    let output = run_hashbytes(&["--expand-archives"], &manifest);
+    assert_eq!(output.status.code(), Some(0));
+    let records = parse_records(&output);
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].get("_skipped"), None);
+    assert_eq!(records[0]["_warnings"][0]["code"], "E_ARCHIVE_READ");
+    assert_eq!(records[1]["bytes_hash"], sha256_prefixed(CSV));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("archive:"));
+
+    let _ = fs::remove_file(&cut);
+    let _ = fs::remove_file(&manifest);
+}
+
+#[test]
+fn members_stay_out_of_the_cdc_summary() {
+    let tar = unique_path("cdc.tar");
+    let manifest = unique_path("cdc-manifest");
+    let tar_data = tar_bytes();
+    fs::write(&tar, &tar_data).expect("write tar");
+    write_manifest(&manifest, &[&tar]);
+
+    let output = run_hashbytes(&["--cdc", "--expand-archives"], &manifest);
+    assert_eq!(output.status.code(), Some(0));
+    let records = parse_records(&output);
+    assert_eq!(records.len(), 3);
+    assert!(
+        records[1..]
+            .iter()
+            .all(|member| member["cdc_chunks"].is_array())
+    );
+
+    let stderr = String::from_utf8(output.stderr).expect("stderr utf8");
+    let summary = stderr
+        .lines()
+        .find_map(|line| line.strip_prefix("hash: cdc_summary: "))
+        .expect("cdc summary line");
+    assert!(summary.starts_with("files=1 "), "{summary}");
+    assert!(
+        summary.contains(&format!(" total_bytes={} ", tar_data.len())),
+        "{summary}"
+    );
+
+    let _ = fs::remove_file(&tar);
+    let _ = fs::remove_file(&manifest);
+}
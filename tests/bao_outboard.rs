@@ -0,0 +1,274 @@
+use hashbytes::hash::bao::{self, SliceVerification};
+use serde_json::{Value, json};
+use std::fs;
+use std::io::{Cursor, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+fn unique_path(label: &str) -> PathBuf {
+    let counter = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!(
+        "hash-bao-outboard-{}-{label}-{counter}",
+        std::process::id()
+    ))
+}
+
+fn patterned(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i % 251) as u8).collect()
+}
+
+fn encode(data: &[u8]) -> (blake3::Hash, Vec<u8>) {
+    let mut outboard = Cursor::new(Vec::new());
+    let root = bao::encode_outboard(&mut Cursor::new(data), data.len() as u64, &mut outboard)
+        .expect("encode outboard");
+    (root, outboard.into_inner())
+}
+
+fn run_hashbytes(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_hashbytes"))
+        .arg("--no-witness")
+        .args(args)
+        .output()
+        .expect("hashbytes should run")
+}
+
+fn first_line(output: &Output) -> Value {
+    let stdout = String::from_utf8(output.stdout.clone()).expect("stdout utf8");
+    serde_json::from_str(stdout.lines().next().expect("one output line")).expect("json line")
+}
+
+#[test]
+fn outboard_root_matches_blake3_and_size_matches_tree() {
+    // 3 MiB + 1 crosses the in-memory subtree threshold, exercising seek-back.
+    for len in [0, 1, 1024, 1025, 4096, 100_000, 3 * 1024 * 1024 + 1] {
+        let data = patterned(len);
+        let (root, outboard) = encode(&data);
+        assert_eq!(root, blake3::hash(&data), "root for len {len}");
+        assert_eq!(
+            outboard.len() as u64,
+            bao::outboard_size(len as u64),
+            "size for len {len}"
+        );
+        assert_eq!(outboard[..8], (len as u64).to_le_bytes());
+    }
+}
+
+#[test]
+fn verify_slice_accepts_good_ranges_and_locates_corruption() {
+    let data = patterned(3 * 1024 * 1024 + 1);
+    let (root, outboard) = encode(&data);
+
+    for (offset, length) in [
+        (0, 1),
+        (1023, 2),
+        (1_500_000, 70_000),
+        (0, data.len() as u64),
+    ] {
+        let outcome = bao::verify_slice(
+            &mut Cursor::new(&data),
+            &mut Cursor::new(&outboard),
+            &root,
+            offset,
+            length,
+        )
+        .expect("verify");
+        assert_eq!(
+            outcome,
+            SliceVerification::Verified,
+            "range {offset}+{length}"
+        );
+    }
+
+    let mut corrupted = data.clone();
+    corrupted[2_000_000] ^= 0xff;
+    let corrupt_chunk = 2_000_000 / 1024 * 1024;
+
+    let untouched = bao::verify_slice(
+        &mut Cursor::new(&corrupted),
+        &mut Cursor::new(&outboard),
+        &root,
+        0,
+        1_000_000,
+    )
+    .expect("verify untouched range");
+    assert_eq!(untouched, SliceVerification::Verified);
+
+    let touched = bao::verify_slice(
+        &mut Cursor::new(&corrupted),
+        &mut Cursor::new(&outboard),
+        &root,
+        1_999_000,
+        4096,
+    )
+    .expect("verify corrupted range");
+    assert_eq!(
+        touched,
+        SliceVerification::Mismatch {
+            offset: corrupt_chunk,
+            length: 1024
+        }
+    );
+
+    let truncated = bao::verify_slice(
+        &mut Cursor::new(&data[..4096]),
+        &mut Cursor::new(&outboard),
+        &root,
+        0,
+        1,
+    )
+    .expect("verify truncated");
+    assert!(matches!(
+        truncated,
+        SliceVerification::LengthMismatch { file_len: 4096, .. }
+    ));
+}
+
+#[test]
+fn a_short_outboard_is_a_mismatch_and_read_errors_name_their_file() {
+    let data = patterned(200_000);
+    let (root, outboard) = encode(&data);
+    let bytes_hash = format!("blake3:{}", root.to_hex());
+
+    for short in [&outboard[..outboard.len() - 10], &outboard[..3]] {
+        let outcome = bao::verify_slice(
+            &mut Cursor::new(&data),
+            &mut Cursor::new(short),
+            &root,
+            0,
+            1,
+        )
+        .expect("verify short outboard");
+        assert_eq!(
+            outcome,
+            SliceVerification::OutboardSize {
+                outboard_bytes: short.len() as u64,
+                expected_bytes: outboard.len() as u64,
+            }
+        );
+    }
+
+    let data_path = unique_path("short-data");
+    let outboard_path = unique_path("short-obao");
+    fs::write(&data_path, &data).expect("write data");
+    fs::write(&outboard_path, &outboard[..outboard.len() - 64]).expect("write outboard");
+    let verify = |outboard_path: &Path| {
+        run_hashbytes(&[
+            "bao-verify",
+            data_path.to_str().expect("utf8"),
+            "--outboard",
+            outboard_path.to_str().expect("utf8"),
+            "--hash",
+            &bytes_hash,
+            "--offset",
+            "150000",
+        ])
+    };
+
+    let short = verify(&outboard_path);
+    assert_eq!(short.status.code(), Some(1));
+    let report = first_line(&short);
+    assert_eq!(report["verified"], false);
+    assert_eq!(report["mismatch"]["reason"], "outboard");
+    assert_eq!(report["mismatch"]["expected_bytes"], outboard.len());
+
+    let missing_outboard = unique_path("missing-obao");
+    let missing = verify(&missing_outboard);
+    assert_eq!(missing.status.code(), Some(2));
+    let refusal = first_line(&missing);
+    assert_eq!(refusal["refusal"]["code"], "E_IO");
+    assert_eq!(
+        refusal["refusal"]["detail"]["path"],
+        missing_outboard.to_string_lossy().as_ref()
+    );
+
+    let _ = fs::remove_file(&data_path);
+    let _ = fs::remove_file(&outboard_path);
+}
+
+fn write_manifest(path: &Path, data_path: &Path) {
+    let mut file = fs::File::create(path).expect("create manifest");
+    let record = json!({ "version": "vacuum.v0", "path": data_path.to_string_lossy() });
+    writeln!(file, "{record}").expect("write manifest line");
+}
+
+#[test]
+fn bao_outboard_flag_writes_outboard_that_bao_verify_accepts() {
+    let data = patterned(200_000);
+    let data_path = unique_path("data");
+    let manifest_path = unique_path("manifest");
+    let outboard_dir = unique_path("obao");
+    fs::write(&data_path, &data).expect("write data");
+    write_manifest(&manifest_path, &data_path);
+
+    let output = run_hashbytes(&[
+        "--algorithm",
+        "blake3,sha256",
+        "--bao-outboard",
+        outboard_dir.to_str().expect("utf8"),
+        manifest_path.to_str().expect("utf8"),
+    ]);
+    assert_eq!(output.status.code(), Some(0));
+    let record = first_line(&output);
+    let bytes_hash = format!("blake3:{}", blake3::hash(&data).to_hex());
+    assert_eq!(record["bytes_hash"], Value::String(bytes_hash.clone()));
+    let outboard_path = PathBuf::from(record["bao_outboard"].as_str().expect("outboard path"));
+    assert_eq!(
+        fs::metadata(&outboard_path).expect("outboard exists").len(),
+        bao::outboard_size(data.len() as u64)
+    );
+
+    let verify = |path: &Path, offset: &str| {
+        run_hashbytes(&[
+            "bao-verify",
+            path.to_str().expect("utf8"),
+            "--outboard",
+            outboard_path.to_str().expect("utf8"),
+            "--hash",
+            &bytes_hash,
+            "--offset",
+            offset,
+            "--length",
+            "5000",
+        ])
+    };
+
+    let ok = verify(&data_path, "100000");
+    assert_eq!(ok.status.code(), Some(0));
+    assert_eq!(first_line(&ok)["verified"], Value::Bool(true));
+
+    let mut corrupted = data.clone();
+    corrupted[101_000] ^= 1;
+    fs::write(&data_path, &corrupted).expect("corrupt data");
+    let bad = verify(&data_path, "100000");
+    assert_eq!(bad.status.code(), Some(1));
+    let report = first_line(&bad);
+    assert_eq!(report["verified"], Value::Bool(false));
+    assert_eq!(report["mismatch"]["offset"], json!(100_352));
+
+    let past_eof = verify(&data_path, "199000");
+    assert_eq!(past_eof.status.code(), Some(2));
+    assert_eq!(first_line(&past_eof)["refusal"]["code"], "E_BAD_INPUT");
+
+    let _ = fs::remove_file(&data_path);
+    let _ = fs::remove_file(&manifest_path);
+    let _ = fs::remove_dir_all(&outboard_dir);
+}
+
+#[test]
+fn bao_outboard_requires_plain_blake3() {
+    let outboard_dir = unique_path("unused");
+    let output = run_hashbytes(&[
+        "--algorithm",
+        "sha256",
+        "--bao-outboard",
+        outboard_dir.to_str().expect("utf8"),
+    ]);
+    assert_eq!(output.status.code(), Some(2));
+    let refusal = first_line(&output);
+    assert_eq!(refusal["refusal"]["code"], "E_BAD_INPUT");
+    assert_eq!(refusal["refusal"]["detail"]["flag"], "--bao-outboard");
+    assert!(!outboard_dir.exists());
+}
@@ -0,0 +1,215 @@
+use serde_json::{Value, json};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+fn unique_path(label: &str) -> PathBuf {
+    let counter = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!(
+        "hash-baseline-{}-{label}-{counter}",
+        std::process::id()
+    ))
+}
+
+fn run_hashbytes(args: &[&str], manifest: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_hashbytes"))
+        .arg("--no-witness")
+        .args(args)
+        .arg(manifest)
+        .output()
+        .expect("hashbytes should run")
+}
+
+fn parse_records(stdout: &[u8]) -> Vec<Value> {
+    String::from_utf8_lossy(stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("record json"))
+        .collect()
+}
+
+fn changes(records: &[Value]) -> Vec<(&str, Option<&str>)> {
+    records
+        .iter()
+        .map(|record| {
+            (
+                record["relative_path"].as_str().expect("relative_path"),
+                record.get("change").and_then(Value::as_str),
+            )
+        })
+        .collect()
+}
+
+fn write_listing(path: &Path, dir: &Path, names: &[&str]) {
+    let mut manifest = fs::File::create(path).expect("create manifest");
+    for name in names {
+        let record = json!({
+            "version": "vacuum.v0",
+            "path": dir.join(name).to_string_lossy(),
+            "relative_path": name
+        });
+        writeln!(manifest, "{record}").expect("write manifest");
+    }
+}
+
+#[test]
+fn records_are_marked_against_the_previous_run() {
+    let dir = unique_path("dir");
+    fs::create_dir(&dir).expect("create dir");
+    for name in ["same", "edited", "dropped"] {
+        fs::write(dir.join(name), name).expect("write data");
+    }
+    let listing_path = unique_path("listing");
+    write_listing(&listing_path, &dir, &["same", "edited", "dropped"]);
+    let output = run_hashbytes(&[], &listing_path);
+    let baseline_path = unique_path("previous");
+    fs::write(&baseline_path, &output.stdout).expect("write baseline");
+
+    fs::write(dir.join("edited"), "edited again").expect("edit data");
+    fs::write(dir.join("fresh"), "fresh").expect("write data");
+    write_listing(&listing_path, &dir, &["same", "edited", "fresh", "missing"]);
+    let baseline_arg = baseline_path.to_string_lossy().into_owned();
+
+    let output = run_hashbytes(&["--baseline", &baseline_arg, "--jobs", "4"], &listing_path);
+    assert_eq!(output.status.code(), Some(1));
+    let records = parse_records(&output.stdout);
+    assert_eq!(
+        changes(&records),
+        vec![
+            ("same", Some("unchanged")),
+            ("edited", Some("modified")),
+            ("fresh", Some("new")),
+            ("missing", None),
+            ("dropped", Some("removed")),
+        ]
+    );
+    assert_eq!(records[3]["_skipped"], true);
+    let previous = parse_records(&fs::read(&baseline_path).expect("read baseline"));
+    assert_eq!(records[4]["bytes_hash"], previous[2]["bytes_hash"]);
+
+    let output = run_hashbytes(
+        &["--baseline", &baseline_arg, "--changed-only"],
+        &listing_path,
+    );
+    assert_eq!(
+        changes(&parse_records(&output.stdout)),
+        vec![
+            ("edited", Some("modified")),
+            ("fresh", Some("new")),
+            ("missing", None),
+            ("dropped", Some("removed")),
+        ]
+    );
+
+    let output = run_hashbytes(&["--changed-only"], &listing_path);
+    assert_eq!(output.status.code(), Some(2));
+    let refusal: Value = serde_json::from_slice(&output.stdout).expect("refusal json");
+    assert_eq!(refusal["refusal"]["detail"]["flag"], "--changed-only");
+
+    let missing_arg = unique_path("absent").to_string_lossy().into_owned();
+    let output = run_hashbytes(&["--baseline", &missing_arg], &listing_path);
+    assert_eq!(output.status.code(), Some(2));
+    let refusal: Value = serde_json::from_slice(&output.stdout).expect("refusal json");
+    assert_eq!(refusal["refusal"]["code"], "E_IO");
+
+    let _ = fs::remove_dir_all(&dir);
+    for path in [&listing_path, &baseline_path] {
+        let _ = fs::remove_file(path);
+    }
+}
+
+#[test]
+fn digests_are_compared_under_one_algorithm() {
+    let dir = unique_path("algorithms");
+    fs::create_dir(&dir).expect("create dir");
+    fs::write(dir.join("same"), "same").expect("write data");
+    let listing_path = unique_path("algorithms-listing");
+    write_listing(&listing_path, &dir, &["same"]);
+    let baseline_path = unique_path("algorithms-previous");
+    let output = run_hashbytes(&["--algorithm", "sha256"], &listing_path);
+    fs::write(&baseline_path, &output.stdout).expect("write baseline");
+    let baseline_arg = baseline_path.to_string_lossy().into_owned();
+
+    // No shared algorithm: no change, and a warning instead of `modified`.
+    let output = run_hashbytes(
+        &["--algorithm", "blake3", "--baseline", &baseline_arg],
+        &listing_path,
+    );
+    assert_eq!(output.status.code(), Some(0));
+    let records = parse_records(&output.stdout);
+    assert_eq!(changes(&records), vec![("same", None)]);
+    let warning = &records[0]["_warnings"][0];
+    assert_eq!(warning["code"], "E_BASELINE_ALGORITHM");
+    assert_eq!(warning["detail"]["hash_algorithm"], "blake3");
+    assert_eq!(warning["detail"]["baseline_hash_algorithm"], "sha256");
+
+    // This run's `hashes` carries the baseline's algorithm.
+    let output = run_hashbytes(
+        &["--algorithm", "blake3,sha256", "--baseline", &baseline_arg],
+        &listing_path,
+    );
+    let records = parse_records(&output.stdout);
+    assert_eq!(changes(&records), vec![("same", Some("unchanged"))]);
+    assert_eq!(records[0].get("_warnings"), None);
+
+    // The baseline's `hashes` carries this run's algorithm.
+    let output = run_hashbytes(&["--algorithm", "sha256,blake3"], &listing_path);
+    fs::write(&baseline_path, &output.stdout).expect("write baseline");
+    fs::write(dir.join("same"), "edited").expect("edit data");
+    let output = run_hashbytes(
+        &["--algorithm", "blake3", "--baseline", &baseline_arg],
+        &listing_path,
+    );
+    assert_eq!(
+        changes(&parse_records(&output.stdout)),
+        vec![("same", Some("modified"))]
+    );
+
+    let _ = fs::remove_dir_all(&dir);
+    for path in [&listing_path, &baseline_path] {
+        let _ = fs::remove_file(path);
+    }
+}
+
+#[test]
+fn skipped_records_get_no_change_and_are_not_removed() {
+    let dir = unique_path("skipped");
+    fs::create_dir(&dir).expect("create dir");
+    for name in ["kept", "unreadable"] {
+        fs::write(dir.join(name), name).expect("write data");
+    }
+    let listing_path = unique_path("skipped-listing");
+    write_listing(&listing_path, &dir, &["kept", "unreadable"]);
+    let baseline_path = unique_path("skipped-previous");
+    let output = run_hashbytes(&[], &listing_path);
+    fs::write(&baseline_path, &output.stdout).expect("write baseline");
+    let baseline_arg = baseline_path.to_string_lossy().into_owned();
+
+    // Still listed, but a directory now: the record is skipped this run.
+    fs::remove_file(dir.join("unreadable")).expect("remove data");
+    fs::create_dir(dir.join("unreadable")).expect("create dir");
+    for args in [
+        vec!["--baseline", baseline_arg.as_str()],
+        vec!["--baseline", baseline_arg.as_str(), "--changed-only"],
+    ] {
+        let output = run_hashbytes(&args, &listing_path);
+        assert_eq!(output.status.code(), Some(1));
+        let records = parse_records(&output.stdout);
+        let expected = if args.len() == 2 {
+            vec![("kept", Some("unchanged")), ("unreadable", None)]
+        } else {
+            vec![("unreadable", None)]
+        };
+        assert_eq!(changes(&records), expected);
+        let skipped = records.last().expect("skipped record");
+        assert_eq!(skipped["_skipped"], true);
+    }
+
+    let _ = fs::remove_dir_all(&dir);
+    for path in [&listing_path, &baseline_path] {
+        let _ = fs::remove_file(path);
+    }
+}
@@ -0,0 +1,155 @@
+use hashbytes::hash::blake3::{Blake3Mode, Blake3Options, hash_file_with, parse_key};
+use serde_json::{Value, json};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+const KEY: [u8; 32] = *b"whats the Elvish word for friend";
+
+fn unique_path(label: &str) -> PathBuf {
+    let counter = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!(
+        "hash-blake3-modes-{}-{label}-{counter}",
+        std::process::id()
+    ))
+}
+
+fn write_manifest(path: &Path, data_path: &Path) {
+    let mut file = fs::File::create(path).expect("create manifest");
+    let record = json!({ "version": "vacuum.v0", "path": data_path.to_string_lossy() });
+    writeln!(file, "{record}").expect("write manifest line");
+}
+
+fn run_hashbytes(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_hashbytes"))
+        .arg("--no-witness")
+        .args(args)
+        .output()
+        .expect("hashbytes should run")
+}
+
+fn first_record(output: &Output) -> Value {
+    let stdout = String::from_utf8(output.stdout.clone()).expect("stdout utf8");
+    serde_json::from_str(stdout.lines().next().expect("one output line")).expect("json line")
+}
+
+#[test]
+fn keyed_derive_and_xof_modes_match_reference_blake3() {
+    let data_path = unique_path("data");
+    fs::write(&data_path, b"partner manifest payload").expect("write data");
+
+    let keyed = Blake3Options {
+        mode: Blake3Mode::Keyed(KEY),
+        ..Blake3Options::default()
+    };
+    assert_eq!(
+        hash_file_with(&data_path, &keyed).expect("keyed hash"),
+        format!(
+            "blake3-keyed:{}",
+            blake3::keyed_hash(&KEY, b"partner manifest payload").to_hex()
+        )
+    );
+
+    let derive = Blake3Options {
+        mode: Blake3Mode::DeriveKey("cmdrvl hash test context".to_owned()),
+        ..Blake3Options::default()
+    };
+    let expected_derive: String =
+        blake3::derive_key("cmdrvl hash test context", b"partner manifest payload")
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+    assert_eq!(
+        hash_file_with(&data_path, &derive).expect("derive hash"),
+        format!("blake3-derive:{expected_derive}")
+    );
+
+    let xof = Blake3Options {
+        digest_length: 64,
+        ..Blake3Options::default()
+    };
+    let xof_digest = hash_file_with(&data_path, &xof).expect("xof hash");
+    let xof_hex = xof_digest.strip_prefix("blake3-xof:").expect("xof prefix");
+    assert_eq!(xof_hex.len(), 128);
+    let plain = blake3::hash(b"partner manifest payload").to_hex();
+    assert!(xof_hex.starts_with(plain.as_str()));
+
+    let _ = fs::remove_file(data_path);
+}
+
+#[test]
+fn key_files_accept_raw_bytes_or_hex_only() {
+    assert_eq!(parse_key(&KEY).expect("raw key"), KEY);
+
+    let hex: String = KEY.iter().map(|byte| format!("{byte:02X}")).collect();
+    assert_eq!(
+        parse_key(format!("{hex}\n").as_bytes()).expect("hex key"),
+        KEY
+    );
+
+    assert!(parse_key(b"too short").is_err());
+    assert!(parse_key(&[0_u8; 31]).is_err());
+}
+
+#[test]
+fn binary_keyed_mode_uses_distinct_prefix_and_algorithm() {
+    let data_path = unique_path("keyed-data");
+    let key_path = unique_path("keyed-key");
+    let manifest_path = unique_path("keyed-manifest");
+    fs::write(&data_path, b"abc").expect("write data");
+    fs::write(&key_path, KEY).expect("write key");
+    write_manifest(&manifest_path, &data_path);
+
+    let output = run_hashbytes(&[
+        "--algorithm",
+        "blake3",
+        "--blake3-key-file",
+        key_path.to_str().expect("key path utf8"),
+        manifest_path.to_str().expect("manifest path utf8"),
+    ]);
+    assert_eq!(output.status.code(), Some(0));
+
+    let record = first_record(&output);
+    assert_eq!(record["hash_algorithm"], "blake3-keyed");
+    assert_eq!(
+        record["bytes_hash"],
+        format!("blake3-keyed:{}", blake3::keyed_hash(&KEY, b"abc").to_hex())
+    );
+
+    let _ = fs::remove_file(data_path);
+    let _ = fs::remove_file(key_path);
+    let _ = fs::remove_file(manifest_path);
+}
+
+#[test]
+fn binary_blake3_flags_without_blake3_are_refused() {
+    let data_path = unique_path("refused-data");
+    let manifest_path = unique_path("refused-manifest");
+    fs::write(&data_path, b"abc").expect("write data");
+    write_manifest(&manifest_path, &data_path);
+
+    let output = run_hashbytes(&[
+        "--blake3-context",
+        "some context",
+        manifest_path.to_str().expect("manifest path utf8"),
+    ]);
+    assert_eq!(output.status.code(), Some(2));
+    let refusal: Value = serde_json::from_slice(&output.stdout).expect("refusal json");
+    assert_eq!(refusal["refusal"]["code"], "E_BAD_INPUT");
+    assert_eq!(refusal["refusal"]["detail"]["flag"], "--blake3-context");
+
+    let out_of_range = run_hashbytes(&[
+        "--algorithm",
+        "blake3",
+        "--digest-length",
+        "8",
+        manifest_path.to_str().expect("manifest path utf8"),
+    ]);
+    assert_eq!(out_of_range.status.code(), Some(2));
+
+    let _ = fs::remove_file(data_path);
+    let _ = fs::remove_file(manifest_path);
+}
@@ -0,0 +1,188 @@
+use fastcdc::v2020::FastCDC;
+use hashbytes::cli::Algorithm;
+use hashbytes::hash::cdc::{AVG_CHUNK_LEN, MAX_CHUNK_LEN, MIN_CHUNK_LEN};
+use hashbytes::hash::{CdcChunk, CdcSummary, HashOptions, hash_file_with_options};
+use serde_json::{Value, json};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+fn unique_path(label: &str) -> PathBuf {
+    let counter = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!(
+        "hash-cdc-chunks-{}-{label}-{counter}",
+        std::process::id()
+    ))
+}
+
+/// Deterministic pseudo-random bytes so FastCDC finds varied cut points.
+fn noise(len: usize, seed: u64) -> Vec<u8> {
+    let mut state = seed;
+    (0..len)
+        .map(|_| {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            (state >> 56) as u8
+        })
+        .collect()
+}
+
+fn cdc_chunks(path: &Path) -> Vec<CdcChunk> {
+    let options = HashOptions {
+        cdc: true,
+        ..HashOptions::new(vec![Algorithm::Sha256])
+    };
+    hash_file_with_options(path, &options)
+        .expect("hash with cdc")
+        .cdc_chunks
+        .expect("cdc chunks")
+}
+
+#[test]
+fn streaming_chunker_matches_in_memory_fastcdc() {
+    let data = noise(1_500_000, 7);
+    let path = unique_path("data");
+    fs::write(&path, &data).expect("write data");
+
+    let expected: Vec<CdcChunk> = FastCDC::new(
+        &data,
+        MIN_CHUNK_LEN as u32,
+        AVG_CHUNK_LEN as u32,
+        MAX_CHUNK_LEN as u32,
+    )
+    .map(|chunk| CdcChunk {
+        offset: chunk.offset as u64,
+        length: chunk.length as u64,
+        blake3: format!(
+            "blake3:{}",
+            blake3::hash(&data[chunk.offset..chunk.offset + chunk.length]).to_hex()
+        ),
+    })
+    .collect();
+
+    assert!(expected.len() > 5);
+    assert_eq!(cdc_chunks(&path), expected);
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn empty_and_tiny_files_chunk_as_expected() {
+    let empty = unique_path("empty");
+    fs::write(&empty, b"").expect("write empty");
+    assert!(cdc_chunks(&empty).is_empty());
+
+    let tiny = unique_path("tiny");
+    fs::write(&tiny, b"abc").expect("write tiny");
+    assert_eq!(
+        cdc_chunks(&tiny),
+        vec![CdcChunk {
+            offset: 0,
+            length: 3,
+            blake3: format!("blake3:{}", blake3::hash(b"abc").to_hex()),
+        }]
+    );
+
+    let _ = fs::remove_file(&empty);
+    let _ = fs::remove_file(&tiny);
+}
+
+#[test]
+fn summary_counts_repeat_chunks_as_shared() {
+    let chunk = |label: &[u8], length| CdcChunk {
+        offset: 0,
+        length,
+        blake3: format!("blake3:{}", blake3::hash(label).to_hex()),
+    };
+    let mut summary = CdcSummary::default();
+    summary.add(&[chunk(b"a", 100), chunk(b"b", 50)]);
+    summary.add(&[chunk(b"a", 100), chunk(b"c", 25)]);
+
+    assert_eq!(summary.files, 2);
+    assert_eq!(summary.chunks, 4);
+    assert_eq!(summary.unique_chunks, 3);
+    assert_eq!(summary.total_bytes, 275);
+    assert_eq!(summary.unique_bytes, 175);
+    assert_eq!(summary.shared_bytes(), 100);
+}
+
+#[test]
+fn cdc_flag_emits_chunk_lists_and_stderr_summary() {
+    // The new version prepends a few bytes; content-defined cuts resync.
+    let old = noise(800_000, 11);
+    let mut new = b"header v2\n".to_vec();
+    new.extend_from_slice(&old);
+
+    let old_path = unique_path("old");
+    let new_path = unique_path("new");
+    let manifest_path = unique_path("manifest");
+    fs::write(&old_path, &old).expect("write old");
+    fs::write(&new_path, &new).expect("write new");
+    let mut manifest = fs::File::create(&manifest_path).expect("create manifest");
+    for path in [&old_path, &new_path] {
+        let record = json!({ "version": "vacuum.v0", "path": path.to_string_lossy() });
+        writeln!(manifest, "{record}").expect("write manifest line");
+    }
+    drop(manifest);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_hashbytes"))
+        .args(["--no-witness", "--cdc", "--progress", "--jobs", "2"])
+        .arg(&manifest_path)
+        .output()
+        .expect("hashbytes should run");
+    assert_eq!(output.status.code(), Some(0));
+
+    let stdout = String::from_utf8(output.stdout).expect("stdout utf8");
+    let records: Vec<Value> = stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("json record"))
+        .collect();
+    assert_eq!(records.len(), 2);
+    let total: u64 = records[1]["cdc_chunks"]
+        .as_array()
+        .expect("cdc_chunks")
+        .iter()
+        .map(|chunk| chunk["length"].as_u64().expect("length"))
+        .sum();
+    assert_eq!(total, new.len() as u64);
+
+    let stderr = String::from_utf8(output.stderr).expect("stderr utf8");
+    let summary: Value =
+        serde_json::from_str(stderr.lines().last().expect("summary line")).expect("summary json");
+    assert_eq!(summary["type"], "cdc_summary");
+    assert_eq!(summary["files"], 2);
+    assert_eq!(summary["total_bytes"], json!(old.len() + new.len()));
+    let shared = summary["shared_bytes"].as_u64().expect("shared_bytes");
+    assert!(
+        shared > old.len() as u64 / 2,
+        "most of the old version should be shared, got {shared}"
+    );
+
+    // Without --progress the same totals end stderr as one plain line.
+    let output = Command::new(env!("CARGO_BIN_EXE_hashbytes"))
+        .args(["--no-witness", "--cdc"])
+        .arg(&manifest_path)
+        .output()
+        .expect("hashbytes should run");
+    assert_eq!(output.status.code(), Some(0));
+    let stderr = String::from_utf8(output.stderr).expect("stderr utf8");
+    assert_eq!(
+        stderr,
+        format!(
+            "hash: cdc_summary: files=2 chunks={} unique_chunks={} total_bytes={} unique_bytes={} shared_bytes={shared}\n",
+            summary["chunks"],
+            summary["unique_chunks"],
+            summary["total_bytes"],
+            summary["unique_bytes"]
+        )
+    );
+
+    let _ = fs::remove_file(&old_path);
+    let _ = fs::remove_file(&new_path);
+    let _ = fs::remove_file(&manifest_path);
+}
@@ -0,0 +1,132 @@
+use serde_json::{Value, json};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread;
+
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+fn unique_path(label: &str) -> PathBuf {
+    let counter = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!(
+        "hash-changed-during-hash-{}-{label}-{counter}",
+        std::process::id()
+    ))
+}
+
+fn run_hashbytes(args: &[&str], manifest: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_hashbytes"))
+        .arg("--no-witness")
+        .args(args)
+        .arg(manifest)
+        .output()
+        .expect("hashbytes should run")
+}
+
+fn parse_records(stdout: &[u8]) -> Vec<Value> {
+    String::from_utf8_lossy(stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("record json"))
+        .collect()
+}
+
+/// Appends to `path` in a tight loop until dropped, so every read pass
+/// over it sees the size change.
+struct Appender {
+    stop: Arc<AtomicBool>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl Appender {
+    fn start(path: &Path) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let mut file = fs::OpenOptions::new()
+            .append(true)
+            .open(path)
+            .expect("open for append");
+        let thread = thread::spawn({
+            let stop = Arc::clone(&stop);
+            move || {
+                while !stop.load(Ordering::Relaxed) {
+                    file.write_all(b"x").expect("append");
+                }
+            }
+        });
+        Self {
+            stop,
+            thread: Some(thread),
+        }
+    }
+}
+
+impl Drop for Appender {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn assert_changed_warning(warning: &Value, path: &Path) {
+    assert_eq!(warning["tool"], "hash");
+    assert_eq!(warning["code"], "E_CHANGED_DURING_HASH");
+    assert_eq!(warning["detail"]["path"], path.to_string_lossy().as_ref());
+    assert_eq!(warning["detail"]["attempts"], 1);
+    assert!(
+        warning["detail"]["changed"]
+            .as_array()
+            .is_some_and(|changed| changed.contains(&json!("size")))
+    );
+}
+
+#[test]
+fn a_file_that_keeps_changing_is_skipped_with_its_attempts() {
+    let steady = unique_path("steady");
+    let growing = unique_path("growing");
+    fs::write(&steady, b"steady").expect("write data");
+    fs::write(&growing, vec![7u8; 8 * 1024 * 1024]).expect("write data");
+    let manifest_path = unique_path("manifest");
+    let mut manifest = fs::File::create(&manifest_path).expect("create manifest");
+    for path in [&steady, &growing] {
+        writeln!(
+            manifest,
+            "{}",
+            json!({ "version": "vacuum.v0", "path": path.to_string_lossy() })
+        )
+        .expect("write manifest");
+    }
+    drop(manifest);
+
+    // Hashed while still: the manifest `verify` re-checks below.
+    let output = run_hashbytes(&[], &manifest_path);
+    assert_eq!(output.status.code(), Some(0));
+    let hashed_path = unique_path("hashed");
+    fs::write(&hashed_path, &output.stdout).expect("write hashed manifest");
+
+    let appender = Appender::start(&growing);
+    let output = run_hashbytes(&["--change-retries", "0"], &manifest_path);
+    let verified = run_hashbytes(&["--change-retries", "0", "verify"], &hashed_path);
+    drop(appender);
+
+    assert_eq!(output.status.code(), Some(1));
+    let records = parse_records(&output.stdout);
+    assert_eq!(records[0].get("_skipped"), None);
+    assert_eq!(records[1]["_skipped"], true);
+    assert_eq!(records[1]["bytes_hash"], Value::Null);
+    assert_eq!(records[1]["hash_algorithm"], Value::Null);
+    assert_changed_warning(&records[1]["_warnings"][0], &growing);
+
+    assert_eq!(verified.status.code(), Some(1));
+    let records = parse_records(&verified.stdout);
+    assert_eq!(records[0]["verification"], "match");
+    assert_eq!(records[1]["verification"], "unreadable");
+    assert_changed_warning(&records[1]["_warnings"][0], &growing);
+
+    for path in [&steady, &growing, &manifest_path, &hashed_path] {
+        let _ = fs::remove_file(path);
+    }
+}
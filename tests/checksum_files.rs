@@ -0,0 +1,175 @@
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+fn unique_path(label: &str) -> PathBuf {
+    let counter = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!(
+        "hash-checksum-files-{}-{label}-{counter}",
+        std::process::id()
+    ))
+}
+
+/// Run in `dir` so checksum lines can name files relative to it.
+fn run_hashbytes(args: &[&str], dir: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_hashbytes"))
+        .current_dir(dir)
+        .arg("--no-witness")
+        .args(args)
+        .output()
+        .expect("hashbytes should run")
+}
+
+fn parse_records(stdout: &[u8]) -> Vec<Value> {
+    String::from_utf8_lossy(stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("record json"))
+        .collect()
+}
+
+fn verifications(records: &[Value]) -> Vec<&str> {
+    records
+        .iter()
+        .map(|record| record["verification"].as_str().expect("verification"))
+        .collect()
+}
+
+fn write_vacuum(dir: &Path, names: &[&str]) {
+    let lines: String = names
+        .iter()
+        .map(|name| {
+            format!(
+                "{}\n",
+                serde_json::json!({
+                    "version": "vacuum.v0",
+                    "path": dir.join(name).to_string_lossy(),
+                    "relative_path": name
+                })
+            )
+        })
+        .collect();
+    fs::write(dir.join("vacuum.jsonl"), lines).expect("write manifest");
+}
+
+#[test]
+fn emitted_sums_check_in_both_formats() {
+    let dir = unique_path("dir");
+    fs::create_dir(&dir).expect("create dir");
+    let names = ["a.txt", "with space.txt", "back\\slash"];
+    for name in names {
+        fs::write(dir.join(name), name.as_bytes()).expect("write data");
+    }
+    write_vacuum(&dir, &names);
+
+    let output = run_hashbytes(&["--emit-sums", "gnu", "vacuum.jsonl"], &dir);
+    assert_eq!(output.status.code(), Some(0));
+    let gnu = String::from_utf8(output.stdout).expect("utf8 sums");
+    let lines: Vec<&str> = gnu.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].ends_with("  a.txt"));
+    assert!(lines[2].starts_with('\\') && lines[2].ends_with("  back\\\\slash"));
+    fs::write(dir.join("SHA256SUMS"), &gnu).expect("write sums");
+
+    let output = run_hashbytes(
+        &[
+            "--algorithm",
+            "blake3",
+            "--emit-sums",
+            "bsd",
+            "vacuum.jsonl",
+        ],
+        &dir,
+    );
+    assert_eq!(output.status.code(), Some(0));
+    let bsd = String::from_utf8(output.stdout).expect("utf8 sums");
+    assert!(bsd.starts_with("BLAKE3 (a.txt) = "));
+    fs::write(dir.join("tagged.sums"), &bsd).expect("write sums");
+
+    for sums in ["SHA256SUMS", "tagged.sums"] {
+        let output = run_hashbytes(&["check", sums, "--jobs", "2"], &dir);
+        assert_eq!(output.status.code(), Some(0));
+        let records = parse_records(&output.stdout);
+        assert_eq!(verifications(&records), vec!["match"; 3]);
+        assert_eq!(records[2]["relative_path"], "back\\slash");
+        assert_eq!(
+            records[2]["path"],
+            dir.join("back\\slash").to_string_lossy().as_ref()
+        );
+    }
+
+    // Verified records write back out unchanged.
+    let output = run_hashbytes(&["--emit-sums", "gnu", "check", "SHA256SUMS"], &dir);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), gnu);
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn b3sum_lines_check_and_report_drift() {
+    let dir = unique_path("dir");
+    fs::create_dir(&dir).expect("create dir");
+    fs::write(dir.join("steady"), b"steady").expect("write data");
+    fs::write(dir.join("edited"), b"before").expect("write data");
+    write_vacuum(&dir, &["steady", "edited"]);
+    let output = run_hashbytes(
+        &[
+            "--algorithm",
+            "blake3",
+            "--emit-sums",
+            "gnu",
+            "vacuum.jsonl",
+        ],
+        &dir,
+    );
+    let mut sums = String::from_utf8(output.stdout).expect("utf8 sums");
+    // b3sum's own spelling for a name with a newline.
+    sums.push_str(&format!("\\{}  gone\\nfile\n", "0".repeat(64)));
+    fs::write(dir.join("B3SUMS"), &sums).expect("write sums");
+    fs::write(dir.join("edited"), b"after").expect("edit data");
+
+    let output = run_hashbytes(&["check", "B3SUMS"], &dir);
+    assert_eq!(output.status.code(), Some(1));
+    let records = parse_records(&output.stdout);
+    assert_eq!(
+        verifications(&records),
+        vec!["match", "mismatch", "missing"]
+    );
+    assert_eq!(records[0]["hash_algorithm"], "blake3");
+    assert_eq!(records[2]["relative_path"], "gone\nfile");
+
+    // The file name decides blake3; without it the length says sha256.
+    fs::rename(dir.join("B3SUMS"), dir.join("plain")).expect("rename sums");
+    let output = run_hashbytes(&["check", "plain"], &dir);
+    let records = parse_records(&output.stdout);
+    assert_eq!(records[0]["hash_algorithm"], "sha256");
+    let output = run_hashbytes(&["check", "plain", "--algorithm", "blake3"], &dir);
+    assert_eq!(verifications(&parse_records(&output.stdout))[0], "match");
+
+    fs::write(dir.join("bad"), "not a checksum line\n").expect("write sums");
+    let output = run_hashbytes(&["check", "bad"], &dir);
+    assert_eq!(output.status.code(), Some(2));
+    let refusal: Value = serde_json::from_slice(&output.stdout).expect("refusal json");
+    assert_eq!(refusal["refusal"]["code"], "E_BAD_INPUT");
+    assert_eq!(refusal["refusal"]["detail"]["line"], 1);
+
+    let output = run_hashbytes(
+        &[
+            "--algorithm",
+            "git-sha1",
+            "--emit-sums",
+            "gnu",
+            "vacuum.jsonl",
+        ],
+        &dir,
+    );
+    assert_eq!(output.status.code(), Some(2));
+    let refusal: Value = serde_json::from_slice(&output.stdout).expect("refusal json");
+    assert_eq!(refusal["refusal"]["detail"]["flag"], "--emit-sums");
+
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,156 @@
+use hashbytes::cli::Algorithm;
+use hashbytes::hash::chunks::chunk_root;
+use hashbytes::hash::{HashOptions, hash_file_with_options};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+fn unique_path(label: &str) -> PathBuf {
+    let counter = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!(
+        "hash-chunk-hashes-{}-{label}-{counter}",
+        std::process::id()
+    ))
+}
+
+fn patterned(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i % 253) as u8).collect()
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+fn chunk_options(algorithm: Algorithm, chunk_size: u64) -> HashOptions {
+    HashOptions {
+        chunk_size: Some(chunk_size),
+        ..HashOptions::new(vec![algorithm])
+    }
+}
+
+#[test]
+fn chunk_hashes_cover_each_slice_across_buffer_boundaries() {
+    // 100_000-byte chunks straddle the 64 KiB read buffer.
+    let data = patterned(250_001);
+    let path = unique_path("data");
+    fs::write(&path, &data).expect("write data");
+
+    let digests = hash_file_with_options(&path, &chunk_options(Algorithm::Sha256, 100_000))
+        .expect("hash with chunks");
+    let chunks = digests.chunks.expect("chunk digests");
+
+    let expected: Vec<String> = data
+        .chunks(100_000)
+        .map(|chunk| format!("sha256:{}", sha256_hex(chunk)))
+        .collect();
+    assert_eq!(chunks.chunk_size, 100_000);
+    assert_eq!(chunks.hashes, expected);
+    assert_eq!(
+        digests.digests,
+        vec![format!("sha256:{}", sha256_hex(&data))]
+    );
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn chunk_root_pairs_raw_digests_and_carries_odd_node() {
+    let leaves: Vec<Vec<u8>> = (0..3_u8).map(|i| Sha256::digest([i]).to_vec()).collect();
+    let hashes: Vec<String> = leaves
+        .iter()
+        .map(|leaf| format!("sha256:{}", hex_string(leaf)))
+        .collect();
+
+    let left = Sha256::digest([leaves[0].as_slice(), leaves[1].as_slice()].concat());
+    let root = Sha256::digest([left.as_slice(), leaves[2].as_slice()].concat());
+
+    let options = HashOptions::new(vec![Algorithm::Sha256]);
+    assert_eq!(
+        chunk_root(&hashes, &options),
+        format!("sha256:{}", hex_string(&root))
+    );
+    assert_eq!(chunk_root(&hashes[..1], &options), hashes[0]);
+}
+
+#[test]
+fn single_chunk_and_empty_files_have_root_equal_to_bytes_hash() {
+    for (label, data) in [("small", patterned(10)), ("empty", Vec::new())] {
+        let path = unique_path(label);
+        fs::write(&path, &data).expect("write data");
+
+        let digests = hash_file_with_options(&path, &chunk_options(Algorithm::Blake3, 1024))
+            .expect("hash with chunks");
+        let chunks = digests.chunks.expect("chunk digests");
+        assert_eq!(chunks.hashes, digests.digests);
+        assert_eq!(chunks.root, digests.digests[0]);
+
+        let _ = fs::remove_file(&path);
+    }
+}
+
+fn hex_string(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn run_hashbytes(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_hashbytes"))
+        .arg("--no-witness")
+        .args(args)
+        .output()
+        .expect("hashbytes should run")
+}
+
+fn write_manifest(path: &Path, data_path: &Path) {
+    let mut file = fs::File::create(path).expect("create manifest");
+    let record = json!({ "version": "vacuum.v0", "path": data_path.to_string_lossy() });
+    writeln!(file, "{record}").expect("write manifest line");
+}
+
+#[test]
+fn chunk_size_flag_adds_chunk_fields_and_rejects_bad_sizes() {
+    let data = patterned(3 * 1024 + 7);
+    let data_path = unique_path("cli-data");
+    let manifest_path = unique_path("cli-manifest");
+    fs::write(&data_path, &data).expect("write data");
+    write_manifest(&manifest_path, &data_path);
+
+    let output = run_hashbytes(&[
+        "--chunk-size",
+        "1KiB",
+        manifest_path.to_str().expect("utf8"),
+    ]);
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).expect("stdout utf8");
+    let record: Value = serde_json::from_str(stdout.trim()).expect("json record");
+    assert_eq!(record["chunk_size"], json!(1024));
+    let chunk_hashes = record["chunk_hashes"].as_array().expect("chunk_hashes");
+    assert_eq!(chunk_hashes.len(), 4);
+    assert_eq!(
+        chunk_hashes[3],
+        Value::String(format!("sha256:{}", sha256_hex(&data[3 * 1024..])))
+    );
+    assert!(
+        record["chunk_root"]
+            .as_str()
+            .is_some_and(|root| root.starts_with("sha256:"))
+    );
+
+    let refused = run_hashbytes(&[
+        "--chunk-size",
+        "64MB",
+        manifest_path.to_str().expect("utf8"),
+    ]);
+    assert_eq!(refused.status.code(), Some(2));
+    let refusal: Value = serde_json::from_slice(&refused.stdout).expect("refusal envelope json");
+    assert_eq!(refusal["refusal"]["code"], "E_BAD_INPUT");
+    assert_eq!(refusal["refusal"]["detail"]["flag"], "--chunk-size");
+
+    let _ = fs::remove_file(&data_path);
+    let _ = fs::remove_file(&manifest_path);
+}
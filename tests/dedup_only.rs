@@ -0,0 +1,218 @@
+use serde_json::{Value, json};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+const KIB: usize = 1024;
+
+fn unique_path(label: &str) -> PathBuf {
+    let counter = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!(
+        "hash-dedup-only-{}-{label}-{counter}",
+        std::process::id()
+    ))
+}
+
+fn run_hashbytes(args: &[&str], manifest: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_hashbytes"))
+        .arg("--no-witness")
+        .args(args)
+        .arg(manifest)
+        .output()
+        .expect("hashbytes should run")
+}
+
+fn parse_records(stdout: &[u8]) -> Vec<Value> {
+    String::from_utf8_lossy(stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("record json"))
+        .collect()
+}
+
+#[test]
+fn each_record_is_settled_by_the_cheapest_tier() {
+    let mut late_edit = vec![3u8; 80 * KIB];
+    late_edit[70 * KIB] = 4;
+    let files: Vec<(&str, Vec<u8>)> = vec![
+        ("unique-size", b"0123456789".to_vec()),
+        ("prefix-a", vec![1u8; 70 * KIB]),
+        ("prefix-b", vec![2u8; 70 * KIB]),
+        ("late-a", vec![3u8; 80 * KIB]),
+        ("late-b", late_edit),
+        ("copy-a", b"hello".to_vec()),
+        ("copy-b", b"hello".to_vec()),
+    ];
+
+    let manifest_path = unique_path("manifest");
+    let mut manifest = fs::File::create(&manifest_path).expect("create manifest");
+    let mut data_paths = Vec::new();
+    for (label, bytes) in &files {
+        let path = unique_path(label);
+        fs::write(&path, bytes).expect("write data");
+        writeln!(
+            manifest,
+            "{}",
+            json!({ "version": "vacuum.v0", "path": path.to_string_lossy(), "size": bytes.len() })
+        )
+        .expect("write manifest");
+        data_paths.push(path);
+    }
+    // Without an upstream size the record is sized by a stat of its path.
+    writeln!(
+        manifest,
+        "{}",
+        json!({ "version": "vacuum.v0", "path": data_paths[5].to_string_lossy() })
+    )
+    .expect("write manifest");
+    writeln!(
+        manifest,
+        "{}",
+        json!({ "version": "vacuum.v0", "path": "/missing", "size": 10, "_skipped": true })
+    )
+    .expect("write manifest");
+    drop(manifest);
+
+    let output = run_hashbytes(&["--dedup-only", "--jobs", "4"], &manifest_path);
+    assert_eq!(output.status.code(), Some(1));
+    let records = parse_records(&output.stdout);
+    assert_eq!(records.len(), 9);
+    for (record, path) in records.iter().zip(&data_paths) {
+        assert_eq!(record["path"], path.to_string_lossy().as_ref());
+    }
+
+    let tiers: Vec<Option<&str>> = records
+        .iter()
+        .map(|record| record.get("dedup_tier").and_then(Value::as_str))
+        .collect();
+    assert_eq!(
+        tiers,
+        [
+            Some("size"),
+            Some("prefix"),
+            Some("prefix"),
+            Some("full"),
+            Some("full"),
+            Some("full"),
+            Some("full"),
+            Some("full"),
+            None,
+        ]
+    );
+
+    assert_eq!(records[0]["bytes_hash"], Value::Null);
+    assert_eq!(records[0]["non_identity"], true);
+    assert_eq!(records[0].get("prefix_hash"), None);
+
+    for record in &records[1..3] {
+        assert_eq!(record["bytes_hash"], Value::Null);
+        assert_eq!(record["non_identity"], true);
+        assert!(
+            record["prefix_hash"]
+                .as_str()
+                .is_some_and(|digest| digest.starts_with("xxh3:"))
+        );
+    }
+
+    // Same first block, so only the full hash tells them apart.
+    assert_eq!(records[3]["prefix_hash"], records[4]["prefix_hash"]);
+    assert_ne!(records[3]["bytes_hash"], records[4]["bytes_hash"]);
+    assert_eq!(records[5]["bytes_hash"], records[6]["bytes_hash"]);
+    assert_eq!(records[5]["bytes_hash"], records[7]["bytes_hash"]);
+    for record in &records[3..8] {
+        assert_eq!(record.get("non_identity"), None);
+        assert_eq!(record["hash_algorithm"], "sha256");
+    }
+    assert_eq!(records[7]["prefix_hash"], records[5]["prefix_hash"]);
+    assert_eq!(records[8]["_skipped"], true);
+
+    let output = run_hashbytes(&["--dedup-only", "--cdc"], &manifest_path);
+    assert_eq!(output.status.code(), Some(2));
+    let refusal: Value = serde_json::from_slice(&output.stdout).expect("refusal json");
+    assert_eq!(refusal["refusal"]["code"], "E_BAD_INPUT");
+    assert_eq!(refusal["refusal"]["detail"]["flag"], "--dedup-only");
+
+    for path in &data_paths {
+        let _ = fs::remove_file(path);
+    }
+    let _ = fs::remove_file(&manifest_path);
+}
+
+fn write_manifest(path: &Path, records: &[Value]) {
+    let mut manifest = fs::File::create(path).expect("create manifest");
+    for record in records {
+        writeln!(manifest, "{record}").expect("write manifest");
+    }
+}
+
+#[test]
+fn unsized_records_are_grouped_with_sized_ones() {
+    let sized = unique_path("sized");
+    let unsized_copy = unique_path("unsized");
+    let other = unique_path("other");
+    fs::write(&sized, b"hello").expect("write data");
+    fs::write(&unsized_copy, b"hello").expect("write data");
+    fs::write(&other, b"0123456789").expect("write data");
+    let manifest_path = unique_path("mixed-manifest");
+    write_manifest(
+        &manifest_path,
+        &[
+            json!({ "version": "vacuum.v0", "path": sized.to_string_lossy(), "size": 5 }),
+            json!({ "version": "vacuum.v0", "path": unsized_copy.to_string_lossy() }),
+            json!({ "version": "vacuum.v0", "path": other.to_string_lossy(), "size": 10 }),
+        ],
+    );
+
+    let output = run_hashbytes(&["--dedup-only"], &manifest_path);
+    assert_eq!(output.status.code(), Some(0));
+    let records = parse_records(&output.stdout);
+    assert_eq!(records[0]["dedup_tier"], "full");
+    assert_eq!(records[1]["dedup_tier"], "full");
+    assert!(records[0]["bytes_hash"].is_string());
+    assert_eq!(records[0]["bytes_hash"], records[1]["bytes_hash"]);
+    assert_eq!(records[2]["dedup_tier"], "size");
+
+    for path in [&sized, &unsized_copy, &other, &manifest_path] {
+        let _ = fs::remove_file(path);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn a_record_of_unknown_size_blocks_the_size_screen() {
+    let sized = unique_path("fifo-peer");
+    let fifo = unique_path("fifo");
+    fs::write(&sized, b"hello").expect("write data");
+    let status = Command::new("mkfifo")
+        .arg(&fifo)
+        .status()
+        .expect("mkfifo should run");
+    assert!(status.success());
+    let writer = {
+        let fifo = fifo.clone();
+        std::thread::spawn(move || fs::write(&fifo, b"hello").expect("write fifo"))
+    };
+    let manifest_path = unique_path("fifo-manifest");
+    write_manifest(
+        &manifest_path,
+        &[
+            json!({ "version": "vacuum.v0", "path": sized.to_string_lossy(), "size": 5 }),
+            json!({ "version": "vacuum.v0", "path": fifo.to_string_lossy() }),
+        ],
+    );
+
+    let output = run_hashbytes(&["--dedup-only"], &manifest_path);
+    assert_eq!(output.status.code(), Some(0));
+    writer.join().expect("fifo writer");
+    let records = parse_records(&output.stdout);
+    assert_eq!(records[0]["dedup_tier"], "full");
+    assert_eq!(records[1]["dedup_tier"], "full");
+    assert_eq!(records[0]["bytes_hash"], records[1]["bytes_hash"]);
+
+    for path in [&sized, &fifo, &manifest_path] {
+        let _ = fs::remove_file(path);
+    }
+}
@@ -0,0 +1,128 @@
+use hashbytes::cli::Algorithm;
+use hashbytes::hash::{DigestEncoding, HashOptions};
+use serde_json::{Value, json};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+const ABC_SHA256: &str = "sha256:ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+fn unique_path(label: &str) -> PathBuf {
+    let counter = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!(
+        "hash-digest-encoding-{}-{label}-{counter}",
+        std::process::id()
+    ))
+}
+
+fn run_hashbytes(args: &[&str], manifest: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_hashbytes"))
+        .arg("--no-witness")
+        .args(args)
+        .arg(manifest)
+        .output()
+        .expect("hashbytes should run")
+}
+
+#[test]
+fn sha256_digest_spellings() {
+    let options = HashOptions::new(vec![Algorithm::Sha256]);
+    let encode = |name: &str| {
+        DigestEncoding::from_str(name)
+            .expect("encoding")
+            .encode(ABC_SHA256, &options)
+            .expect("encodable")
+    };
+
+    assert_eq!(encode("hex"), ABC_SHA256);
+    assert_eq!(
+        encode("SRI"),
+        "sha256-ungWv48Bz+pBQUDeXa4iI7ADYaOWF3qctBD/YfIAFa0="
+    );
+    assert_eq!(
+        encode("multihash"),
+        "1220ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+    assert_eq!(
+        encode("multibase-base32"),
+        "bciqlu6awx6hqdt7kifaubxs5vyrchmadmgrzmf32ts2bb73b6iablli"
+    );
+    assert!(DigestEncoding::from_str("base58").is_err());
+}
+
+#[test]
+fn encodings_are_checked_against_the_primary_algorithm() {
+    let blake3 = HashOptions::new(vec![Algorithm::Blake3]);
+    assert!(DigestEncoding::Sri.check(&blake3).is_err());
+    assert!(DigestEncoding::Multihash.check(&blake3).is_ok());
+    let digest = format!("blake3:{}", "ab".repeat(32));
+    let multihash = DigestEncoding::Multihash
+        .encode(&digest, &blake3)
+        .expect("blake3 multihash");
+    assert_eq!(multihash, format!("1e20{}", "ab".repeat(32)));
+
+    let mut keyed = blake3.clone();
+    keyed.blake3.mode = hashbytes::hash::blake3::Blake3Mode::Keyed([7; 32]);
+    assert!(DigestEncoding::Multihash.check(&keyed).is_err());
+
+    let xxh3 = HashOptions::new(vec![Algorithm::Xxh3_128]);
+    assert!(DigestEncoding::MultibaseBase32.check(&xxh3).is_err());
+    assert!(DigestEncoding::Hex.check(&xxh3).is_err());
+    assert!(DigestEncoding::Hex.check(&blake3).is_err());
+    assert!(
+        DigestEncoding::Hex
+            .check(&HashOptions::new(vec![Algorithm::Sha512]))
+            .is_ok()
+    );
+}
+
+#[test]
+fn encoded_field_sits_beside_canonical_bytes_hash() {
+    let data_path = unique_path("data");
+    let manifest_path = unique_path("manifest");
+    fs::write(&data_path, b"abc").expect("write data");
+    let mut manifest = fs::File::create(&manifest_path).expect("create manifest");
+    writeln!(
+        manifest,
+        "{}",
+        json!({ "version": "vacuum.v0", "path": data_path.to_string_lossy() })
+    )
+    .expect("write manifest");
+    writeln!(
+        manifest,
+        "{}",
+        json!({ "version": "vacuum.v0", "path": "/nonexistent/hash-encoding" })
+    )
+    .expect("write manifest");
+
+    let output = run_hashbytes(&["--encoding", "sri"], &manifest_path);
+    assert_eq!(output.status.code(), Some(1));
+    let records: Vec<Value> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("json record"))
+        .collect();
+    assert_eq!(records[0]["bytes_hash"], ABC_SHA256);
+    assert_eq!(
+        records[0]["bytes_hash_encoded"],
+        "sha256-ungWv48Bz+pBQUDeXa4iI7ADYaOWF3qctBD/YfIAFa0="
+    );
+    assert_eq!(records[1]["_skipped"], true);
+    assert_eq!(records[1].get("bytes_hash_encoded"), None);
+
+    let output = run_hashbytes(
+        &["--algorithm", "blake3", "--encoding", "sri"],
+        &manifest_path,
+    );
+    assert_eq!(output.status.code(), Some(2));
+    let refusal: Value = serde_json::from_slice(&output.stdout).expect("refusal json");
+    assert_eq!(refusal["refusal"]["code"], "E_BAD_INPUT");
+    assert_eq!(refusal["refusal"]["detail"]["flag"], "--encoding");
+
+    let _ = fs::remove_file(&data_path);
+    let _ = fs::remove_file(&manifest_path);
+}
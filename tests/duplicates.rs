@@ -0,0 +1,210 @@
+use serde_json::{Value, json};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+fn unique_path(label: &str) -> PathBuf {
+    let counter = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!(
+        "hash-duplicates-{}-{label}-{counter}",
+        std::process::id()
+    ))
+}
+
+fn run_hashbytes(args: &[&str], manifest: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_hashbytes"))
+        .arg("--no-witness")
+        .args(args)
+        .arg(manifest)
+        .output()
+        .expect("hashbytes should run")
+}
+
+fn parse_records(stdout: &[u8]) -> Vec<Value> {
+    String::from_utf8_lossy(stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("record json"))
+        .collect()
+}
+
+#[test]
+fn repeated_content_is_annotated_and_reported() {
+    let files: [(&str, &[u8]); 5] = [
+        ("unique", b"only once"),
+        ("copy-a", b"same bytes"),
+        ("pair-a", b"xy"),
+        ("copy-b", b"same bytes"),
+        ("pair-b", b"xy"),
+    ];
+    let manifest_path = unique_path("manifest");
+    let mut manifest = fs::File::create(&manifest_path).expect("create manifest");
+    let mut data_paths = Vec::new();
+    for (label, bytes) in files {
+        let path = unique_path(label);
+        fs::write(&path, bytes).expect("write data");
+        writeln!(
+            manifest,
+            "{}",
+            json!({ "version": "vacuum.v0", "path": path.to_string_lossy(), "size": bytes.len() })
+        )
+        .expect("write manifest");
+        data_paths.push(path);
+    }
+    let copy_c = unique_path("copy-c");
+    fs::write(&copy_c, b"same bytes").expect("write data");
+    writeln!(
+        manifest,
+        "{}",
+        json!({ "version": "vacuum.v0", "path": copy_c.to_string_lossy(), "size": 10 })
+    )
+    .expect("write manifest");
+    data_paths.push(copy_c);
+    drop(manifest);
+
+    let output = run_hashbytes(&["--annotate-duplicates", "--jobs", "4"], &manifest_path);
+    assert_eq!(output.status.code(), Some(0));
+    let records = parse_records(&output.stdout);
+    assert_eq!(records.len(), 6);
+    for (record, path) in records.iter().zip(&data_paths) {
+        assert_eq!(record["path"], path.to_string_lossy().as_ref());
+    }
+
+    assert_eq!(records[0].get("duplicate_group"), None);
+    for index in [1, 3, 5] {
+        assert_eq!(records[index]["duplicate_group"], records[1]["bytes_hash"]);
+        assert_eq!(records[index]["duplicate_count"], 3);
+        assert_eq!(records[index]["first_path"], records[1]["path"]);
+    }
+    for index in [2, 4] {
+        assert_eq!(records[index]["duplicate_group"], records[2]["bytes_hash"]);
+        assert_eq!(records[index]["duplicate_count"], 2);
+        assert_eq!(records[index]["first_path"], records[2]["path"]);
+    }
+
+    let output = run_hashbytes(&[], &manifest_path);
+    assert!(
+        parse_records(&output.stdout)
+            .iter()
+            .all(|record| record.get("duplicate_group").is_none())
+    );
+
+    let hashed_path = unique_path("hashed");
+    fs::write(&hashed_path, &output.stdout).expect("write hashed manifest");
+    let output = Command::new(env!("CARGO_BIN_EXE_hashbytes"))
+        .arg("duplicates")
+        .arg(&hashed_path)
+        .output()
+        .expect("hashbytes should run");
+    assert_eq!(output.status.code(), Some(0));
+    let report: Value = serde_json::from_slice(&output.stdout).expect("report json");
+    assert_eq!(report["records"], 6);
+    assert_eq!(report["duplicate_groups"], 2);
+    assert_eq!(report["duplicate_records"], 5);
+    assert_eq!(report["reclaimable_bytes"], 2 * 10 + 2);
+    assert_eq!(report["groups"][0]["bytes_hash"], records[1]["bytes_hash"]);
+    assert_eq!(report["groups"][0]["count"], 3);
+    assert_eq!(report["groups"][0]["size"], 10);
+    assert_eq!(report["groups"][0]["reclaimable_bytes"], 20);
+    assert_eq!(
+        report["groups"][0]["paths"],
+        json!([records[1]["path"], records[3]["path"], records[5]["path"]])
+    );
+    assert_eq!(report["groups"][1]["paths"][0], records[2]["path"]);
+
+    let missing = unique_path("missing");
+    let output = Command::new(env!("CARGO_BIN_EXE_hashbytes"))
+        .arg("duplicates")
+        .arg(&missing)
+        .output()
+        .expect("hashbytes should run");
+    assert_eq!(output.status.code(), Some(2));
+    let refusal: Value = serde_json::from_slice(&output.stdout).expect("refusal json");
+    assert_eq!(refusal["refusal"]["code"], "E_IO");
+
+    for path in data_paths.iter().chain([&manifest_path, &hashed_path]) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+#[test]
+fn archive_members_ranged_and_non_cryptographic_records_never_group() {
+    let digest = format!("sha256:{}", "ab".repeat(32));
+    let xxh3 = format!("xxh3:{}", "cd".repeat(16));
+    let manifest_path = unique_path("members");
+    let mut manifest = fs::File::create(&manifest_path).expect("create manifest");
+    for record in [
+        json!({ "version": "hash.v0", "path": "/data/a.bin", "size": 10, "bytes_hash": digest }),
+        json!({ "version": "hash.v0", "path": "/data/b.bin", "size": 10, "bytes_hash": digest }),
+        json!({
+            "version": "hash.v0",
+            "path": "/data/bundle.zip!/a.bin",
+            "size": 10,
+            "bytes_hash": digest,
+            "parent_bytes_hash": format!("sha256:{}", "ef".repeat(32)),
+        }),
+        // A plain file whose path happens to contain "!/" still groups.
+        json!({ "version": "hash.v0", "path": "/data/c!/a.bin", "size": 10, "bytes_hash": digest }),
+        json!({
+            "version": "hash.v0",
+            "path": "/data/d.bin",
+            "size": 30,
+            "bytes_hash": digest,
+            "hash_offset": 20,
+            "hash_length": 10,
+        }),
+        json!({ "version": "hash.v0", "path": "/data/x", "size": 4, "bytes_hash": xxh3, "non_cryptographic": true }),
+        json!({ "version": "hash.v0", "path": "/data/y", "size": 4, "bytes_hash": xxh3, "non_cryptographic": true }),
+    ] {
+        writeln!(manifest, "{record}").expect("write manifest");
+    }
+    drop(manifest);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_hashbytes"))
+        .arg("duplicates")
+        .arg(&manifest_path)
+        .output()
+        .expect("hashbytes should run");
+    assert_eq!(output.status.code(), Some(0));
+    let report: Value = serde_json::from_slice(&output.stdout).expect("report json");
+    assert_eq!(report["records"], 7);
+    assert_eq!(report["duplicate_groups"], 1);
+    assert_eq!(report["duplicate_records"], 3);
+    assert_eq!(report["reclaimable_bytes"], 20);
+    assert_eq!(
+        report["groups"][0]["paths"],
+        json!(["/data/a.bin", "/data/b.bin", "/data/c!/a.bin"])
+    );
+
+    let data_paths = [unique_path("xxh3-a"), unique_path("xxh3-b")];
+    let mut manifest = fs::File::create(&manifest_path).expect("create manifest");
+    for path in &data_paths {
+        fs::write(path, b"same bytes").expect("write data");
+        writeln!(
+            manifest,
+            "{}",
+            json!({ "version": "vacuum.v0", "path": path.to_string_lossy(), "size": 10 })
+        )
+        .expect("write manifest");
+    }
+    drop(manifest);
+    let output = run_hashbytes(
+        &["--annotate-duplicates", "--algorithm", "xxh3-128"],
+        &manifest_path,
+    );
+    assert_eq!(output.status.code(), Some(0));
+    let records = parse_records(&output.stdout);
+    assert_eq!(records[0]["bytes_hash"], records[1]["bytes_hash"]);
+    assert!(
+        records
+            .iter()
+            .all(|record| record.get("duplicate_group").is_none())
+    );
+
+    for path in data_paths.iter().chain([&manifest_path]) {
+        let _ = fs::remove_file(path);
+    }
+}
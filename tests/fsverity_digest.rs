@@ -0,0 +1,112 @@
+use hashbytes::cli::Algorithm;
+use hashbytes::hash::{
+    ByteRange, HashOptions, hash_file_range_with_options, hash_file_with_options,
+};
+use serde_json::{Value, json};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+fn unique_path(label: &str) -> PathBuf {
+    let counter = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!(
+        "hash-fsverity-{}-{label}-{counter}",
+        std::process::id()
+    ))
+}
+
+fn fsverity_options() -> HashOptions {
+    let mut options = HashOptions::new(vec![Algorithm::Sha256]);
+    options.fsverity = true;
+    options
+}
+
+fn fsverity_digest(data: &[u8]) -> String {
+    let path = unique_path("data");
+    fs::write(&path, data).expect("write data");
+    let file_digests = hash_file_with_options(&path, &fsverity_options()).expect("hash");
+    let _ = fs::remove_file(&path);
+    file_digests.fsverity_digest.expect("fsverity digest")
+}
+
+#[test]
+fn digests_match_fsverity_utils() {
+    // `fsverity digest` of an empty file: all-zero root hash.
+    assert_eq!(
+        fsverity_digest(b""),
+        "sha256:3d248ca542a24fc62d1c43b916eae5016878e2533c88238480b26128a1f1af95"
+    );
+    // One partial data block: the root is that block's hash.
+    assert_eq!(
+        fsverity_digest(b"hello world"),
+        "sha256:1e2eaa4202d750a41174ee454970b92c1bc2f925b1e35076d8c7d5f56362ba64"
+    );
+    // 131 data blocks: two level-0 tree blocks under a level-1 root block.
+    let data: Vec<u8> = (0..4096 * 130 + 5).map(|i| (i % 251) as u8).collect();
+    assert_eq!(
+        fsverity_digest(&data),
+        "sha256:fa0a2a824dd5b544a7efc08bdaac0280c884536619e058f4945f6b4e387f5db3"
+    );
+}
+
+#[test]
+fn range_digest_covers_only_the_range() {
+    let path = unique_path("ranged");
+    let mut data = b"HEADER--".to_vec();
+    data.extend_from_slice(b"hello world");
+    fs::write(&path, &data).expect("write data");
+
+    let range = ByteRange {
+        offset: 8,
+        length: None,
+    };
+    let file_digests =
+        hash_file_range_with_options(&path, range, &fsverity_options()).expect("hash range");
+    assert_eq!(
+        file_digests.fsverity_digest.as_deref(),
+        Some("sha256:1e2eaa4202d750a41174ee454970b92c1bc2f925b1e35076d8c7d5f56362ba64")
+    );
+    let _ = fs::remove_file(&path);
+}
+
+fn run_hashbytes(args: &[&str], manifest: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_hashbytes"))
+        .arg("--no-witness")
+        .args(args)
+        .arg(manifest)
+        .output()
+        .expect("hashbytes should run")
+}
+
+#[test]
+fn fsverity_digest_field_is_emitted_on_request() {
+    let data_path = unique_path("data");
+    let manifest_path = unique_path("manifest");
+    fs::write(&data_path, b"").expect("write data");
+    let mut manifest = fs::File::create(&manifest_path).expect("create manifest");
+    writeln!(
+        manifest,
+        "{}",
+        json!({ "version": "vacuum.v0", "path": data_path.to_string_lossy() })
+    )
+    .expect("write manifest");
+
+    let output = run_hashbytes(&["--fsverity", "--algorithm", "blake3"], &manifest_path);
+    assert_eq!(output.status.code(), Some(0));
+    let record: Value = serde_json::from_slice(&output.stdout).expect("record json");
+    assert_eq!(
+        record["fsverity_digest"],
+        "sha256:3d248ca542a24fc62d1c43b916eae5016878e2533c88238480b26128a1f1af95"
+    );
+
+    let output = run_hashbytes(&[], &manifest_path);
+    let record: Value = serde_json::from_slice(&output.stdout).expect("record json");
+    assert_eq!(record.get("fsverity_digest"), None);
+
+    let _ = fs::remove_file(&data_path);
+    let _ = fs::remove_file(&manifest_path);
+}
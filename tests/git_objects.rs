@@ -0,0 +1,180 @@
+use hashbytes::cli::Algorithm;
+use hashbytes::hash::{HashOptions, hash_file, hash_file_with_options};
+use serde_json::{Value, json};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+fn unique_path(label: &str) -> PathBuf {
+    let counter = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!(
+        "hash-git-objects-{}-{label}-{counter}",
+        std::process::id()
+    ))
+}
+
+fn write_manifest(path: &Path, paths: &[&Path]) {
+    let mut file = fs::File::create(path).expect("create manifest");
+    for entry in paths {
+        let record = json!({ "version": "vacuum.v0", "path": entry.to_string_lossy() });
+        writeln!(file, "{record}").expect("write manifest line");
+    }
+}
+
+fn run_hashbytes(args: &[&str], manifest: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_hashbytes"))
+        .arg("--no-witness")
+        .args(args)
+        .arg(manifest)
+        .output()
+        .expect("hashbytes should run")
+}
+
+fn records(output: &Output) -> Vec<Value> {
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("json record"))
+        .collect()
+}
+
+#[test]
+fn git_blob_ids_match_git_hash_object() {
+    let empty = unique_path("empty");
+    let hello = unique_path("hello");
+    fs::write(&empty, b"").expect("write empty");
+    fs::write(&hello, b"hello\n").expect("write hello");
+
+    assert_eq!(
+        hash_file(&empty, Algorithm::GitSha1).expect("hash"),
+        "gitsha1:e69de29bb2d1d6434b8b29ae775ad8c2e48c5391"
+    );
+    assert_eq!(
+        hash_file(&hello, Algorithm::GitSha1).expect("hash"),
+        "gitsha1:ce013625030ba8dba906f756967f9e9ca394464a"
+    );
+    assert_eq!(
+        hash_file(&hello, Algorithm::GitSha256).expect("hash"),
+        "gitsha256:2cf8d83d9ee29543b34a87727421fdecb7e3f3a183d337639025de576db9ebb4"
+    );
+
+    // Chunk digests are git blobs of each chunk, so the header carries the
+    // chunk's own length.
+    let mut options = HashOptions::new(vec![Algorithm::GitSha1]);
+    options.chunk_size = Some(3);
+    let digests = hash_file_with_options(&hello, &options).expect("hash chunks");
+    let chunks = digests.chunks.expect("chunks");
+    assert_eq!(chunks.hashes.len(), 2);
+    let last_chunk = unique_path("lo");
+    fs::write(&last_chunk, b"lo\n").expect("write chunk");
+    assert_eq!(
+        chunks.hashes[1],
+        hash_file(&last_chunk, Algorithm::GitSha1).expect("hash")
+    );
+
+    for path in [empty, hello, last_chunk] {
+        let _ = fs::remove_file(path);
+    }
+}
+
+fn build_checkout(root: &Path) {
+    fs::create_dir_all(root.join("sub")).expect("create dirs");
+    fs::write(root.join("a.txt"), b"hello\n").expect("write a.txt");
+    fs::write(root.join("sub/b.txt"), b"").expect("write sub/b.txt");
+    fs::write(root.join("sub.txt"), b"x").expect("write sub.txt");
+    fs::write(root.join("run.sh"), b"#!/bin/sh\n").expect("write run.sh");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(root.join("run.sh"), fs::Permissions::from_mode(0o755))
+            .expect("chmod run.sh");
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn git_tree_ids_for_directory_records_match_git_write_tree() {
+    let root = unique_path("checkout");
+    build_checkout(&root);
+    let manifest = unique_path("manifest");
+    // Directories come before their files to exercise deferred output.
+    write_manifest(
+        &manifest,
+        &[
+            &root,
+            &root.join("sub"),
+            &root.join("a.txt"),
+            &root.join("run.sh"),
+            &root.join("sub.txt"),
+            &root.join("sub/b.txt"),
+        ],
+    );
+
+    let output = run_hashbytes(&["--algorithm", "git-sha1", "--git-tree"], &manifest);
+    assert_eq!(output.status.code(), Some(0));
+    let sha1_records = records(&output);
+    assert_eq!(sha1_records.len(), 6);
+    assert_eq!(sha1_records[0]["path"], root.to_string_lossy().as_ref());
+    assert_eq!(
+        sha1_records[0]["bytes_hash"],
+        "gitsha1:fe7573aacfc7ae91b816e2b4cbef695245368c4b"
+    );
+    assert_eq!(sha1_records[0]["hash_algorithm"], "git-sha1");
+    assert_eq!(sha1_records[0]["git_object_type"], "tree");
+    assert_eq!(
+        sha1_records[2]["bytes_hash"],
+        "gitsha1:ce013625030ba8dba906f756967f9e9ca394464a"
+    );
+    assert_eq!(sha1_records[2].get("git_object_type"), None);
+
+    let output = run_hashbytes(&["--algorithm", "git-sha256", "--git-tree"], &manifest);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        records(&output)[0]["bytes_hash"],
+        "gitsha256:353142424c8b8c6d9e061a34ffafaa9fd4a3f675d968fa359154b782f8ab8366"
+    );
+
+    let _ = fs::remove_dir_all(&root);
+    let _ = fs::remove_file(&manifest);
+}
+
+#[test]
+fn directory_with_unreadable_file_is_skipped_as_incomplete() {
+    let root = unique_path("incomplete");
+    build_checkout(&root);
+    let missing = root.join("gone.txt");
+    let manifest = unique_path("incomplete-manifest");
+    write_manifest(&manifest, &[&root.join("a.txt"), &missing, &root]);
+
+    let output = run_hashbytes(&["--algorithm", "git-sha1", "--git-tree"], &manifest);
+    assert_eq!(output.status.code(), Some(1));
+    let records = records(&output);
+    assert_eq!(records[1]["_warnings"][0]["code"], "E_IO");
+    assert_eq!(records[2]["_skipped"], true);
+    let warning = &records[2]["_warnings"][0];
+    assert_eq!(warning["code"], "E_GIT_TREE_INCOMPLETE");
+    assert_eq!(
+        warning["detail"]["unresolved"],
+        json!([missing.to_string_lossy()])
+    );
+
+    let _ = fs::remove_dir_all(&root);
+    let _ = fs::remove_file(&manifest);
+}
+
+#[test]
+fn git_tree_requires_a_git_primary_algorithm() {
+    let manifest = unique_path("refusal-manifest");
+    write_manifest(&manifest, &[Path::new("/tmp")]);
+
+    let output = run_hashbytes(&["--algorithm", "sha256,git-sha1", "--git-tree"], &manifest);
+    assert_eq!(output.status.code(), Some(2));
+    let refusal: Value = serde_json::from_slice(&output.stdout).expect("refusal json");
+    assert_eq!(refusal["refusal"]["code"], "E_BAD_INPUT");
+    assert_eq!(refusal["refusal"]["detail"]["flag"], "--git-tree");
+
+    let _ = fs::remove_file(&manifest);
+}
@@ -1,5 +1,5 @@
 use hashbytes::cli::Algorithm;
-use hashbytes::hash::{blake3, compute, sha256};
+use hashbytes::hash::{blake3, compute, sha3_256, sha256, sha384, sha512, xxh3};
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -114,3 +114,121 @@ fn abc_matches_known_vectors() {
 
     let _ = std::fs::remove_file(path);
 }
+
+#[test]
+fn multi_algorithm_pass_matches_single_algorithm_digests() {
+    let mut data = Vec::with_capacity(200 * 1024);
+    while data.len() < 200 * 1024 {
+        data.extend(0_u8..=255);
+    }
+    let path = write_temp_file(&data);
+
+    let digests = compute::hash_file_multi(&path, &[Algorithm::Blake3, Algorithm::Sha256])
+        .expect("multi-algorithm pass");
+    assert_eq!(digests.len(), 2);
+    assert_eq!(digests[0], blake3::hash_file(&path).expect("direct blake3"));
+    assert_eq!(digests[1], sha256::hash_file(&path).expect("direct sha256"));
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn sha384_sha512_and_sha3_256_match_known_abc_vectors() {
+    let path = write_temp_file(b"abc");
+
+    assert_eq!(
+        sha384::hash_file(&path).expect("abc sha384"),
+        "sha384:cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed8086072ba1e7cc2358baeca134c825a7"
+    );
+    assert_eq!(
+        sha512::hash_file(&path).expect("abc sha512"),
+        "sha512:ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+    );
+    assert_eq!(
+        sha3_256::hash_file(&path).expect("abc sha3-256"),
+        "sha3-256:3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532"
+    );
+
+    let digests = compute::hash_file_multi(
+        &path,
+        &[Algorithm::Sha512, Algorithm::Sha384, Algorithm::Sha3_256],
+    )
+    .expect("multi-algorithm pass");
+    for (digest, algorithm) in
+        digests
+            .iter()
+            .zip([Algorithm::Sha512, Algorithm::Sha384, Algorithm::Sha3_256])
+    {
+        assert_eq!(
+            digest,
+            &compute::hash_file(&path, algorithm).expect("dispatch")
+        );
+        let hex = digest
+            .strip_prefix(&format!("{}:", algorithm.prefix()))
+            .expect("algorithm prefix");
+        assert_eq!(hex.len(), algorithm.digest_hex_len());
+        assert!(is_lower_hex(hex));
+    }
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn xxh3_128_matches_known_empty_vector() {
+    let path = write_temp_file(b"");
+
+    let digest = xxh3::hash_file(&path).expect("empty xxh3-128");
+    assert_eq!(digest, "xxh3:99aa06d3014798d86001c324468d497f");
+    assert_eq!(
+        compute::hash_file(&path, Algorithm::Xxh3_128).expect("dispatch xxh3-128"),
+        digest
+    );
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn parallel_blake3_matches_streaming_digest_in_every_mode() {
+    let contents: Vec<u8> = (0..3 * 1024 * 1024 + 17_u32)
+        .map(|i| (i.wrapping_mul(31) >> 3) as u8)
+        .collect();
+    let path = write_temp_file(&contents);
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(4)
+        .build()
+        .expect("build pool");
+
+    let modes = [
+        blake3::Blake3Options::default(),
+        blake3::Blake3Options {
+            mode: blake3::Blake3Mode::Keyed([7; 32]),
+            ..blake3::Blake3Options::default()
+        },
+        blake3::Blake3Options {
+            digest_length: 64,
+            ..blake3::Blake3Options::default()
+        },
+    ];
+    for blake3_options in modes {
+        let streaming = compute::HashOptions {
+            blake3: blake3_options.clone(),
+            ..compute::HashOptions::new(vec![Algorithm::Blake3])
+        };
+        let parallel = compute::HashOptions {
+            parallel_blake3_min_len: Some(1024 * 1024),
+            ..streaming.clone()
+        };
+
+        let expected = compute::hash_file_with_options(&path, &streaming).expect("streaming");
+        let actual = pool
+            .install(|| compute::hash_file_with_options(&path, &parallel))
+            .expect("parallel");
+        assert_eq!(actual.digests, expected.digests);
+        assert_eq!(
+            blake3::hash_file_parallel(&path, &blake3_options).expect("direct parallel"),
+            expected.digests[0]
+        );
+    }
+
+    std::fs::remove_file(path).expect("cleanup temp file");
+}
@@ -0,0 +1,163 @@
+use hashbytes::cli::Algorithm;
+use hashbytes::hash::{Compression, HashOptions, inner::hash_inner};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+const PAYLOAD: &[u8] = b"id,amount\n1,10.00\n2,20.00\n3,30.00\n";
+
+fn unique_path(label: &str) -> PathBuf {
+    let counter = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!(
+        "hash-inner-hash-{}-{label}-{counter}",
+        std::process::id()
+    ))
+}
+
+fn gzip(data: &[u8], mtime: u32) -> Vec<u8> {
+    let mut encoder = flate2::GzBuilder::new()
+        .mtime(mtime)
+        .write(Vec::new(), flate2::Compression::default());
+    encoder.write_all(data).expect("gzip");
+    encoder.finish().expect("finish gzip")
+}
+
+fn compressed(compression: Compression, data: &[u8]) -> Vec<u8> {
+    match compression {
+        Compression::Gzip => gzip(data, 0),
+        Compression::Zstd => zstd::encode_all(data, 3).expect("zstd"),
+        Compression::Xz => {
+            let mut encoder = xz2::write::XzEncoder::new(Vec::new(), 6);
+            encoder.write_all(data).expect("xz");
+            encoder.finish().expect("finish xz")
+        }
+        Compression::Bz2 => {
+            let mut encoder =
+                bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+            encoder.write_all(data).expect("bz2");
+            encoder.finish().expect("finish bz2")
+        }
+    }
+}
+
+fn sha256_prefixed(data: &[u8]) -> String {
+    format!("sha256:{:x}", Sha256::digest(data))
+}
+
+#[test]
+fn every_format_decodes_to_the_payload_digest() {
+    let mut options = HashOptions::new(vec![Algorithm::Sha256]);
+    options.inner_hash = Compression::ALL.to_vec();
+
+    for compression in Compression::ALL {
+        let path = unique_path(compression.name());
+        fs::write(&path, compressed(compression, PAYLOAD)).expect("write compressed");
+        let inner = hash_inner(&path, None, &options)
+            .expect("read")
+            .expect("detected");
+        assert_eq!(inner.compression, compression);
+        assert_eq!(inner.digest, Ok(sha256_prefixed(PAYLOAD)));
+
+        let data = compressed(compression, PAYLOAD);
+        fs::write(&path, &data[..data.len() / 2]).expect("write truncated");
+        let inner = hash_inner(&path, None, &options)
+            .expect("read")
+            .expect("detected");
+        assert!(
+            inner.digest.is_err(),
+            "truncated {compression} must not decode"
+        );
+        let _ = fs::remove_file(&path);
+    }
+}
+
+#[test]
+fn concatenated_gzip_members_decode_in_full() {
+    let mut data = gzip(&PAYLOAD[..10], 1);
+    data.extend(gzip(&PAYLOAD[10..], 2));
+    let path = unique_path("multi");
+    fs::write(&path, data).expect("write");
+
+    let mut options = HashOptions::new(vec![Algorithm::Sha256]);
+    options.inner_hash = vec![Compression::Gzip];
+    let inner = hash_inner(&path, None, &options).expect("read");
+    assert_eq!(
+        inner.map(|inner| inner.digest),
+        Some(Ok(sha256_prefixed(PAYLOAD)))
+    );
+
+    // Formats not enabled are left alone.
+    options.inner_hash = vec![Compression::Zstd];
+    assert_eq!(hash_inner(&path, None, &options).expect("read"), None);
+    let _ = fs::remove_file(&path);
+}
+
+fn write_manifest(path: &Path, paths: &[&Path]) {
+    let mut file = fs::File::create(path).expect("create manifest");
+    for entry in paths {
+        let record = json!({ "version": "vacuum.v0", "path": entry.to_string_lossy() });
+        writeln!(file, "{record}").expect("write manifest line");
+    }
+}
+
+fn run_hashbytes(args: &[&str], manifest: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_hashbytes"))
+        .arg("--no-witness")
+        .args(args)
+        .arg(manifest)
+        .output()
+        .expect("hashbytes should run")
+}
+
+#[test]
+fn regzipped_files_share_inner_hash_and_corrupt_streams_warn() {
+    let first = unique_path("first.csv.gz");
+    let second = unique_path("second.csv.gz");
+    let plain = unique_path("plain.csv");
+    let truncated = unique_path("truncated.csv.gz");
+    fs::write(&first, gzip(PAYLOAD, 1_700_000_000)).expect("write first");
+    fs::write(&second, gzip(PAYLOAD, 1_700_086_400)).expect("write second");
+    fs::write(&plain, PAYLOAD).expect("write plain");
+    let gz = gzip(PAYLOAD, 0);
+    fs::write(&truncated, &gz[..gz.len() - 12]).expect("write truncated");
+
+    let manifest = unique_path("manifest");
+    write_manifest(&manifest, &[&first, &second, &plain, &truncated]);
+    let output = run_hashbytes(&["--inner-hash", "gzip,zstd"], &manifest);
+    assert_eq!(output.status.code(), Some(0));
+    let records: Vec<Value> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("json record"))
+        .collect();
+
+    assert_ne!(records[0]["bytes_hash"], records[1]["bytes_hash"]);
+    assert_eq!(records[0]["inner_bytes_hash"], sha256_prefixed(PAYLOAD));
+    assert_eq!(records[1]["inner_bytes_hash"], sha256_prefixed(PAYLOAD));
+    assert_eq!(records[0]["inner_compression"], "gzip");
+
+    assert_eq!(records[2].get("inner_bytes_hash"), None);
+    assert_eq!(records[2].get("inner_compression"), None);
+
+    assert_eq!(records[3].get("_skipped"), None);
+    assert!(records[3]["bytes_hash"].is_string());
+    assert_eq!(records[3].get("inner_bytes_hash"), None);
+    assert_eq!(records[3]["_warnings"][0]["code"], "E_INNER_DECODE");
+    assert_eq!(records[3]["_warnings"][0]["detail"]["compression"], "gzip");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("corrupt gzip stream"));
+
+    let output = run_hashbytes(
+        &["--algorithm", "git-sha1", "--inner-hash", "gzip"],
+        &manifest,
+    );
+    assert_eq!(output.status.code(), Some(2));
+
+    for path in [first, second, plain, truncated, manifest] {
+        let _ = fs::remove_file(path);
+    }
+}
@@ -0,0 +1,273 @@
+use hashbytes::cli::Algorithm;
+use hashbytes::hash::{HashOptions, hash_file_with_options};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+const CHUNK: usize = 256 * 1024;
+
+fn unique_path(label: &str) -> PathBuf {
+    let counter = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!(
+        "hash-ipfs-cid-{}-{label}-{counter}",
+        std::process::id()
+    ))
+}
+
+fn ipfs_cid(data: &[u8]) -> String {
+    let path = unique_path("data");
+    fs::write(&path, data).expect("write data");
+    let mut options = HashOptions::new(vec![Algorithm::Sha256]);
+    options.ipfs_cid = true;
+    let file_digests = hash_file_with_options(&path, &options).expect("hash");
+    let _ = fs::remove_file(&path);
+    file_digests.ipfs_cid.expect("ipfs cid")
+}
+
+fn base32_cid(bytes: &[u8]) -> String {
+    format!(
+        "b{}",
+        data_encoding::BASE32_NOPAD
+            .encode(bytes)
+            .to_ascii_lowercase()
+    )
+}
+
+/// CIDv1 bytes for a SHA-256 multihash; both codecs fit a one-byte varint.
+fn cid_bytes(codec: u8, block: &[u8]) -> Vec<u8> {
+    let mut bytes = vec![0x01, codec, 0x12, 0x20];
+    bytes.extend_from_slice(&Sha256::digest(block));
+    bytes
+}
+
+#[test]
+fn single_chunk_files_are_raw_leaves() {
+    // `ipfs add --cid-version=1` of an empty file and of "hello world".
+    assert_eq!(
+        ipfs_cid(b""),
+        "bafkreihdwdcefgh4dqkjv67uzcmw7ojee6xedzdetojuzjevtenxquvyku"
+    );
+    assert_eq!(
+        ipfs_cid(b"hello world"),
+        "bafkreifzjut3te2nhyekklss27nh3k72ysco7y32koao5eei66wof36n5e"
+    );
+
+    let full = vec![7u8; CHUNK];
+    assert_eq!(ipfs_cid(&full), base32_cid(&cid_bytes(0x55, &full)));
+}
+
+#[test]
+fn two_chunks_join_under_one_dag_pb_file_node() {
+    let data: Vec<u8> = (0..CHUNK + 1).map(|i| (i % 251) as u8).collect();
+    let (first, second) = data.split_at(CHUNK);
+
+    // PBNode { Links: [{Hash, Name: "", Tsize}; 2], Data: UnixFS { Type:
+    // File, filesize, blocksizes } } with every varint spelled out.
+    let mut node = Vec::new();
+    for (leaf, tsize) in [(first, &[0x80, 0x80, 0x10][..]), (second, &[0x01][..])] {
+        let mut link = vec![0x0a, 0x24];
+        link.extend(cid_bytes(0x55, leaf));
+        link.extend([0x12, 0x00, 0x18]);
+        link.extend(tsize);
+        node.extend([0x12, link.len() as u8]);
+        node.extend(link);
+    }
+    node.extend([
+        0x0a, 0x0c, // Data
+        0x08, 0x02, // Type: File
+        0x18, 0x81, 0x80, 0x10, // filesize: 262145
+        0x20, 0x80, 0x80, 0x10, // blocksizes: 262144
+        0x20, 0x01, // blocksizes: 1
+    ]);
+
+    let cid = ipfs_cid(&data);
+    assert!(cid.starts_with("bafybei"));
+    assert_eq!(cid, base32_cid(&cid_bytes(0x70, &node)));
+    assert_eq!(cid, base32_cid(&reference_cid(&data)));
+}
+
+// No CIDs from a real `ipfs add --cid-version=1 --raw-leaves` are pinned for
+// multi-chunk files yet. Until they are, multi-chunk and multi-level DAGs
+// are checked against the reference below. It is written independently of
+// `hash::ipfs`: it lays the DAG out top-down over the whole chunk list, as
+// go-unixfs `balanced.Layout` does, and has its own protobuf encoder. The
+// streaming builder folds levels bottom-up instead.
+
+const REFERENCE_MAX_LINKS: usize = 174;
+
+struct ReferenceLink {
+    cid: Vec<u8>,
+    tsize: u64,
+    file_len: u64,
+}
+
+/// Root CID bytes of `data` as `ipfs add --cid-version=1 --raw-leaves`
+/// lays it out.
+fn reference_cid(data: &[u8]) -> Vec<u8> {
+    let chunks: Vec<&[u8]> = if data.is_empty() {
+        vec![data]
+    } else {
+        data.chunks(CHUNK).collect()
+    };
+    if chunks.len() == 1 {
+        return cid_bytes(0x55, chunks[0]);
+    }
+
+    // Fill a depth-1 node; while chunks remain, the old root becomes the
+    // first link of a new root whose other links are subtrees of its depth.
+    let mut next = 0;
+    let mut root = reference_fill(&chunks, &mut next, 1);
+    let mut depth = 1;
+    while next < chunks.len() {
+        let mut links = vec![root];
+        while links.len() < REFERENCE_MAX_LINKS && next < chunks.len() {
+            links.push(reference_fill(&chunks, &mut next, depth));
+        }
+        root = reference_node(&links);
+        depth += 1;
+    }
+    root.cid
+}
+
+fn reference_fill(chunks: &[&[u8]], next: &mut usize, depth: u32) -> ReferenceLink {
+    let mut links = Vec::new();
+    while links.len() < REFERENCE_MAX_LINKS && *next < chunks.len() {
+        if depth == 1 {
+            let chunk = chunks[*next];
+            *next += 1;
+            links.push(ReferenceLink {
+                cid: cid_bytes(0x55, chunk),
+                tsize: chunk.len() as u64,
+                file_len: chunk.len() as u64,
+            });
+        } else {
+            links.push(reference_fill(chunks, next, depth - 1));
+        }
+    }
+    reference_node(&links)
+}
+
+/// dag-pb `PBNode { Links, Data: UnixFS File { filesize, blocksizes } }`.
+fn reference_node(links: &[ReferenceLink]) -> ReferenceLink {
+    let file_len: u64 = links.iter().map(|link| link.file_len).sum();
+    let mut unixfs = Vec::new();
+    proto_varint_field(&mut unixfs, 1, 2);
+    proto_varint_field(&mut unixfs, 3, file_len);
+    for link in links {
+        proto_varint_field(&mut unixfs, 4, link.file_len);
+    }
+
+    let mut node = Vec::new();
+    for link in links {
+        let mut pb_link = Vec::new();
+        proto_bytes_field(&mut pb_link, 1, &link.cid);
+        proto_bytes_field(&mut pb_link, 2, b"");
+        proto_varint_field(&mut pb_link, 3, link.tsize);
+        proto_bytes_field(&mut node, 2, &pb_link);
+    }
+    proto_bytes_field(&mut node, 1, &unixfs);
+
+    ReferenceLink {
+        cid: cid_bytes(0x70, &node),
+        tsize: node.len() as u64 + links.iter().map(|link| link.tsize).sum::<u64>(),
+        file_len,
+    }
+}
+
+fn proto_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn proto_varint_field(out: &mut Vec<u8>, field: u64, value: u64) {
+    proto_varint(out, field << 3);
+    proto_varint(out, value);
+}
+
+fn proto_bytes_field(out: &mut Vec<u8>, field: u64, value: &[u8]) {
+    proto_varint(out, (field << 3) | 2);
+    proto_varint(out, value.len() as u64);
+    out.extend_from_slice(value);
+}
+
+/// Deterministic, chunk-distinct content so no two leaves share a CID.
+fn patterned(len: usize) -> Vec<u8> {
+    (0..len)
+        .map(|i| (i.wrapping_mul(2_654_435_761) >> 13) as u8)
+        .collect()
+}
+
+#[test]
+fn multi_chunk_files_match_the_reference_layout() {
+    for len in [2 * CHUNK, 3 * CHUNK + 17] {
+        let data = patterned(len);
+        assert_eq!(ipfs_cid(&data), base32_cid(&reference_cid(&data)), "{len}");
+    }
+}
+
+#[test]
+fn more_than_174_chunks_add_a_dag_level() {
+    // 174 chunks still fit one node; one more byte needs a second level
+    // whose root links the full node and a node holding the last leaf.
+    let full_node = patterned(REFERENCE_MAX_LINKS * CHUNK);
+    assert_eq!(ipfs_cid(&full_node), base32_cid(&reference_cid(&full_node)));
+
+    let mut two_levels = full_node;
+    two_levels.push(0xa5);
+    assert_eq!(
+        ipfs_cid(&two_levels),
+        base32_cid(&reference_cid(&two_levels))
+    );
+
+    // A second subtree of several leaves, the last one partial.
+    let data = patterned((REFERENCE_MAX_LINKS + 2) * CHUNK + 1);
+    assert_eq!(ipfs_cid(&data), base32_cid(&reference_cid(&data)));
+}
+
+fn run_hashbytes(args: &[&str], manifest: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_hashbytes"))
+        .arg("--no-witness")
+        .args(args)
+        .arg(manifest)
+        .output()
+        .expect("hashbytes should run")
+}
+
+#[test]
+fn ipfs_cid_field_is_emitted_on_request() {
+    let data_path = unique_path("data");
+    let manifest_path = unique_path("manifest");
+    fs::write(&data_path, b"hello world").expect("write data");
+    let mut manifest = fs::File::create(&manifest_path).expect("create manifest");
+    writeln!(
+        manifest,
+        "{}",
+        json!({ "version": "vacuum.v0", "path": data_path.to_string_lossy() })
+    )
+    .expect("write manifest");
+
+    let output = run_hashbytes(&["--ipfs-cid", "--algorithm", "blake3"], &manifest_path);
+    assert_eq!(output.status.code(), Some(0));
+    let record: Value = serde_json::from_slice(&output.stdout).expect("record json");
+    assert_eq!(
+        record["ipfs_cid"],
+        "bafkreifzjut3te2nhyekklss27nh3k72ysco7y32koao5eei66wof36n5e"
+    );
+    assert_eq!(record["hash_algorithm"], "blake3");
+
+    let output = run_hashbytes(&[], &manifest_path);
+    let record: Value = serde_json::from_slice(&output.stdout).expect("record json");
+    assert_eq!(record.get("ipfs_cid"), None);
+
+    let _ = fs::remove_file(&data_path);
+    let _ = fs::remove_file(&manifest_path);
+}
@@ -0,0 +1,140 @@
+use hashbytes::cli::Algorithm;
+use hashbytes::hash::{
+    HashOptions, ObjectStoreChecksums, ObjectStoreOptions, hash_file_with_options,
+};
+use md5::{Digest, Md5};
+use serde_json::{Value, json};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+fn unique_path(label: &str) -> PathBuf {
+    let counter = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!(
+        "hash-object-store-{}-{label}-{counter}",
+        std::process::id()
+    ))
+}
+
+fn checksums(data: &[u8], s3_part_size: u64) -> ObjectStoreChecksums {
+    let path = unique_path("data");
+    fs::write(&path, data).expect("write data");
+    let mut options = HashOptions::new(vec![Algorithm::Sha256]);
+    options.object_store = Some(ObjectStoreOptions { s3_part_size });
+    let file_digests = hash_file_with_options(&path, &options).expect("hash");
+    let _ = fs::remove_file(&path);
+    file_digests.object_store.expect("object store checksums")
+}
+
+fn md5_hex(data: &[u8]) -> String {
+    format!("{:x}", Md5::digest(data))
+}
+
+#[test]
+fn crc_check_values_and_empty_object() {
+    let check = checksums(b"123456789", 1024);
+    // CRC-32C check 0xe3069283 and CRC-64/NVME check 0xae8b14860a799888.
+    assert_eq!(check.crc32c, "4waSgw==");
+    assert_eq!(check.crc64nvme, "rosUhgp5mIg=");
+    assert_eq!(check.s3_etag, md5_hex(b"123456789"));
+
+    let empty = checksums(b"", 1024);
+    assert_eq!(empty.s3_etag, "d41d8cd98f00b204e9800998ecf8427e");
+    assert_eq!(empty.crc32c, "AAAAAA==");
+    assert_eq!(empty.crc64nvme, "AAAAAAAAAAA=");
+}
+
+#[test]
+fn s3_etag_switches_to_multipart_at_one_part() {
+    // Below the part size is a single-part upload: plain MD5.
+    assert_eq!(checksums(b"abc", 4).s3_etag, md5_hex(b"abc"));
+    assert_eq!(checksums(b"", 4).s3_etag, md5_hex(b""));
+
+    // The part size is also the multipart threshold, so exactly one part
+    // is a one-part multipart upload.
+    assert_eq!(
+        checksums(b"abcd", 4).s3_etag,
+        format!("{}-1", md5_hex(&Md5::digest(b"abcd")))
+    );
+
+    let mut part_digests = Vec::new();
+    for part in [&b"abcd"[..], b"efgh", b"ij"] {
+        part_digests.extend_from_slice(&Md5::digest(part));
+    }
+    let multipart = checksums(b"abcdefghij", 4);
+    assert_eq!(multipart.s3_etag, format!("{}-3", md5_hex(&part_digests)));
+    assert_eq!(multipart.s3_part_size, 4);
+    assert_eq!(
+        checksums(b"abcdefgh", 4).s3_etag,
+        format!("{}-2", md5_hex(&part_digests[..32]))
+    );
+
+    // One part and one byte: a full part plus a one-byte last part.
+    let mut one_over = Vec::new();
+    one_over.extend_from_slice(&Md5::digest(b"abcd"));
+    one_over.extend_from_slice(&Md5::digest(b"e"));
+    assert_eq!(
+        checksums(b"abcde", 4).s3_etag,
+        format!("{}-2", md5_hex(&one_over))
+    );
+}
+
+fn run_hashbytes(args: &[&str], manifest: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_hashbytes"))
+        .arg("--no-witness")
+        .args(args)
+        .arg(manifest)
+        .output()
+        .expect("hashbytes should run")
+}
+
+#[test]
+fn checksums_sit_in_one_object_and_part_size_is_checked() {
+    let data_path = unique_path("data");
+    let manifest_path = unique_path("manifest");
+    fs::write(&data_path, b"123456789").expect("write data");
+    let mut manifest = fs::File::create(&manifest_path).expect("create manifest");
+    writeln!(
+        manifest,
+        "{}",
+        json!({ "version": "vacuum.v0", "path": data_path.to_string_lossy() })
+    )
+    .expect("write manifest");
+
+    let output = run_hashbytes(&["--s3-part-size", "16MiB"], &manifest_path);
+    assert_eq!(output.status.code(), Some(0));
+    let record: Value = serde_json::from_slice(&output.stdout).expect("record json");
+    assert_eq!(
+        record["object_store_checksums"],
+        json!({
+            "s3_etag": md5_hex(b"123456789"),
+            "s3_part_size": 16 * 1024 * 1024,
+            "crc32c": "4waSgw==",
+            "crc64nvme": "rosUhgp5mIg=",
+        })
+    );
+
+    let output = run_hashbytes(&["--object-store-checksums"], &manifest_path);
+    let record: Value = serde_json::from_slice(&output.stdout).expect("record json");
+    assert_eq!(
+        record["object_store_checksums"]["s3_part_size"],
+        8 * 1024 * 1024
+    );
+
+    let output = run_hashbytes(&[], &manifest_path);
+    let record: Value = serde_json::from_slice(&output.stdout).expect("record json");
+    assert_eq!(record.get("object_store_checksums"), None);
+
+    let output = run_hashbytes(&["--s3-part-size", "1MiB"], &manifest_path);
+    assert_eq!(output.status.code(), Some(2));
+    let refusal: Value = serde_json::from_slice(&output.stdout).expect("refusal json");
+    assert_eq!(refusal["refusal"]["code"], "E_BAD_INPUT");
+    assert_eq!(refusal["refusal"]["detail"]["flag"], "--s3-part-size");
+
+    let _ = fs::remove_file(&data_path);
+    let _ = fs::remove_file(&manifest_path);
+}
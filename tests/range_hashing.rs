@@ -0,0 +1,199 @@
+use hashbytes::cli::Algorithm;
+use hashbytes::hash::range::{RangePastEof, range_past_eof};
+use hashbytes::hash::{ByteRange, HashOptions, hash_file_range_with_options};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+fn unique_path(label: &str) -> PathBuf {
+    let counter = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!(
+        "hash-range-hashing-{}-{label}-{counter}",
+        std::process::id()
+    ))
+}
+
+fn sha256_prefixed(data: &[u8]) -> String {
+    format!("sha256:{:x}", Sha256::digest(data))
+}
+
+#[test]
+fn resolve_fills_open_ended_length_and_rejects_past_eof() {
+    let range = |offset, length| ByteRange { offset, length };
+
+    assert_eq!(range(0, None).resolve(10), Ok(10));
+    assert_eq!(range(4, None).resolve(10), Ok(6));
+    assert_eq!(range(10, None).resolve(10), Ok(0));
+    assert_eq!(range(2, Some(8)).resolve(10), Ok(8));
+    assert_eq!(
+        range(2, Some(9)).resolve(10),
+        Err(RangePastEof {
+            range: range(2, Some(9)),
+            file_len: 10
+        })
+    );
+    assert!(range(11, None).resolve(10).is_err());
+    assert!(range(u64::MAX, Some(1)).resolve(10).is_err());
+}
+
+#[test]
+fn range_hash_covers_only_the_requested_bytes() {
+    let data = b"HEADER--payload bytes that matter--TRAILER";
+    let path = unique_path("data");
+    fs::write(&path, data).expect("write data");
+    let options = HashOptions::new(vec![Algorithm::Sha256]);
+
+    let ranged = hash_file_range_with_options(
+        &path,
+        ByteRange {
+            offset: 8,
+            length: Some(26),
+        },
+        &options,
+    )
+    .expect("hash range");
+    assert_eq!(ranged.digests, vec![sha256_prefixed(&data[8..34])]);
+    assert_eq!(
+        ranged.range,
+        Some(ByteRange {
+            offset: 8,
+            length: Some(26)
+        })
+    );
+
+    let err = hash_file_range_with_options(
+        &path,
+        ByteRange {
+            offset: 40,
+            length: Some(5),
+        },
+        &options,
+    )
+    .expect_err("past EOF");
+    assert_eq!(
+        range_past_eof(&err).map(|past_eof| past_eof.file_len),
+        Some(data.len() as u64)
+    );
+
+    let _ = fs::remove_file(&path);
+}
+
+fn write_manifest(path: &Path, records: &[Value]) {
+    let mut file = fs::File::create(path).expect("create manifest");
+    for record in records {
+        writeln!(file, "{record}").expect("write manifest line");
+    }
+}
+
+fn run_hashbytes(manifest: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_hashbytes"))
+        .arg("--no-witness")
+        .arg(manifest)
+        .output()
+        .expect("hashbytes should run")
+}
+
+#[test]
+fn record_range_fields_are_hashed_echoed_and_checked_against_eof() {
+    let data = b"0123456789abcdef";
+    let data_path = unique_path("cli-data");
+    let manifest_path = unique_path("cli-manifest");
+    fs::write(&data_path, data).expect("write data");
+    let path = data_path.to_string_lossy();
+    write_manifest(
+        &manifest_path,
+        &[
+            json!({ "version": "vacuum.v0", "path": path, "hash_offset": 10 }),
+            json!({ "version": "vacuum.v0", "path": path, "hash_length": 4 }),
+            json!({ "version": "vacuum.v0", "path": path, "hash_offset": 12, "hash_length": 8 }),
+        ],
+    );
+
+    let output = run_hashbytes(&manifest_path);
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8(output.stdout).expect("stdout utf8");
+    let records: Vec<Value> = stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("json record"))
+        .collect();
+
+    assert_eq!(records[0]["bytes_hash"], sha256_prefixed(&data[10..]));
+    assert_eq!(records[0]["hash_offset"], 10);
+    assert_eq!(records[0]["hash_length"], 6);
+
+    assert_eq!(records[1]["bytes_hash"], sha256_prefixed(&data[..4]));
+    assert_eq!(records[1]["hash_offset"], 0);
+    assert_eq!(records[1]["hash_length"], 4);
+
+    assert_eq!(records[2]["_skipped"], true);
+    assert_eq!(records[2]["bytes_hash"], Value::Null);
+    let warning = &records[2]["_warnings"][0];
+    assert_eq!(warning["code"], "E_RANGE_PAST_EOF");
+    assert_eq!(warning["detail"]["file_size"], 16);
+
+    let _ = fs::remove_file(&data_path);
+    let _ = fs::remove_file(&manifest_path);
+}
+
+#[test]
+fn non_integer_range_field_is_refused() {
+    let manifest_path = unique_path("bad-manifest");
+    write_manifest(
+        &manifest_path,
+        &[json!({ "version": "vacuum.v0", "path": "/tmp/x", "hash_offset": -1 })],
+    );
+
+    let output = run_hashbytes(&manifest_path);
+    assert_eq!(output.status.code(), Some(2));
+    let refusal: Value = serde_json::from_slice(&output.stdout).expect("refusal json");
+    assert_eq!(refusal["refusal"]["code"], "E_BAD_INPUT");
+    assert_eq!(refusal["refusal"]["detail"]["field"], "hash_offset");
+
+    let _ = fs::remove_file(&manifest_path);
+}
+
+#[cfg(unix)]
+#[test]
+fn whole_file_hash_reads_to_eof_regardless_of_metadata_size() {
+    let fifo_path = unique_path("fifo");
+    let manifest_path = unique_path("fifo-manifest");
+    let status = Command::new("mkfifo")
+        .arg(&fifo_path)
+        .status()
+        .expect("mkfifo should run");
+    assert!(status.success());
+    let writer = {
+        let fifo_path = fifo_path.clone();
+        std::thread::spawn(move || fs::write(&fifo_path, b"hello").expect("write fifo"))
+    };
+    let proc_version = fs::read("/proc/version").expect("read /proc/version");
+    write_manifest(
+        &manifest_path,
+        &[
+            json!({ "version": "vacuum.v0", "path": fifo_path.to_string_lossy() }),
+            json!({ "version": "vacuum.v0", "path": "/proc/version" }),
+        ],
+    );
+
+    let output = run_hashbytes(&manifest_path);
+    assert_eq!(output.status.code(), Some(0));
+    writer.join().expect("fifo writer");
+    let stdout = String::from_utf8(output.stdout).expect("stdout utf8");
+    let records: Vec<Value> = stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("json record"))
+        .collect();
+
+    assert_eq!(records[0]["bytes_hash"], sha256_prefixed(b"hello"));
+    assert_eq!(records[0]["_warnings"], Value::Null);
+    assert_eq!(records[1]["bytes_hash"], sha256_prefixed(&proc_version));
+
+    let _ = fs::remove_file(&fifo_path);
+    let _ = fs::remove_file(&manifest_path);
+}
@@ -12,6 +12,30 @@ fn base_cli() -> Cli {
         command: None,
         input: None,
         algorithm: "sha256".to_string(),
+        blake3_key_file: None,
+        blake3_context: None,
+        digest_length: None,
+        bao_outboard: None,
+        chunk_size: None,
+        cdc: false,
+        encoding: None,
+        inner_hash: None,
+        git_tree: false,
+        expand_archives: false,
+        object_store_checksums: false,
+        s3_part_size: None,
+        ipfs_cid: false,
+        fsverity: false,
+        sample_hash: false,
+        sample_edge: None,
+        sample_windows: None,
+        sample_window_size: None,
+        dedup_only: false,
+        annotate_duplicates: false,
+        emit_sums: None,
+        baseline: None,
+        changed_only: false,
+        change_retries: None,
         jobs: None,
         no_witness: false,
         progress: false,
@@ -148,6 +172,136 @@ fn binary_all_hashed_returns_exit_zero_and_hash_fields() {
     let _ = fs::remove_file(witness_path);
 }
 
+#[test]
+fn binary_multi_algorithm_emits_primary_and_hashes_object() {
+    let data_path = unique_path("multi-algorithm-data");
+    let manifest_path = unique_path("multi-algorithm-manifest");
+    let witness_path = unique_path("multi-algorithm-witness");
+
+    fs::write(&data_path, b"abc").expect("write fixture file");
+    write_jsonl(
+        &manifest_path,
+        &[json!({
+            "version": "vacuum.v0",
+            "path": data_path.to_string_lossy()
+        })],
+    );
+
+    let output = run_hash_with_witness(
+        &witness_path,
+        &[
+            "--no-witness",
+            "--algorithm",
+            "sha256,blake3",
+            manifest_path.to_str().expect("manifest path utf8"),
+        ],
+    );
+    assert_eq!(output.status.code(), Some(0));
+
+    let rows = parse_jsonl(&output.stdout);
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0]["hash_algorithm"], "sha256");
+    assert_eq!(
+        rows[0]["bytes_hash"],
+        "sha256:ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+    assert_eq!(rows[0]["hashes"]["sha256"], rows[0]["bytes_hash"]);
+    assert_eq!(
+        rows[0]["hashes"]["blake3"],
+        "blake3:6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"
+    );
+
+    let single = run_hash_with_witness(
+        &witness_path,
+        &[
+            "--no-witness",
+            manifest_path.to_str().expect("manifest path utf8"),
+        ],
+    );
+    let single_rows = parse_jsonl(&single.stdout);
+    assert!(single_rows[0].get("hashes").is_none());
+
+    let _ = fs::remove_file(data_path);
+    let _ = fs::remove_file(manifest_path);
+    let _ = fs::remove_file(witness_path);
+}
+
+#[test]
+fn binary_xxh3_records_are_marked_non_cryptographic() {
+    let data_path = unique_path("xxh3-data");
+    let manifest_path = unique_path("xxh3-manifest");
+    let witness_path = unique_path("xxh3-witness");
+
+    fs::write(&data_path, b"").expect("write fixture file");
+    write_jsonl(
+        &manifest_path,
+        &[json!({
+            "version": "vacuum.v0",
+            "path": data_path.to_string_lossy()
+        })],
+    );
+
+    let output = run_hash_with_witness(
+        &witness_path,
+        &[
+            "--no-witness",
+            "--algorithm",
+            "xxh3-128,sha256",
+            manifest_path.to_str().expect("manifest path utf8"),
+        ],
+    );
+    assert_eq!(output.status.code(), Some(0));
+
+    let rows = parse_jsonl(&output.stdout);
+    assert_eq!(rows[0]["hash_algorithm"], "xxh3-128");
+    assert_eq!(
+        rows[0]["bytes_hash"],
+        "xxh3:99aa06d3014798d86001c324468d497f"
+    );
+    assert_eq!(rows[0]["non_cryptographic"], true);
+    assert_eq!(rows[0]["hashes"]["xxh3-128"], rows[0]["bytes_hash"]);
+
+    let sha_primary = run_hash_with_witness(
+        &witness_path,
+        &[
+            "--no-witness",
+            "--algorithm",
+            "sha256,xxh3-128",
+            manifest_path.to_str().expect("manifest path utf8"),
+        ],
+    );
+    let sha_rows = parse_jsonl(&sha_primary.stdout);
+    assert!(sha_rows[0].get("non_cryptographic").is_none());
+
+    let _ = fs::remove_file(data_path);
+    let _ = fs::remove_file(manifest_path);
+    let _ = fs::remove_file(witness_path);
+}
+
+#[test]
+fn binary_duplicate_algorithm_list_is_refused() {
+    let manifest_path = unique_path("duplicate-algorithm-manifest");
+    let witness_path = unique_path("duplicate-algorithm-witness");
+    write_jsonl(&manifest_path, &[]);
+
+    let output = run_hash_with_witness(
+        &witness_path,
+        &[
+            "--no-witness",
+            "--algorithm",
+            "blake3,BLAKE3",
+            manifest_path.to_str().expect("manifest path utf8"),
+        ],
+    );
+    assert_eq!(output.status.code(), Some(2));
+
+    let refusal: Value = serde_json::from_slice(&output.stdout).expect("refusal envelope json");
+    assert_eq!(refusal["refusal"]["code"], "E_BAD_INPUT");
+
+    let _ = fs::remove_file(manifest_path);
+    let _ = fs::remove_file(witness_path);
+}
+
 #[test]
 fn binary_parallel_jobs_match_sequential_output_bytes() {
     let manifest_path = unique_path("jobs-parity-manifest");
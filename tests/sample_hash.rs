@@ -0,0 +1,167 @@
+use hashbytes::cli::Algorithm;
+use hashbytes::hash::sample::sample_file;
+use hashbytes::hash::{ByteRange, HashOptions, SampleOptions};
+use serde_json::{Value, json};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+const KIB: u64 = 1024;
+
+fn unique_path(label: &str) -> PathBuf {
+    let counter = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!(
+        "hash-sample-hash-{}-{label}-{counter}",
+        std::process::id()
+    ))
+}
+
+fn small_sample() -> SampleOptions {
+    SampleOptions {
+        edge_len: KIB,
+        windows: 2,
+        window_len: KIB,
+    }
+}
+
+#[test]
+fn ranges_cover_edges_and_evenly_spaced_windows() {
+    let sample = small_sample();
+    assert_eq!(sample.ranges(4 * KIB), vec![(0, 4 * KIB)]);
+    assert_eq!(sample.ranges(0), vec![(0, 0)]);
+    assert_eq!(
+        sample.ranges(102 * KIB),
+        vec![(0, KIB), (34 * KIB, KIB), (67 * KIB, KIB), (101 * KIB, KIB)]
+    );
+    // Overlapping windows are merged into one read.
+    assert_eq!(
+        sample.ranges(5 * KIB),
+        vec![(0, KIB), (1706, 1707), (4 * KIB, KIB)]
+    );
+}
+
+#[test]
+fn unsampled_bytes_do_not_change_the_sample_hash() {
+    let options = HashOptions::new(vec![Algorithm::Sha256]);
+    let data = vec![0x5a; 100 * KIB as usize];
+    let mut edited = data.clone();
+    edited[50 * KIB as usize] ^= 0xff;
+    let mut resized = data.clone();
+    resized.push(0x5a);
+
+    let digests: Vec<_> = [&data, &edited, &resized]
+        .into_iter()
+        .map(|bytes| {
+            let path = unique_path("data");
+            fs::write(&path, bytes).expect("write data");
+            let digest = sample_file(&path, None, small_sample(), &options).expect("sample");
+            let _ = fs::remove_file(&path);
+            digest
+        })
+        .collect();
+
+    assert!(digests[0].digest.starts_with("sha256:"));
+    assert_eq!(digests[0].sampled_len, 4 * KIB);
+    // This is why sample hashes are never identity.
+    assert_eq!(digests[0].digest, digests[1].digest);
+    assert_ne!(digests[0].digest, digests[2].digest);
+}
+
+#[test]
+fn ranged_samples_echo_the_resolved_range() {
+    let path = unique_path("ranged");
+    fs::write(&path, b"HEADER--payload").expect("write data");
+    let options = HashOptions::new(vec![Algorithm::Blake3]);
+    let range = ByteRange {
+        offset: 8,
+        length: None,
+    };
+    let digest = sample_file(&path, Some(range), small_sample(), &options).expect("sample");
+    assert_eq!(digest.sampled_len, 7);
+    assert_eq!(
+        digest.range,
+        Some(ByteRange {
+            offset: 8,
+            length: Some(7)
+        })
+    );
+    let _ = fs::remove_file(&path);
+}
+
+fn run_hashbytes(args: &[&str], manifest: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_hashbytes"))
+        .arg("--no-witness")
+        .args(args)
+        .arg(manifest)
+        .output()
+        .expect("hashbytes should run")
+}
+
+#[test]
+fn sample_records_are_marked_non_identity() {
+    let data_path = unique_path("data");
+    let manifest_path = unique_path("manifest");
+    fs::write(&data_path, vec![7u8; 100 * KIB as usize]).expect("write data");
+    let mut manifest = fs::File::create(&manifest_path).expect("create manifest");
+    writeln!(
+        manifest,
+        "{}",
+        json!({ "version": "vacuum.v0", "path": data_path.to_string_lossy() })
+    )
+    .expect("write manifest");
+
+    let output = run_hashbytes(
+        &[
+            "--sample-hash",
+            "--sample-edge",
+            "1KiB",
+            "--sample-windows",
+            "2",
+            "--sample-window-size",
+            "1KiB",
+        ],
+        &manifest_path,
+    );
+    assert_eq!(output.status.code(), Some(0));
+    let record: Value = serde_json::from_slice(&output.stdout).expect("record json");
+    assert_eq!(record["bytes_hash"], Value::Null);
+    assert_eq!(record["hash_algorithm"], Value::Null);
+    assert_eq!(record["non_identity"], true);
+    assert_eq!(record.get("_skipped"), None);
+    assert!(
+        record["sample_hash"]
+            .as_str()
+            .is_some_and(|digest| digest.starts_with("sha256:"))
+    );
+    assert_eq!(
+        record["sample_params"],
+        json!({
+            "edge_bytes": 1024,
+            "windows": 2,
+            "window_bytes": 1024,
+            "sampled_bytes": 4096,
+        })
+    );
+
+    for (args, flag) in [
+        (&["--sample-hash", "--cdc"][..], "--sample-hash"),
+        (
+            &["--sample-hash", "--algorithm", "sha256,blake3"][..],
+            "--sample-hash",
+        ),
+        (&["--sample-edge", "1KiB"][..], "--sample-edge"),
+    ] {
+        let output = run_hashbytes(args, &manifest_path);
+        assert_eq!(output.status.code(), Some(2), "{args:?}");
+        let refusal: Value = serde_json::from_slice(&output.stdout).expect("refusal json");
+        assert_eq!(refusal["refusal"]["code"], "E_BAD_INPUT");
+        assert_eq!(refusal["refusal"]["detail"]["flag"], flag);
+    }
+
+    let _ = fs::remove_file(&data_path);
+    let _ = fs::remove_file(&manifest_path);
+}
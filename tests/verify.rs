@@ -0,0 +1,456 @@
+use serde_json::{Value, json};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+fn unique_path(label: &str) -> PathBuf {
+    let counter = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!(
+        "hash-verify-{}-{label}-{counter}",
+        std::process::id()
+    ))
+}
+
+fn run_hashbytes(args: &[&str], manifest: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_hashbytes"))
+        .arg("--no-witness")
+        .args(args)
+        .arg(manifest)
+        .output()
+        .expect("hashbytes should run")
+}
+
+fn parse_records(stdout: &[u8]) -> Vec<Value> {
+    String::from_utf8_lossy(stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("record json"))
+        .collect()
+}
+
+fn verifications(records: &[Value]) -> Vec<Option<&str>> {
+    records
+        .iter()
+        .map(|record| record.get("verification").and_then(Value::as_str))
+        .collect()
+}
+
+fn write_manifest(path: &Path, records: &[Value]) {
+    let mut manifest = fs::File::create(path).expect("create manifest");
+    for record in records {
+        writeln!(manifest, "{record}").expect("write manifest");
+    }
+}
+
+#[test]
+fn records_are_checked_against_the_disk_in_order() {
+    let labels = ["steady", "edited", "deleted", "ranged"];
+    let data_paths: Vec<PathBuf> = labels.iter().map(|label| unique_path(label)).collect();
+    for path in &data_paths {
+        fs::write(path, b"HEADER--payload").expect("write data");
+    }
+    let vacuum_path = unique_path("vacuum");
+    let mut vacuum: Vec<Value> = data_paths
+        .iter()
+        .map(|path| json!({ "version": "vacuum.v0", "path": path.to_string_lossy() }))
+        .collect();
+    vacuum[3]["hash_offset"] = json!(8);
+    write_manifest(&vacuum_path, &vacuum);
+
+    let output = run_hashbytes(&["--algorithm", "blake3"], &vacuum_path);
+    assert_eq!(output.status.code(), Some(0));
+    let hashed_path = unique_path("hashed");
+    fs::write(&hashed_path, &output.stdout).expect("write hashed manifest");
+
+    let output = run_hashbytes(&["verify", "--jobs", "4"], &hashed_path);
+    assert_eq!(output.status.code(), Some(0));
+    let records = parse_records(&output.stdout);
+    assert_eq!(verifications(&records), vec![Some("match"); 4]);
+
+    fs::write(&data_paths[1], b"HEADER--changed").expect("edit data");
+    fs::remove_file(&data_paths[2]).expect("delete data");
+    // Only the hashed range matters to a ranged record.
+    fs::write(&data_paths[3], b"header--payload").expect("edit header");
+    let mut hashed = parse_records(&fs::read(&hashed_path).expect("read manifest"));
+    let dir_path = unique_path("dir");
+    fs::create_dir(&dir_path).expect("create dir");
+    let mut unreadable = hashed[0].clone();
+    unreadable["path"] = json!(dir_path.to_string_lossy());
+    hashed.push(unreadable);
+    hashed.push(json!({
+        "version": "hash.v0",
+        "path": "/never-hashed",
+        "bytes_hash": null,
+        "hash_algorithm": null,
+        "_skipped": true
+    }));
+    write_manifest(&hashed_path, &hashed);
+
+    let output = run_hashbytes(&["verify", "--jobs", "4"], &hashed_path);
+    assert_eq!(output.status.code(), Some(1));
+    let records = parse_records(&output.stdout);
+    assert_eq!(
+        verifications(&records),
+        vec![
+            Some("match"),
+            Some("mismatch"),
+            Some("missing"),
+            Some("match"),
+            Some("unreadable"),
+            None,
+        ]
+    );
+    assert_eq!(records[1]["bytes_hash"], hashed[1]["bytes_hash"]);
+    assert!(
+        records[1]["actual_bytes_hash"]
+            .as_str()
+            .is_some_and(|digest| digest.starts_with("blake3:"))
+    );
+    assert_ne!(records[1]["actual_bytes_hash"], records[1]["bytes_hash"]);
+    assert_eq!(records[2]["_warnings"][0]["code"], "E_IO");
+    assert_eq!(records[2].get("_skipped"), None);
+    assert_eq!(records[5], hashed[5]);
+
+    hashed[0]["hash_algorithm"] = json!("md4");
+    write_manifest(&hashed_path, &hashed[..1]);
+    let output = run_hashbytes(&["verify"], &hashed_path);
+    assert_eq!(output.status.code(), Some(2));
+    let refusal: Value = serde_json::from_slice(&output.stdout).expect("refusal json");
+    assert_eq!(refusal["refusal"]["code"], "E_BAD_INPUT");
+    assert_eq!(refusal["refusal"]["detail"]["field"], "hash_algorithm");
+
+    let output = run_hashbytes(&["--cdc", "verify"], &hashed_path);
+    assert_eq!(output.status.code(), Some(2));
+    let refusal: Value = serde_json::from_slice(&output.stdout).expect("refusal json");
+    assert_eq!(refusal["refusal"]["detail"]["flag"], "verify");
+
+    for path in data_paths.iter().chain([&vacuum_path, &hashed_path]) {
+        let _ = fs::remove_file(path);
+    }
+    let _ = fs::remove_dir(&dir_path);
+}
+
+#[test]
+fn records_with_nothing_to_check_leave_the_outcome_alone() {
+    let data_path = unique_path("plain");
+    fs::write(&data_path, b"plain bytes").expect("write data");
+    let vacuum_path = unique_path("plain-vacuum");
+    write_manifest(
+        &vacuum_path,
+        &[json!({ "version": "vacuum.v0", "path": data_path.to_string_lossy() })],
+    );
+    let output = run_hashbytes(&[], &vacuum_path);
+    assert_eq!(output.status.code(), Some(0));
+    let mut hashed = parse_records(&output.stdout);
+    let archive_hash = hashed[0]["bytes_hash"].clone();
+    hashed.extend([
+        json!({
+            "version": "hash.v0",
+            "path": format!("{}!/inner.txt", data_path.to_string_lossy()),
+            "bytes_hash": format!("sha256:{}", "ab".repeat(32)),
+            "hash_algorithm": "sha256",
+            "parent_bytes_hash": archive_hash,
+        }),
+        json!({
+            "version": "hash.v0",
+            "path": "/data/tree",
+            "bytes_hash": format!("gitsha1:{}", "cd".repeat(20)),
+            "hash_algorithm": "git-sha1",
+            "git_object_type": "tree",
+        }),
+        json!({
+            "version": "hash.v0",
+            "path": data_path.to_string_lossy(),
+            "bytes_hash": null,
+            "hash_algorithm": null,
+            "non_identity": true,
+            "sample_hash": format!("sha256:{}", "ef".repeat(32)),
+        }),
+    ]);
+    let hashed_path = unique_path("nothing-to-check");
+    write_manifest(&hashed_path, &hashed);
+
+    let output = run_hashbytes(&["verify"], &hashed_path);
+    assert_eq!(output.status.code(), Some(0));
+    let records = parse_records(&output.stdout);
+    assert_eq!(
+        verifications(&records),
+        vec![Some("match"), None, None, None]
+    );
+    for (record, original) in records[1..].iter().zip(&hashed[1..]) {
+        assert_eq!(record.get("_warnings"), None);
+        assert_eq!(record["bytes_hash"], original["bytes_hash"]);
+    }
+
+    // An upstream skipped record still makes the run partial.
+    hashed.push(json!({
+        "version": "hash.v0",
+        "path": "/data/gone",
+        "bytes_hash": null,
+        "hash_algorithm": null,
+        "_skipped": true,
+    }));
+    write_manifest(&hashed_path, &hashed);
+    let output = run_hashbytes(&["verify"], &hashed_path);
+    assert_eq!(output.status.code(), Some(1));
+
+    for path in [&data_path, &vacuum_path, &hashed_path] {
+        let _ = fs::remove_file(path);
+    }
+}
+
+#[test]
+fn recorded_chunk_hashes_name_the_changed_chunks() {
+    let data_path = unique_path("chunked");
+    fs::write(&data_path, b"aaaabbbbcc").expect("write data");
+    let vacuum_path = unique_path("vacuum");
+    write_manifest(
+        &vacuum_path,
+        &[json!({ "version": "vacuum.v0", "path": data_path.to_string_lossy() })],
+    );
+    let output = run_hashbytes(&["--chunk-size", "4"], &vacuum_path);
+    let hashed_path = unique_path("hashed");
+    fs::write(&hashed_path, &output.stdout).expect("write hashed manifest");
+
+    fs::write(&data_path, b"aaaaBbbbccc").expect("edit data");
+    let output = run_hashbytes(&["verify"], &hashed_path);
+    assert_eq!(output.status.code(), Some(1));
+    let record: Value = serde_json::from_slice(&output.stdout).expect("record json");
+    assert_eq!(record["verification"], "mismatch");
+    assert_eq!(record["mismatched_chunks"], json!([1, 2]));
+
+    for path in [&data_path, &vacuum_path, &hashed_path] {
+        let _ = fs::remove_file(path);
+    }
+}
+
+#[test]
+fn lockfile_artifacts_report_drift() {
+    let dir = unique_path("locked");
+    fs::create_dir(&dir).expect("create dir");
+    for name in ["steady", "edited", "deleted"] {
+        fs::write(dir.join(name), name).expect("write data");
+    }
+    let listing: Vec<Value> = ["steady", "edited", "deleted"]
+        .iter()
+        .map(|name| json!({ "version": "vacuum.v0", "path": dir.join(name).to_string_lossy() }))
+        .collect();
+    let listing_path = unique_path("listing");
+    write_manifest(&listing_path, &listing);
+    let output = run_hashbytes(&["--algorithm", "blake3"], &listing_path);
+    let mut members = parse_records(&output.stdout);
+    // Lockfiles may leave hash_algorithm to the bytes_hash prefix.
+    members[0]
+        .as_object_mut()
+        .expect("record object")
+        .remove("hash_algorithm");
+    let lock_path = unique_path("dec.lock.json");
+    fs::write(
+        &lock_path,
+        json!({ "version": "lock.v0", "dataset_id": "dec", "members": members }).to_string(),
+    )
+    .expect("write lockfile");
+
+    let output = run_hashbytes(&["verify", "--lockfile"], &lock_path);
+    assert_eq!(output.status.code(), Some(0));
+    let records = parse_records(&output.stdout);
+    assert_eq!(verifications(&records), vec![Some("match"); 3]);
+    assert_eq!(records[0]["hash_algorithm"], "blake3");
+    assert!(records.iter().all(|record| record.get("drift").is_none()));
+
+    fs::write(dir.join("edited"), "changed").expect("edit data");
+    fs::remove_file(dir.join("deleted")).expect("delete data");
+    fs::write(dir.join("new"), "new").expect("write data");
+    let mut listing = listing[..2].to_vec();
+    listing.push(json!({ "version": "vacuum.v0", "path": dir.join("new").to_string_lossy() }));
+    write_manifest(&listing_path, &listing);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_hashbytes"))
+        .arg("--no-witness")
+        .arg("verify")
+        .arg(&listing_path)
+        .arg("--lockfile")
+        .arg(&lock_path)
+        .output()
+        .expect("hashbytes should run");
+    assert_eq!(output.status.code(), Some(1));
+    let records = parse_records(&output.stdout);
+    let drift: Vec<Option<&str>> = records
+        .iter()
+        .map(|record| record.get("drift").and_then(Value::as_str))
+        .collect();
+    assert_eq!(
+        drift,
+        vec![None, Some("changed"), Some("missing"), Some("added")]
+    );
+    assert_eq!(records[3]["path"], listing[2]["path"]);
+    assert_eq!(records[3]["bytes_hash"], Value::Null);
+
+    fs::write(&lock_path, json!({ "version": "lock.v0" }).to_string()).expect("write lockfile");
+    let output = run_hashbytes(&["verify", "--lockfile"], &lock_path);
+    assert_eq!(output.status.code(), Some(2));
+    let refusal: Value = serde_json::from_slice(&output.stdout).expect("refusal json");
+    assert_eq!(refusal["refusal"]["code"], "E_BAD_INPUT");
+
+    let _ = fs::remove_dir_all(&dir);
+    for path in [&listing_path, &lock_path] {
+        let _ = fs::remove_file(path);
+    }
+}
+
+#[test]
+fn keyed_derive_and_xof_records_are_rehashed_in_their_mode() {
+    let data_path = unique_path("partner-data");
+    fs::write(&data_path, b"partner payload").expect("write data");
+    let key_path = unique_path("key");
+    fs::write(&key_path, "ab".repeat(32)).expect("write key");
+    let vacuum_path = unique_path("partner-vacuum");
+    write_manifest(
+        &vacuum_path,
+        &[json!({ "version": "vacuum.v0", "path": data_path.to_string_lossy() })],
+    );
+
+    let key_arg = key_path.to_string_lossy().into_owned();
+    let mut hashed = Vec::new();
+    for args in [
+        vec![
+            "--algorithm",
+            "blake3",
+            "--blake3-key-file",
+            key_arg.as_str(),
+        ],
+        vec!["--algorithm", "blake3", "--blake3-context", "partner v1"],
+        vec!["--algorithm", "blake3", "--digest-length", "48"],
+    ] {
+        let output = run_hashbytes(&args, &vacuum_path);
+        assert_eq!(output.status.code(), Some(0));
+        hashed.extend(parse_records(&output.stdout));
+    }
+    assert_eq!(hashed[0]["hash_algorithm"], "blake3-keyed");
+    assert_eq!(hashed[1]["hash_algorithm"], "blake3-derive");
+    assert_eq!(hashed[2]["hash_algorithm"], "blake3-xof");
+    let hashed_path = unique_path("partner-hashed");
+    write_manifest(&hashed_path, &hashed);
+
+    // Without the key or context only the XOF record can be checked; the
+    // others are unverifiable one by one instead of refusing the run.
+    let output = run_hashbytes(&["verify"], &hashed_path);
+    assert_eq!(output.status.code(), Some(1));
+    let records = parse_records(&output.stdout);
+    assert_eq!(
+        verifications(&records),
+        vec![Some("unverifiable"), Some("unverifiable"), Some("match")]
+    );
+    assert_eq!(records[0]["_warnings"][0]["code"], "E_BAD_INPUT");
+    assert_eq!(
+        records[0]["_warnings"][0]["detail"]["field"],
+        "hash_algorithm"
+    );
+
+    let output = run_hashbytes(&["--blake3-key-file", &key_arg, "verify"], &hashed_path);
+    let records = parse_records(&output.stdout);
+    assert_eq!(
+        verifications(&records),
+        vec![Some("match"), Some("unverifiable"), Some("match")]
+    );
+
+    let output = run_hashbytes(&["--blake3-context", "partner v1", "verify"], &hashed_path);
+    let records = parse_records(&output.stdout);
+    assert_eq!(verifications(&records)[1], Some("match"));
+
+    write_manifest(&hashed_path, &hashed[..1]);
+    let output = run_hashbytes(&["--blake3-key-file", &key_arg, "verify"], &hashed_path);
+    assert_eq!(output.status.code(), Some(0));
+
+    fs::write(&data_path, b"partner payload, edited").expect("edit data");
+    let output = run_hashbytes(&["--blake3-key-file", &key_arg, "verify"], &hashed_path);
+    assert_eq!(output.status.code(), Some(1));
+    let records = parse_records(&output.stdout);
+    assert_eq!(verifications(&records), vec![Some("mismatch")]);
+    assert!(
+        records[0]["actual_bytes_hash"]
+            .as_str()
+            .is_some_and(|digest| digest.starts_with("blake3-keyed:"))
+    );
+
+    let output = run_hashbytes(&["--digest-length", "48", "verify"], &hashed_path);
+    assert_eq!(output.status.code(), Some(2));
+    let refusal: Value = serde_json::from_slice(&output.stdout).expect("refusal json");
+    assert_eq!(refusal["refusal"]["detail"]["flag"], "verify");
+
+    for path in [&data_path, &key_path, &vacuum_path, &hashed_path] {
+        let _ = fs::remove_file(path);
+    }
+}
+
+#[test]
+fn relative_lockfile_paths_resolve_against_the_lockfile() {
+    let dir = unique_path("lock-dir");
+    fs::create_dir(&dir).expect("create dir");
+    fs::write(dir.join("steady"), "steady").expect("write data");
+    let listing_path = unique_path("lock-listing");
+    write_manifest(
+        &listing_path,
+        &[json!({ "version": "vacuum.v0", "path": dir.join("steady").to_string_lossy() })],
+    );
+    let output = run_hashbytes(&["--algorithm", "blake3"], &listing_path);
+    let hashed = parse_records(&output.stdout);
+
+    // Pinned by a path relative to the lockfile; the test runs elsewhere.
+    let lock_path = dir.join("dec.lock.json");
+    let member = json!({ "path": "steady", "bytes_hash": hashed[0]["bytes_hash"] });
+    fs::write(
+        &lock_path,
+        json!({ "version": "lock.v0", "members": [member] }).to_string(),
+    )
+    .expect("write lockfile");
+
+    // A listing that spells the path as pinned matches without relative_path.
+    write_manifest(
+        &listing_path,
+        &[json!({ "version": "vacuum.v0", "path": "steady" })],
+    );
+    let output = Command::new(env!("CARGO_BIN_EXE_hashbytes"))
+        .arg("--no-witness")
+        .arg("verify")
+        .arg(&listing_path)
+        .arg("--lockfile")
+        .arg(&lock_path)
+        .output()
+        .expect("hashbytes should run");
+    assert_eq!(output.status.code(), Some(0));
+    let records = parse_records(&output.stdout);
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0]["verification"], "match");
+    assert_eq!(
+        records[0]["path"],
+        dir.join("steady").to_string_lossy().as_ref()
+    );
+    assert_eq!(records[0]["hash_algorithm"], "blake3");
+    assert_eq!(
+        records[0]["tool_versions"]["hash"],
+        env!("CARGO_PKG_VERSION")
+    );
+
+    // `lock` writes members; any other layout is refused.
+    fs::write(
+        &lock_path,
+        json!({ "version": "lock.v0", "artifacts": [member] }).to_string(),
+    )
+    .expect("write lockfile");
+    let output = run_hashbytes(&["verify", "--lockfile"], &lock_path);
+    assert_eq!(output.status.code(), Some(2));
+    let refusal: Value = serde_json::from_slice(&output.stdout).expect("refusal json");
+    assert_eq!(refusal["refusal"]["code"], "E_BAD_INPUT");
+    assert!(
+        refusal["refusal"]["detail"]["error"]
+            .as_str()
+            .is_some_and(|error| error.contains("members"))
+    );
+
+    let _ = fs::remove_dir_all(&dir);
+    let _ = fs::remove_file(&listing_path);
+}